@@ -0,0 +1,62 @@
+//===------------ vectors.rs --------------------------------------------===//
+//  Copyright 2021, Tesseract Systems, Inc.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//===----------------------------------------------------------------------===//
+
+#[path = "../../../vectors/support.rs"]
+mod support;
+
+use support::roundtrip;
+
+use tesseract_one::envelope::{RequestEnvelope, ResponseEnvelope};
+
+use tesseract_protocol_substrate::{
+    AccountType, GetAccountRequest, GetAccountResponse, SignTransactionRequest,
+    SignTransactionResponse,
+};
+
+#[test]
+fn get_account() {
+    for marker in ["json", "cbor"] {
+        let request: RequestEnvelope<GetAccountRequest> =
+            roundtrip("substrate", &format!("get_account.request.{}", marker));
+        assert_eq!("substrate-v1", request.protocol);
+        assert!(matches!(request.request.account_type, AccountType::Sr25519));
+
+        let response: ResponseEnvelope<GetAccountResponse> =
+            roundtrip("substrate", &format!("get_account.response.{}", marker));
+        let account = response.response.into_result().unwrap();
+        assert_eq!(32, account.public_key.len());
+        assert_eq!("//1", account.path);
+    }
+}
+
+#[test]
+fn sign_transaction() {
+    for marker in ["json", "cbor"] {
+        let request: RequestEnvelope<SignTransactionRequest> =
+            roundtrip("substrate", &format!("sign_transaction.request.{}", marker));
+        assert_eq!("sign_transaction", request.method);
+        assert_eq!("//1", request.request.account_path);
+
+        let response: ResponseEnvelope<SignTransactionResponse> =
+            roundtrip("substrate", &format!("sign_transaction.response.{}", marker));
+        assert_eq!(64, response.response.into_result().unwrap().signature.len());
+
+        let error: ResponseEnvelope<SignTransactionResponse> =
+            roundtrip("substrate", &format!("sign_transaction.error.{}", marker));
+        assert_eq!(Some(3), error.id);
+        assert!(error.response.into_result().is_err());
+    }
+}
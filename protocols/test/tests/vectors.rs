@@ -0,0 +1,45 @@
+//===------------ vectors.rs --------------------------------------------===//
+//  Copyright 2021, Tesseract Systems, Inc.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//===----------------------------------------------------------------------===//
+
+#[path = "../../../vectors/support.rs"]
+mod support;
+
+use support::roundtrip;
+
+use tesseract_one::envelope::{RequestEnvelope, ResponseEnvelope};
+
+use tesseract_protocol_test::{SignTransactionRequest, SignTransactionResponse};
+
+#[test]
+fn sign_transaction() {
+    for marker in ["json", "cbor"] {
+        let request: RequestEnvelope<SignTransactionRequest> =
+            roundtrip("test", &format!("sign_transaction.request.{}", marker));
+        assert_eq!("test", request.protocol);
+        assert_eq!("sign_transaction", request.method);
+        assert_eq!(1, request.id);
+
+        let response: ResponseEnvelope<SignTransactionResponse> =
+            roundtrip("test", &format!("sign_transaction.response.{}", marker));
+        assert_eq!(Some(1), response.id);
+        assert!(response.response.into_result().is_ok());
+
+        let error: ResponseEnvelope<SignTransactionResponse> =
+            roundtrip("test", &format!("sign_transaction.error.{}", marker));
+        assert_eq!(Some(2), error.id);
+        assert!(error.response.into_result().is_err());
+    }
+}
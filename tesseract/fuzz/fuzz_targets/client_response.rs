@@ -0,0 +1,86 @@
+//===------------ client_response.rs --------------------------------------------===//
+//  Copyright 2021, Tesseract Systems, Inc.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//===----------------------------------------------------------------------===//
+
+#![no_main]
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use libfuzzer_sys::fuzz_target;
+use serde::de::IgnoredAny;
+
+use tesseract_one::client::delegate::SingleTransportDelegate;
+use tesseract_one::client::transport::Status;
+use tesseract_one::client::{Connection, ErasedService, Tesseract, Transport};
+use tesseract_one::{Protocol, Result};
+
+#[derive(Clone, Copy)]
+struct FuzzProtocol {}
+
+impl Protocol for FuzzProtocol {
+    fn id(&self) -> String {
+        "fuzz".to_owned()
+    }
+}
+
+//replies to any request with the fuzzer input
+struct FuzzConnection {
+    response: Vec<u8>,
+}
+
+#[async_trait]
+impl Connection for FuzzConnection {
+    async fn send(self: Arc<Self>, _: Vec<u8>) -> Result<()> {
+        Ok(())
+    }
+
+    async fn receive(self: Arc<Self>) -> Result<Vec<u8>> {
+        Ok(self.response.clone())
+    }
+}
+
+struct FuzzTransport {
+    response: Vec<u8>,
+}
+
+#[async_trait]
+impl Transport for FuzzTransport {
+    fn id(&self) -> String {
+        "fuzz".to_owned()
+    }
+
+    async fn status(self: Arc<Self>, _: Box<dyn Protocol>) -> Status {
+        Status::Ready
+    }
+
+    fn connect(&self, _: Box<dyn Protocol>) -> Box<dyn Connection + Sync + Send> {
+        Box::new(FuzzConnection {
+            response: self.response.clone(),
+        })
+    }
+}
+
+fuzz_target!(|data: &[u8]| {
+    let tesseract = Tesseract::new(SingleTransportDelegate::arc()).transport(FuzzTransport {
+        response: data.to_vec(),
+    });
+    let service = tesseract.service(FuzzProtocol {});
+
+    let _ = futures::executor::block_on(
+        service.call::<HashMap<String, String>, IgnoredAny>("method".to_owned(), HashMap::new()),
+    );
+});
@@ -0,0 +1,46 @@
+//===------------ processor.rs --------------------------------------------===//
+//  Copyright 2021, Tesseract Systems, Inc.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//===----------------------------------------------------------------------===//
+
+#![no_main]
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use libfuzzer_sys::fuzz_target;
+use serde::de::IgnoredAny;
+
+use tesseract_one::serialize::Serializer;
+use tesseract_one::service::processor::Processor;
+use tesseract_one::service::{Executor, MethodExecutor, TransportProcessor};
+
+struct FuzzExecutor {}
+
+#[async_trait]
+impl Executor for FuzzExecutor {
+    async fn call(self: Arc<Self>, serializer: Serializer, _: &str, data: &[u8]) -> Vec<u8> {
+        Self::call_method(serializer, data, |_: IgnoredAny| async { Ok(()) }).await
+    }
+}
+
+fuzz_target!(|data: &[u8]| {
+    let processor = Processor::new();
+    processor.add_executor(Box::new(FuzzExecutor {}), "fuzz");
+
+    let response = futures::executor::block_on(Arc::new(processor).process(data));
+
+    //whatever comes in, the dApp must get a readable response
+    assert!(Serializer::read_marker(&response).is_ok());
+});
@@ -0,0 +1,35 @@
+//===------------ serializer.rs --------------------------------------------===//
+//  Copyright 2021, Tesseract Systems, Inc.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//===----------------------------------------------------------------------===//
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use serde::de::IgnoredAny;
+
+use tesseract_one::envelope::{RequestEnvelope, ResponseEnvelope};
+use tesseract_one::serialize::{Limits, Serializer};
+
+fuzz_target!(|data: &[u8]| {
+    let limits = Limits::default();
+
+    let _ = Serializer::read_marker(data);
+    let _ = Serializer::deserialize_marked_limited::<RequestEnvelope<IgnoredAny>>(data, &limits);
+    let _ = Serializer::deserialize_marked_limited::<ResponseEnvelope<IgnoredAny>>(data, &limits);
+
+    for serializer in [Serializer::Json, Serializer::Cbor] {
+        let _ = limits.check(serializer, data);
+    }
+});
@@ -0,0 +1,57 @@
+//===------------ relay.rs --------------------------------------------===//
+//  Copyright 2021, Tesseract Systems, Inc.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//===----------------------------------------------------------------------===//
+
+//The reference relay server for the relay transport.
+//  tesseract-relay [address]    (127.0.0.1:7070 by default)
+
+use std::env;
+use std::process::ExitCode;
+
+use tesseract_one::transports::relay::server::RelayServer;
+
+const DEFAULT_ADDRESS: &str = "127.0.0.1:7070";
+
+fn main() -> ExitCode {
+    let address = env::args()
+        .nth(1)
+        .unwrap_or_else(|| DEFAULT_ADDRESS.to_owned());
+
+    let runtime = match tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+    {
+        Ok(runtime) => runtime,
+        Err(e) => {
+            eprintln!("can't start the runtime: {}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let server = match RelayServer::listen(&address) {
+        Ok(server) => server,
+        Err(e) => {
+            eprintln!("can't listen on {}: {}", address, e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    if let Ok(address) = server.local_addr() {
+        println!("relaying on {}", address);
+    }
+
+    runtime.block_on(server.run());
+    ExitCode::SUCCESS
+}
@@ -16,15 +16,26 @@
 
 use std::pin::Pin;
 use std::sync::Arc;
+#[cfg(feature = "keepalive")]
+use std::sync::Weak;
+#[cfg(feature = "keepalive")]
+use std::time::Duration;
 
-use futures::future::FutureExt;
+use futures::channel::mpsc;
 use futures::lock::Mutex;
-use futures::stream::{Stream, StreamExt};
+use futures::stream::{BoxStream, Stream, StreamExt};
+#[cfg(feature = "keepalive")]
+use futures::future::{self, Either, FutureExt};
+#[cfg(feature = "keepalive")]
+use futures_timer::Delay;
 
 use async_trait::async_trait;
 //use atomic_refcell::AtomicRefCell;
 
-use crate::Result;
+use crate::{Error, ErrorKind, Result};
+
+#[cfg(feature = "keepalive")]
+use crate::keepalive::{KeepAlive, PING};
 
 #[async_trait]
 pub trait Connection {
@@ -32,20 +43,83 @@ pub trait Connection {
     async fn receive(self: Arc<Self>) -> Result<Vec<u8>>;
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConnectionState {
+    Disconnected,
+    Connecting,
+    Connected,
+    Failed(Error),
+}
+
+//The current state and everyone watching it. A watcher gets the current state first, then every change
+struct States {
+    states: std::sync::Mutex<(ConnectionState, Vec<mpsc::UnboundedSender<ConnectionState>>)>,
+}
+
+impl States {
+    fn new() -> Self {
+        Self {
+            states: std::sync::Mutex::new((ConnectionState::Disconnected, Vec::new())),
+        }
+    }
+
+    fn set(&self, state: ConnectionState) {
+        self.update(|_| Some(state));
+    }
+
+    fn update(&self, f: impl FnOnce(&ConnectionState) -> Option<ConnectionState>) {
+        let mut states = self.states.lock().unwrap();
+        let (current, watchers) = &mut *states;
+
+        if let Some(state) = f(current) {
+            watchers.retain(|watcher| watcher.unbounded_send(state.clone()).is_ok());
+            *current = state;
+        }
+    }
+
+    //a connection in use fails with the transport and recovers with the next response
+    fn report<R>(&self, result: &Result<R>) {
+        self.update(|current| match (current, result) {
+            (ConnectionState::Connected, Err(error)) if error.kind == ErrorKind::Transport => {
+                Some(ConnectionState::Failed(error.clone()))
+            }
+            (ConnectionState::Failed(_), Ok(_)) => Some(ConnectionState::Connected),
+            _ => None,
+        });
+    }
+
+    fn current(&self) -> ConnectionState {
+        self.states.lock().unwrap().0.clone()
+    }
+
+    fn watch(&self) -> BoxStream<'static, ConnectionState> {
+        let mut states = self.states.lock().unwrap();
+        let (current, watchers) = &mut *states;
+
+        let (sender, receiver) = mpsc::unbounded();
+        let _ = sender.unbounded_send(current.clone());
+        watchers.push(sender);
+
+        receiver.boxed()
+    }
+}
+
 pub struct CachedConnection<
-    S: Stream<Item = Result<Box<dyn Connection + Send + Sync>>> + Send + Sync,
+    S: Stream<Item = Result<Box<dyn Connection + Send + Sync>>> + Send,
 > {
     cached: Mutex<Option<Arc<dyn Connection + Sync + Send>>>,
     stream: Mutex<Pin<Box<S>>>,
+    states: States,
 }
 
-impl<S: Stream<Item = Result<Box<dyn Connection + Send + Sync>>> + Send + Sync>
+impl<S: Stream<Item = Result<Box<dyn Connection + Send + Sync>>> + Send>
     CachedConnection<S>
 {
     pub fn new(stream: S) -> Self {
         CachedConnection {
             stream: Mutex::new(Box::pin(stream)),
             cached: Mutex::new(None),
+            states: States::new(),
         }
     }
 
@@ -56,21 +130,60 @@ impl<S: Stream<Item = Result<Box<dyn Connection + Send + Sync>>> + Send + Sync>
         return match cached {
             Some(p) => Ok(Arc::clone(&p)),
             None => {
+                self.states.set(ConnectionState::Connecting);
+
                 let mut stream_lock = self.stream.lock().await;
                 let stream = &mut *stream_lock;
-                let new = stream.next().await.unwrap()?; //the stream is neverending, unwrap is fine
+                let new = match stream.next().await.unwrap() { //the stream is neverending, unwrap is fine
+                    Ok(new) => new,
+                    Err(error) => {
+                        //the user declining to select a wallet is not a failure
+                        self.states.set(match error.kind {
+                            ErrorKind::Cancelled => ConnectionState::Disconnected,
+                            _ => ConnectionState::Failed(error.clone()),
+                        });
+                        return Err(error);
+                    }
+                };
 
                 let to_store = Arc::from(new);
                 let result = Arc::clone(&to_store);
                 *lock = Some(to_store);
+                self.states.set(ConnectionState::Connected);
                 Ok(result)
             }
         };
     }
+
+    //the requests already sent keep the connection they were sent over
+    async fn disconnect(&self) {
+        let mut lock = self.cached.lock().await;
+
+        if lock.take().is_some() {
+            self.states.set(ConnectionState::Disconnected);
+        }
+    }
+
+    //the connection in use, unless there is none or it's being made right now
+    #[cfg(feature = "keepalive")]
+    fn current(&self) -> Option<Arc<dyn Connection + Sync + Send>> {
+        self.cached.try_lock().and_then(|cached| cached.clone())
+    }
+
+    //disconnects, if the dead connection is still the one in use
+    #[cfg(feature = "keepalive")]
+    async fn dead(&self, connection: &Arc<dyn Connection + Sync + Send>) {
+        let mut lock = self.cached.lock().await;
+
+        if matches!(&*lock, Some(cached) if Arc::ptr_eq(cached, connection)) {
+            *lock = None;
+            self.states.set(ConnectionState::Disconnected);
+        }
+    }
 }
 
 #[async_trait]
-impl<S: Stream<Item = Result<Box<dyn Connection + Sync + Send>>> + Sync + Send> Connection
+impl<S: Stream<Item = Result<Box<dyn Connection + Sync + Send>>> + Send> Connection
     for CachedConnection<S>
 {
     async fn send(self: Arc<Self>, request: Vec<u8>) -> Result<()> {
@@ -85,29 +198,103 @@ impl<S: Stream<Item = Result<Box<dyn Connection + Sync + Send>>> + Sync + Send>
 #[async_trait]
 pub trait ServiceConnection {
     async fn request(self: Arc<Self>, req: Vec<u8>) -> Result<Vec<u8>>;
+
+    async fn connect(self: Arc<Self>) -> Result<()>;
+    async fn disconnect(self: Arc<Self>);
+    fn state(&self) -> ConnectionState;
+    fn states(&self) -> BoxStream<'static, ConnectionState>;
+
+    #[cfg(feature = "keepalive")]
+    async fn heartbeat(self: Arc<Self>, timeout: Duration);
+}
+
+//pings the connection every interval, as long as the service is alive
+#[cfg(feature = "keepalive")]
+pub(crate) async fn keepalive(connection: Weak<dyn ServiceConnection + Send + Sync>, keepalive: KeepAlive) {
+    loop {
+        Delay::new(keepalive.interval).await;
+
+        match connection.upgrade() {
+            Some(connection) => connection.heartbeat(keepalive.timeout).await,
+            None => return,
+        }
+    }
 }
 
-pub struct QueuedConnection<C: Connection + Send + Sync> {
-    connection: Mutex<Arc<C>>,
+pub struct QueuedConnection<
+    S: Stream<Item = Result<Box<dyn Connection + Send + Sync>>> + Send,
+> {
+    connection: Arc<CachedConnection<S>>,
+    queue: Mutex<()>,
 }
 
-impl<C: Connection + Send + Sync> QueuedConnection<C> {
-    pub fn new(connection: C) -> Self {
+impl<S: Stream<Item = Result<Box<dyn Connection + Send + Sync>>> + Send>
+    QueuedConnection<S>
+{
+    pub fn new(connection: CachedConnection<S>) -> Self {
         QueuedConnection {
-            connection: Mutex::new(Arc::new(connection)),
+            connection: Arc::new(connection),
+            queue: Mutex::new(()),
         }
     }
 }
 
 #[async_trait]
-impl<C: Connection + Send + Sync> ServiceConnection for QueuedConnection<C> {
+impl<S: Stream<Item = Result<Box<dyn Connection + Send + Sync>>> + Send> ServiceConnection
+    for QueuedConnection<S>
+{
     async fn request(self: Arc<Self>, req: Vec<u8>) -> Result<Vec<u8>> {
-        let lock = self.connection.lock();
+        let _queue = self.queue.lock().await;
+
+        //the response comes over the same connection, even if it's disconnected meanwhile
+        let connection = Arc::clone(&self.connection).connection().await?;
+        let response = match Arc::clone(&connection).send(req).await {
+            Ok(()) => connection.receive().await,
+            Err(error) => Err(error),
+        };
 
-        lock.then(|connection| async move {
-            Arc::clone(&*connection).send(req).await?;
-            Arc::clone(&*connection).receive().await
-        })
-        .await
+        self.connection.states.report(&response);
+        response
+    }
+
+    async fn connect(self: Arc<Self>) -> Result<()> {
+        Arc::clone(&self.connection).connection().await.map(|_| ())
+    }
+
+    async fn disconnect(self: Arc<Self>) {
+        self.connection.disconnect().await
+    }
+
+    fn state(&self) -> ConnectionState {
+        self.connection.states.current()
+    }
+
+    fn states(&self) -> BoxStream<'static, ConnectionState> {
+        self.connection.states.watch()
+    }
+
+    //Only the idle connection is pinged, the requests made meanwhile wait for the pong.
+    //Any response proves the wallet alive, even an error one from a wallet without keepalive
+    #[cfg(feature = "keepalive")]
+    async fn heartbeat(self: Arc<Self>, timeout: Duration) {
+        let _queue = match self.queue.try_lock() {
+            Some(queue) => queue,
+            None => return,
+        };
+        let connection = match self.connection.current() {
+            Some(connection) => connection,
+            None => return,
+        };
+
+        let pinged = Arc::clone(&connection);
+        let pong = async move {
+            Arc::clone(&pinged).send(PING.to_vec()).await?;
+            pinged.receive().await
+        };
+
+        match future::select(pong.boxed(), Delay::new(timeout)).await {
+            Either::Left((Ok(_), _)) => self.connection.states.report(&Ok(())),
+            _ => self.connection.dead(&connection).await,
+        }
     }
 }
@@ -20,7 +20,9 @@ use std::sync::Arc;
 
 use async_trait::async_trait;
 
+use super::exclusive::Exclusive;
 use super::transport;
+use super::transport::Endpoint;
 
 use futures::Future;
 use futures::FutureExt;
@@ -29,10 +31,31 @@ use futures::FutureExt;
 pub trait AsyncDelegate {
     fn select_transport_async<'a>(
         self: &Arc<Self>,
-        transports: &HashMap<String, transport::Status>,
+        transports: &'a HashMap<String, transport::Status>,
     ) -> Pin<Box<dyn Future<Output = Option<String>> + Send + Sync + 'a>>
     where
         Self: Sync + 'a;
+
+    //selects among the endpoints by id and status, as select_transport_async does
+    fn select_endpoint_async<'a>(
+        self: &Arc<Self>,
+        endpoints: &'a [Endpoint],
+    ) -> Pin<Box<dyn Future<Output = Option<String>> + Send + Sync + 'a>>
+    where
+        Self: Sync + Send + 'a,
+    {
+        let this = Arc::clone(self);
+
+        let result = async move {
+            let transports = endpoints
+                .iter()
+                .map(|endpoint| (endpoint.id.clone(), endpoint.status.clone()))
+                .collect();
+
+            this.select_transport_async(&transports).await
+        };
+        Box::pin(Exclusive::new(result.boxed()))
+    }
 }
 
 #[async_trait]
@@ -41,6 +64,17 @@ pub trait Delegate {
         &self,
         transports: &HashMap<String, transport::Status>,
     ) -> Option<String>;
+
+    //Override to present every wallet found by the discovering transports (with its name and icon).
+    //By default the endpoints are selected by id and status, as the transports are
+    async fn select_endpoint(&self, endpoints: &[Endpoint]) -> Option<String> {
+        let transports = endpoints
+            .iter()
+            .map(|endpoint| (endpoint.id.clone(), endpoint.status.clone()))
+            .collect();
+
+        self.select_transport(&transports).await
+    }
 }
 
 impl<T> AsyncDelegate for T
@@ -49,7 +83,7 @@ where
 {
     fn select_transport_async<'a>(
         self: &Arc<Self>,
-        transports: &HashMap<String, transport::Status>,
+        transports: &'a HashMap<String, transport::Status>,
     ) -> Pin<Box<dyn Future<Output = Option<String>> + Send + Sync + 'a>>
     where
         Self: Sync + 'a,
@@ -57,17 +91,26 @@ where
         let this = Arc::clone(self);
 
         let result = async move { this.select_transport(transports).await };
-        let boxed = result.boxed();
-        //ugly cast, because of limitations of async in Traits. Can be fixed by a PR to async_trait crate allowing to add Sync marker to the futures
-        unsafe {
-            let fut_raw = Box::into_raw(Pin::into_inner_unchecked(boxed))
-                as *mut (dyn Future<Output = Option<String>> + Sync + Send);
-            Pin::from(Box::from_raw(fut_raw))
-        }
+        Box::pin(Exclusive::new(result.boxed()))
+    }
+
+    fn select_endpoint_async<'a>(
+        self: &Arc<Self>,
+        endpoints: &'a [Endpoint],
+    ) -> Pin<Box<dyn Future<Output = Option<String>> + Send + Sync + 'a>>
+    where
+        Self: Sync + Send + 'a,
+    {
+        let this = Arc::clone(self);
+
+        let result = async move { this.select_endpoint(endpoints).await };
+        Box::pin(Exclusive::new(result.boxed()))
     }
 }
 
 //This delegate has a special purpose - to be used with single transport only to omit the transport selection logic.
+//A discovering transport may offer several wallets, then the only ready one is selected. If more than one
+//is ready the choice is the user's, so the call is cancelled. Please, consider implementing a custom delegate then.
 pub struct SingleTransportDelegate {}
 
 impl SingleTransportDelegate {
@@ -82,7 +125,17 @@ impl Delegate for SingleTransportDelegate {
         &self,
         transports: &HashMap<String, transport::Status>,
     ) -> Option<String> {
-        assert_eq!(1, transports.len(), "Single transport delegate is designed to work only for a single transport. If you want to use more than one transport option, please, consider implementing a custom delegate.");
-        transports.keys().next().map(String::clone)
+        if transports.len() == 1 {
+            return transports.keys().next().cloned();
+        }
+
+        let mut ready = transports
+            .iter()
+            .filter(|(_, status)| **status == transport::Status::Ready);
+
+        match (ready.next(), ready.next()) {
+            (Some((id, _)), None) => Some(id.clone()),
+            _ => None,
+        }
     }
 }
@@ -0,0 +1,42 @@
+//===------------ exclusive.rs --------------------------------------------===//
+//  Copyright 2021, Tesseract Systems, Inc.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//===----------------------------------------------------------------------===//
+
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures::Future;
+
+//Makes the futures of async_trait (Send only) Sync. A future can only be polled through Pin<&mut>,
+//so a shared reference to it gives no access at all, which is what Sync is about (same as std's Exclusive)
+pub(crate) struct Exclusive<F> {
+    future: F,
+}
+
+unsafe impl<F> Sync for Exclusive<F> {}
+
+impl<F> Exclusive<F> {
+    pub(crate) fn new(future: F) -> Self {
+        Self { future }
+    }
+}
+
+impl<F: Future + Unpin> Future for Exclusive<F> {
+    type Output = F::Output;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        Pin::new(&mut self.future).poll(cx)
+    }
+}
@@ -16,13 +16,19 @@
 
 mod connection;
 pub mod delegate;
+mod exclusive;
 mod service;
+pub mod session;
 pub mod tesseract;
 pub mod transport;
 
 pub use self::tesseract::Tesseract;
 pub use connection::Connection;
+pub use connection::ConnectionState;
 pub use delegate::Delegate;
 pub use service::ErasedService;
+pub use service::Lifecycle;
+pub use service::RequestIds;
 pub use service::Service;
+pub use session::SessionStore;
 pub use transport::Transport;
@@ -15,19 +15,20 @@
 //===----------------------------------------------------------------------===//
 
 use std::sync::{
-    atomic::{AtomicU32, Ordering},
+    atomic::{AtomicU64, Ordering},
     Arc,
 };
 
 use async_trait::async_trait;
+use futures::stream::BoxStream;
 use serde::{de::DeserializeOwned, Serialize};
 
-use crate::envelope::{RequestEnvelope, ResponseEnvelope};
-use crate::serialize::Serializer;
+use crate::envelope::{EnvelopeHeader, RequestEnvelope, ResponseEnvelope, VERSION};
+use crate::serialize::{Limits, Serializer};
 use crate::Protocol;
 use crate::{Error, ErrorKind, Result};
 
-use super::connection::ServiceConnection;
+use super::connection::{ConnectionState, ServiceConnection};
 
 pub trait Service: Sync + Send {
     type Protocol;
@@ -35,7 +36,11 @@ pub trait Service: Sync + Send {
     fn protocol(&self) -> &dyn Protocol;
     fn connection(&self) -> Arc<dyn ServiceConnection + Send + Sync>;
     fn serializer(&self) -> &Serializer;
-    fn next_rid(&self) -> u32;
+    //applied to the responses
+    fn limits(&self) -> &Limits {
+        &Limits::DEFAULT
+    }
+    fn next_rid(&self) -> u64;
 }
 
 #[async_trait]
@@ -47,11 +52,80 @@ pub trait ErasedService {
     ) -> Result<Res>;
 }
 
+//The services connect on the first call by default. Connecting explicitly lets the dApp select
+//the wallet (the Delegate is asked now) and show the result before making any calls
+#[async_trait]
+pub trait Lifecycle {
+    async fn connect(self: Arc<Self>) -> Result<()>;
+    //the calls in progress finish over the old connection, the next one connects again
+    async fn disconnect(self: Arc<Self>);
+    fn state(&self) -> ConnectionState;
+    //the current state first, then every change
+    fn states(&self) -> BoxStream<'static, ConnectionState>;
+}
+
+#[async_trait]
+impl<T> Lifecycle for T
+where
+    T: Service + ?Sized,
+{
+    async fn connect(self: Arc<Self>) -> Result<()> {
+        self.connection().connect().await
+    }
+
+    async fn disconnect(self: Arc<Self>) {
+        self.connection().disconnect().await
+    }
+
+    fn state(&self) -> ConnectionState {
+        self.connection().state()
+    }
+
+    fn states(&self) -> BoxStream<'static, ConnectionState> {
+        self.connection().states()
+    }
+}
+
+//All the services of a Tesseract share the ids, so the ones talking over the same wallet link
+//never collide. 64 bits don't wrap in practice, though 0 is skipped if they ever do.
+pub struct RequestIds {
+    next: AtomicU64,
+}
+
+impl RequestIds {
+    pub fn new() -> Self {
+        Self::starting_at(1)
+    }
+
+    //i.e. to go on from where the previous run has stopped
+    pub fn starting_at(first: u64) -> Self {
+        Self {
+            next: AtomicU64::new(first),
+        }
+    }
+
+    pub fn next(&self) -> u64 {
+        loop {
+            let id = self.next.fetch_add(1, Ordering::Relaxed);
+            if id != 0 {
+                return id;
+            }
+        }
+    }
+}
+
+impl Default for RequestIds {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 pub struct ServiceImpl<P: Protocol, C: ServiceConnection + Sync + Send> {
     protocol: P,
     connection: Arc<C>,
-    rid: AtomicU32,
+    rids: Arc<RequestIds>,
     serializer: Serializer,
+    limits: Limits,
 }
 
 impl<P: Protocol, C: ServiceConnection + Send + Sync> ServiceImpl<P, C> {
@@ -59,10 +133,20 @@ impl<P: Protocol, C: ServiceConnection + Send + Sync> ServiceImpl<P, C> {
         ServiceImpl::<P, C> {
             protocol: protocol,
             connection: Arc::new(connection),
-            rid: AtomicU32::new(1),
+            rids: Arc::new(RequestIds::new()),
             serializer: serializer,
+            limits: Limits::default(),
         }
     }
+
+    pub fn limits(self, limits: Limits) -> Self {
+        Self { limits, ..self }
+    }
+
+    //shared with the other services talking over the same wallet link
+    pub fn rids(self, rids: Arc<RequestIds>) -> Self {
+        Self { rids, ..self }
+    }
 }
 
 impl<P: Protocol, C: ServiceConnection + Send + Sync + 'static> Service for ServiceImpl<P, C> {
@@ -80,8 +164,12 @@ impl<P: Protocol, C: ServiceConnection + Send + Sync + 'static> Service for Serv
         &self.serializer
     }
 
-    fn next_rid(&self) -> u32 {
-        self.rid.fetch_add(1, Ordering::Relaxed)
+    fn limits(&self) -> &Limits {
+        &self.limits
+    }
+
+    fn next_rid(&self) -> u64 {
+        self.rids.next()
     }
 }
 
@@ -99,6 +187,7 @@ where
         let serializer = self.serializer();
 
         let request = RequestEnvelope {
+            version: VERSION,
             protocol: self.protocol().id(),
             method: method,
             id: self.next_rid(),
@@ -112,8 +201,12 @@ where
         // let repr = str::from_utf8(&request_data);
 
         let response_data = connection.request(request_data).await?;
-        let (response, _) =
-            Serializer::deserialize_marked::<ResponseEnvelope<Res>>(&response_data)?;
+        let (header, response_serializer) =
+            Serializer::deserialize_marked_limited::<EnvelopeHeader>(&response_data, self.limits())?;
+        header.check()?;
+
+        let response = response_serializer
+            .deserialize::<ResponseEnvelope<Res>>(&response_data[Serializer::marker_len()..])?;
 
         match response.id {
             None => response
@@ -0,0 +1,217 @@
+//===------------ session.rs --------------------------------------------===//
+//  Copyright 2021, Tesseract Systems, Inc.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//===----------------------------------------------------------------------===//
+
+//The sessions remembered between the launches of a dApp, one per protocol.
+//
+//Once the Delegate selects an endpoint, Tesseract saves it into the store and connects to it
+//directly next time, as long as the same transport reports it ready. The wallet identity and
+//the keys (i.e. the pairing credentials) are opaque to Tesseract, the dApp remembers them
+//with Tesseract::remember and gets them back with Tesseract::resume before making the transports.
+
+use std::collections::BTreeMap;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Error, ErrorKind, Result};
+
+use super::transport::Endpoint;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StoredSession {
+    pub protocol: String,
+    //the transport and the endpoint selected by the Delegate, if there was a connection already
+    #[serde(default)]
+    pub transport: Option<String>,
+    #[serde(default)]
+    pub endpoint: Option<String>,
+    //the wallet's identity, i.e. its public key or the name of the endpoint
+    #[serde(default)]
+    pub wallet: Option<String>,
+    #[serde(default)]
+    pub keys: Option<serde_json::Value>,
+    //seconds since the UNIX epoch
+    pub saved_at: u64,
+}
+
+impl StoredSession {
+    pub fn new(protocol: &str) -> Self {
+        Self {
+            protocol: protocol.to_owned(),
+            transport: None,
+            endpoint: None,
+            wallet: None,
+            keys: None,
+            saved_at: now(),
+        }
+    }
+
+    //the endpoint the Delegate selected. The wallet and the keys are kept only if they were for the same one
+    pub(crate) fn selected(
+        previous: Option<Self>,
+        protocol: &str,
+        transport: String,
+        endpoint: &Endpoint,
+    ) -> Self {
+        let previous = previous.filter(|previous| {
+            previous.endpoint.is_none()
+                || (previous.endpoint.as_ref() == Some(&endpoint.id)
+                    && previous.transport.as_ref() == Some(&transport))
+        });
+        let (wallet, keys) = match previous {
+            Some(previous) => (
+                previous.wallet.or_else(|| endpoint.name.clone()),
+                previous.keys,
+            ),
+            None => (endpoint.name.clone(), None),
+        };
+
+        Self {
+            protocol: protocol.to_owned(),
+            transport: Some(transport),
+            endpoint: Some(endpoint.id.clone()),
+            wallet,
+            keys,
+            saved_at: now(),
+        }
+    }
+}
+
+pub trait SessionStore {
+    fn load(&self, protocol: &str) -> Result<Option<StoredSession>>;
+    fn save(&self, session: StoredSession) -> Result<()>;
+    fn list(&self) -> Result<Vec<StoredSession>>;
+    //true if there was a session to remove
+    fn remove(&self, protocol: &str) -> Result<bool>;
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|now| now.as_secs())
+        .unwrap_or_default()
+}
+
+fn io_error(description: &str, error: io::Error) -> Error {
+    Error::new(ErrorKind::Storage, description, error)
+}
+
+type Sessions = BTreeMap<String, StoredSession>;
+
+//Keeps the sessions in a JSON file, written under a temporary name and renamed,
+//so a crash never leaves it half-written. A missing file is an empty store.
+//The file holds the pairing keys, so on unix it's readable by the owner only
+pub struct FileSessionStore {
+    path: PathBuf,
+    lock: Mutex<()>,
+}
+
+impl FileSessionStore {
+    pub fn new<P: AsRef<Path>>(path: P) -> Self {
+        Self {
+            path: path.as_ref().to_owned(),
+            lock: Mutex::new(()),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn read(&self) -> Result<Sessions> {
+        match fs::read(&self.path) {
+            Ok(data) => serde_json::from_slice(&data).map_err(|e| {
+                Error::new(
+                    ErrorKind::Serialization,
+                    "the session store is corrupted",
+                    e,
+                )
+            }),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Sessions::new()),
+            Err(e) => Err(io_error("can't read the session store", e)),
+        }
+    }
+
+    fn write(&self, sessions: &Sessions) -> Result<()> {
+        let data = serde_json::to_vec_pretty(sessions)
+            .map_err(|e| Error::new(ErrorKind::Serialization, "can't serialize the sessions", e))?;
+
+        let mut temporary = self.path.clone().into_os_string();
+        temporary.push(".tmp");
+
+        Self::create(Path::new(&temporary))
+            .and_then(|mut file| file.write_all(&data).and_then(|_| file.sync_all()))
+            .and_then(|_| fs::rename(&temporary, &self.path))
+            .map_err(|e| io_error("can't write the session store", e))
+    }
+
+    //the mode applies only to a new file, so a leftover one is removed first
+    fn create(path: &Path) -> io::Result<fs::File> {
+        match fs::remove_file(path) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e),
+            _ => (),
+        }
+
+        let mut options = OpenOptions::new();
+        options.write(true).create_new(true);
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+
+        options.open(path)
+    }
+
+    fn update<R>(&self, f: impl FnOnce(&mut Sessions) -> R) -> Result<R> {
+        let _guard = self.lock.lock().unwrap();
+
+        let mut sessions = self.read()?;
+        let result = f(&mut sessions);
+        self.write(&sessions)?;
+
+        Ok(result)
+    }
+}
+
+impl SessionStore for FileSessionStore {
+    fn load(&self, protocol: &str) -> Result<Option<StoredSession>> {
+        let _guard = self.lock.lock().unwrap();
+
+        Ok(self.read()?.remove(protocol))
+    }
+
+    fn save(&self, session: StoredSession) -> Result<()> {
+        self.update(|sessions| {
+            sessions.insert(session.protocol.clone(), session);
+        })
+    }
+
+    fn list(&self) -> Result<Vec<StoredSession>> {
+        let _guard = self.lock.lock().unwrap();
+
+        Ok(self.read()?.into_values().collect())
+    }
+
+    fn remove(&self, protocol: &str) -> Result<bool> {
+        self.update(|sessions| sessions.remove(protocol).is_some())
+    }
+}
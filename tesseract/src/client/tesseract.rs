@@ -19,23 +19,33 @@ use std::sync::Arc;
 
 use futures::future;
 use futures::future::FutureExt;
+#[cfg(feature = "keepalive")]
+use futures::future::BoxFuture;
 use futures::stream;
 use futures::stream::Stream;
 
-use crate::serialize::Serializer;
+#[cfg(feature = "keepalive")]
+use crate::keepalive::{KeepAlive, Spawn};
+use crate::serialize::{Limits, Serializer};
 use crate::Protocol;
-use crate::{Result, ResultDefs};
+use crate::{Error, ErrorKind, Result, ResultDefs};
 
 use super::connection::{CachedConnection, Connection, QueuedConnection, ServiceConnection};
 use super::delegate::AsyncDelegate;
 use super::delegate::Delegate;
-use super::service::{Service, ServiceImpl};
-use super::transport::Transport;
+use super::service::{RequestIds, Service, ServiceImpl};
+use super::session::{SessionStore, StoredSession};
+use super::transport::{Status, Transport};
 
 pub struct Tesseract {
     delegate: Arc<dyn Delegate + Sync + Send + 'static>,
     serializer: Serializer,
+    limits: Limits,
+    rids: Arc<RequestIds>,
     transports: Vec<Arc<dyn Transport + Sync + Send>>,
+    sessions: Option<Arc<dyn SessionStore + Sync + Send>>,
+    #[cfg(feature = "keepalive")]
+    keepalive: Option<(KeepAlive, Spawn)>,
 }
 
 impl Tesseract {
@@ -43,7 +53,12 @@ impl Tesseract {
         Tesseract {
             delegate: delegate,
             serializer: serializer,
+            limits: Limits::default(),
+            rids: Arc::new(RequestIds::new()),
             transports: Vec::new(),
+            sessions: None,
+            #[cfg(feature = "keepalive")]
+            keepalive: None,
         }
     }
 
@@ -56,9 +71,78 @@ impl Tesseract {
         tr.push(Arc::new(transport));
 
         Tesseract {
-            delegate: self.delegate,
-            serializer: self.serializer,
             transports: tr,
+            ..self
+        }
+    }
+
+    //limits applied to the responses coming from the wallet
+    pub fn limits(self, limits: Limits) -> Self {
+        Tesseract { limits, ..self }
+    }
+
+    //remembers the endpoints selected by the Delegate, so the next launches connect to them without asking
+    pub fn session_store<S: SessionStore + Sync + Send + 'static>(self, store: S) -> Self {
+        Tesseract {
+            sessions: Some(Arc::new(store)),
+            ..self
+        }
+    }
+
+    //pings the idle connections of the services made after this, the tasks doing it are run with spawn
+    #[cfg(feature = "keepalive")]
+    pub fn keepalive<F: Fn(BoxFuture<'static, ()>) + Send + Sync + 'static>(
+        self,
+        keepalive: KeepAlive,
+        spawn: F,
+    ) -> Self {
+        Tesseract {
+            keepalive: Some((keepalive, Arc::new(spawn))),
+            ..self
+        }
+    }
+
+    //the session stored for the protocol, i.e. to make the transports with its keys
+    pub fn resume<P: Protocol>(&self, protocol: P) -> Result<Option<StoredSession>> {
+        match &self.sessions {
+            Some(sessions) => sessions.load(&protocol.id()),
+            None => Ok(None),
+        }
+    }
+
+    //stores the wallet identity and the keys of the protocol's session, keeping the selected endpoint
+    pub fn remember<P: Protocol>(
+        &self,
+        protocol: P,
+        wallet: Option<String>,
+        keys: Option<serde_json::Value>,
+    ) -> Result<()> {
+        let sessions = self.sessions.as_ref().ok_or_else(|| {
+            Error::described(ErrorKind::Weird, "no session store to remember the session in")
+        })?;
+
+        let id = protocol.id();
+        let session = sessions.load(&id)?.unwrap_or_else(|| StoredSession::new(&id));
+
+        sessions.save(StoredSession {
+            wallet,
+            keys,
+            ..session
+        })
+    }
+
+    pub fn sessions(&self) -> Result<Vec<StoredSession>> {
+        match &self.sessions {
+            Some(sessions) => sessions.list(),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    //forgets the session, the next connection goes through the Delegate again
+    pub fn revoke<P: Protocol>(&self, protocol: P) -> Result<bool> {
+        match &self.sessions {
+            Some(sessions) => sessions.remove(&protocol.id()),
+            None => Ok(false),
         }
     }
 }
@@ -67,45 +151,85 @@ impl Tesseract {
     pub fn service<P: Protocol + Copy + 'static>(&self, r#for: P) -> Arc<impl Service<Protocol = P>> {
         let service_connection = self.conn_service(r#for);
 
-        Arc::new(ServiceImpl::new(
-            r#for,
-            self.serializer,
-            service_connection,
-        ))
+        let service = Arc::new(
+            ServiceImpl::new(r#for, self.serializer, service_connection)
+                .limits(self.limits)
+                .rids(Arc::clone(&self.rids)),
+        );
+
+        #[cfg(feature = "keepalive")]
+        if let Some((keepalive, spawn)) = &self.keepalive {
+            let connection = Arc::downgrade(&service.connection());
+            spawn(super::connection::keepalive(connection, *keepalive).boxed());
+        }
+
+        service
     }
 
     fn conn_stream<P: Protocol + Copy + 'static>(
         &self, protocol: P
-    ) -> impl Stream<Item = Result<Box<dyn Connection + Sync + Send>>> + Sync + Send {
+    ) -> impl Stream<Item = Result<Box<dyn Connection + Sync + Send>>> + Send {
         let transports: Vec<_> = self.transports.iter().map(|t| Arc::clone(t)).collect();
 
         let delegate = Arc::clone(&self.delegate);
+        let sessions = self.sessions.clone();
 
         stream::unfold(
-            (delegate, transports),
-            move |(delegate, transports)| async move {
-                let statuses = future::join_all(transports.iter().map(move |t| {
-                    let id = t.id();
-                    
+            (delegate, transports, sessions),
+            move |(delegate, transports, sessions)| async move {
+                let endpoints = future::join_all(transports.iter().map(move |t| {
                     let pboxed = Box::new(protocol);
 
-                    Arc::clone(t).status_plus_sync(pboxed).map(|check| (id, check))
+                    Arc::clone(t).endpoints(pboxed).map(move |endpoints| (t, endpoints))
                 }));
-                let statuses = statuses.await.into_iter().collect::<HashMap<_, _>>();
-
-                match delegate.select_transport_async(&statuses).await {
-                    None => Some((Result::CANCELLED, (delegate, transports))),
-                    Some(transport_id) => {
-                        let transports_map: HashMap<_, _> =
-                            transports.iter().map(|t| (t.id(), t)).collect();
+                let endpoints = endpoints.await;
+
+                let transports_map: HashMap<_, _> = endpoints
+                    .iter()
+                    .flat_map(|(t, endpoints)| endpoints.iter().map(move |e| (e.id.clone(), *t)))
+                    .collect();
+                let endpoints: Vec<_> = endpoints.into_iter().flat_map(|(_, e)| e).collect();
+
+                //the store failing must not stop the dApp, it's asked again then
+                let stored = sessions
+                    .as_ref()
+                    .and_then(|sessions| sessions.load(&protocol.id()).ok().flatten());
+                let resumed = stored.as_ref().and_then(|stored| {
+                    endpoints
+                        .iter()
+                        .find(|e| Some(&e.id) == stored.endpoint.as_ref() && e.status == Status::Ready)
+                        .filter(|e| Some(transports_map[&e.id].id()) == stored.transport)
+                        .map(|e| e.id.clone())
+                });
+
+                let selected = match resumed {
+                    Some(endpoint_id) => Some(endpoint_id),
+                    None => {
+                        let selected = delegate.select_endpoint_async(&endpoints).await;
+                        let endpoint = selected
+                            .as_ref()
+                            .and_then(|id| endpoints.iter().find(|e| &e.id == id));
+
+                        if let (Some(sessions), Some(endpoint)) = (&sessions, endpoint) {
+                            let transport = transports_map[&endpoint.id].id();
+                            let session = StoredSession::selected(stored, &protocol.id(), transport, endpoint);
+                            let _ = sessions.save(session);
+                        }
+
+                        selected
+                    }
+                };
 
-                        let connection = match transports_map.get(&transport_id) {
+                match selected {
+                    None => Some((Result::CANCELLED, (delegate, transports, sessions))),
+                    Some(endpoint_id) => {
+                        let connection = match transports_map.get(&endpoint_id) {
                             Some(transport) =>
-                                transport.connect(Box::new(protocol)),
-                            None => panic!("Unable to find transport: {}", transport_id),
+                                transport.connect_endpoint(&endpoint_id, Box::new(protocol)),
+                            None => panic!("Unable to find endpoint: {}", endpoint_id),
                         };
 
-                        Some((Ok(connection), (delegate, transports)))
+                        Some((Ok(connection), (delegate, transports, sessions)))
                     }
                 }
             },
@@ -117,6 +241,6 @@ impl Tesseract {
     }
 
     pub fn conn_service<P: Protocol + Copy + 'static>(&self, protocol: P) -> impl ServiceConnection {
-        QueuedConnection::new(self.conn_chached(protocol))
+        QueuedConnection::new(CachedConnection::new(self.conn_stream(protocol)))
     }
 }
@@ -19,12 +19,12 @@ use std::sync::Arc;
 
 use async_trait::async_trait;
 use futures::Future;
-use futures::FutureExt;
 
 use crate::Protocol;
 use crate::Error;
 
 use super::connection::Connection;
+use super::exclusive::Exclusive;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Status {
@@ -33,12 +33,50 @@ pub enum Status {
     Error(Error),
 }
 
+//A wallet the dApp can connect to. Most of the transports reach a single wallet and report themselves,
+//while the discovering ones report every wallet found. The id must be unique among all the transports
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Endpoint {
+    pub id: String,
+    pub name: Option<String>,
+    pub icon: Option<String>,
+    pub status: Status,
+}
+
+impl Endpoint {
+    pub fn new(id: String, status: Status) -> Self {
+        Self {
+            id,
+            name: None,
+            icon: None,
+            status,
+        }
+    }
+}
+
 #[async_trait]
 pub trait Transport {
     fn id(&self) -> String;
     async fn status(self: Arc<Self>, protocol: Box<dyn Protocol>) -> Status;
 
     fn connect(&self, protocol: Box<dyn Protocol>) -> Box<dyn Connection + Sync + Send>;
+
+    async fn endpoints(self: Arc<Self>, protocol: Box<dyn Protocol>) -> Vec<Endpoint>
+    where
+        Self: Sync + Send + 'static,
+    {
+        let id = self.id();
+        vec![Endpoint::new(id, self.status(protocol).await)]
+    }
+
+    //endpoint is the id of one of the endpoints reported by the transport
+    fn connect_endpoint(
+        &self,
+        _endpoint: &str,
+        protocol: Box<dyn Protocol>,
+    ) -> Box<dyn Connection + Sync + Send> {
+        self.connect(protocol)
+    }
 }
 
 impl dyn Transport + Send + Sync + 'static {
@@ -48,13 +86,6 @@ impl dyn Transport + Send + Sync + 'static {
     where
         Self: 'a,
     {
-        let result = self.status(protocol);
-        let boxed = result.boxed();
-        //ugly cast, because of limitations of async in Traits. Can be fixed by a PR to async_trait crate allowing to add Sync marker to the futures
-        unsafe {
-            let fut_raw = Box::into_raw(Pin::into_inner_unchecked(boxed))
-                as *mut (dyn Future<Output = Status> + Sync + Send);
-            Pin::from(Box::from_raw(fut_raw))
-        }
+        Box::pin(Exclusive::new(self.status(protocol)))
     }
 }
@@ -0,0 +1,158 @@
+//===------------ client.rs --------------------------------------------===//
+//  Copyright 2021, Tesseract Systems, Inc.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//===----------------------------------------------------------------------===//
+
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+
+use crate::Protocol;
+use crate::Result;
+
+use crate::client::transport::{Endpoint, Status};
+use crate::client::Connection;
+use crate::client::Transport;
+
+use super::{handshake, offer, Algorithm, Config};
+
+//what the handshake has shown
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Negotiation {
+    Pending,
+    Done(Option<Algorithm>),
+}
+
+struct CompressedConnection {
+    connection: Arc<dyn Connection + Sync + Send>,
+    config: Config,
+    negotiation: Mutex<Negotiation>,
+}
+
+impl CompressedConnection {
+    fn new(connection: Box<dyn Connection + Sync + Send>, config: Config) -> Self {
+        let negotiation = if config.algorithms.is_empty() {
+            Negotiation::Done(None)
+        } else {
+            Negotiation::Pending
+        };
+
+        Self {
+            connection: Arc::from(connection),
+            config,
+            negotiation: Mutex::new(negotiation),
+        }
+    }
+
+    //a failed handshake is retried with the next big request
+    async fn negotiate(self: Arc<Self>) -> Result<Option<Algorithm>> {
+        if let Negotiation::Done(algorithm) = *self.negotiation.lock().unwrap() {
+            return Ok(algorithm);
+        }
+
+        Arc::clone(&self.connection)
+            .send(offer(&self.config.algorithms))
+            .await?;
+        let answer = Arc::clone(&self.connection).receive().await?;
+
+        //anything else is an error of a wallet that doesn't know the handshake
+        let algorithm = handshake(&answer).and_then(|picked| {
+            picked
+                .into_iter()
+                .find(|a| self.config.algorithms.contains(a))
+        });
+
+        *self.negotiation.lock().unwrap() = Negotiation::Done(algorithm);
+        Ok(algorithm)
+    }
+}
+
+#[async_trait]
+impl Connection for CompressedConnection {
+    async fn send(self: Arc<Self>, request: Vec<u8>) -> Result<()> {
+        let request = if request.len() >= self.config.threshold {
+            match Arc::clone(&self).negotiate().await? {
+                Some(algorithm) => algorithm.compress(&request)?,
+                None => request,
+            }
+        } else {
+            request
+        };
+
+        Arc::clone(&self.connection).send(request).await
+    }
+
+    async fn receive(self: Arc<Self>) -> Result<Vec<u8>> {
+        let response = Arc::clone(&self.connection).receive().await?;
+
+        match Algorithm::read_marker(&response) {
+            Some((algorithm, data)) => algorithm.decompress(data, self.config.limit),
+            None => Ok(response),
+        }
+    }
+}
+
+pub struct CompressedTransport<T: Transport> {
+    transport: Arc<T>,
+    config: Config,
+}
+
+impl<T: Transport> CompressedTransport<T> {
+    pub fn new(transport: T) -> Self {
+        Self::new_with_config(transport, Config::default())
+    }
+
+    pub fn new_with_config(transport: T, config: Config) -> Self {
+        Self {
+            transport: Arc::new(transport),
+            config,
+        }
+    }
+}
+
+#[async_trait]
+impl<T: Transport + Sync + Send> Transport for CompressedTransport<T> {
+    fn id(&self) -> String {
+        self.transport.id()
+    }
+
+    async fn status(self: Arc<Self>, protocol: Box<dyn Protocol>) -> Status {
+        Arc::clone(&self.transport).status(protocol).await
+    }
+
+    fn connect(&self, protocol: Box<dyn Protocol>) -> Box<dyn Connection + Sync + Send> {
+        Box::new(CompressedConnection::new(
+            self.transport.connect(protocol),
+            self.config.clone(),
+        ))
+    }
+
+    async fn endpoints(self: Arc<Self>, protocol: Box<dyn Protocol>) -> Vec<Endpoint>
+    where
+        Self: 'static,
+    {
+        Arc::clone(&self.transport).endpoints(protocol).await
+    }
+
+    fn connect_endpoint(
+        &self,
+        endpoint: &str,
+        protocol: Box<dyn Protocol>,
+    ) -> Box<dyn Connection + Sync + Send> {
+        Box::new(CompressedConnection::new(
+            self.transport.connect_endpoint(endpoint, protocol),
+            self.config.clone(),
+        ))
+    }
+}
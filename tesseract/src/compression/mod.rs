@@ -0,0 +1,174 @@
+//===------------ mod.rs --------------------------------------------===//
+//  Copyright 2021, Tesseract Systems, Inc.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//===----------------------------------------------------------------------===//
+
+//Transparent compression of marked messages.
+//
+//A compressed message is framed with its own 4 byte marker (i.e. "defl") followed by
+//the compressed marked message, so it can never be confused with a plain "json" or "cbor" one.
+//Before the first request big enough to be compressed, the client sends a handshake:
+//  cmpr<names of the algorithms it supports, comma separated, the preferred first>
+//and the service answers with the one it picked (or none):
+//  cmpr<name>
+//A wallet without the support answers the handshake with an error, so the client keeps
+//sending plain requests on that connection. The responses are compressed only for compressed requests.
+
+#[cfg(feature = "client")]
+pub mod client;
+
+#[cfg(feature = "service")]
+pub mod service;
+
+use std::io::{Read, Write};
+
+use crate::serialize::Serializer;
+use crate::{Error, ErrorKind, Result};
+
+pub const HANDSHAKE_MARKER: &str = "cmpr";
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Algorithm {
+    Deflate,
+}
+
+impl Algorithm {
+    pub fn all() -> Vec<Self> {
+        vec![Self::Deflate]
+    }
+
+    #[inline]
+    pub fn marker(&self) -> &'static str {
+        match self {
+            Self::Deflate => "defl",
+        }
+    }
+
+    #[inline]
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Deflate => "deflate",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::all().into_iter().find(|a| a.name() == name)
+    }
+
+    //None means the data is not compressed (i.e. it's a plain marked message)
+    pub fn read_marker(from: &[u8]) -> Option<(Self, &[u8])> {
+        let marker = from.get(0..Serializer::marker_len())?;
+
+        Self::all()
+            .into_iter()
+            .find(|a| a.marker().as_bytes() == marker)
+            .map(|a| (a, &from[Serializer::marker_len()..]))
+    }
+
+    //returns the compressed data prefixed with the algorithm marker
+    pub fn compress(&self, data: &[u8]) -> Result<Vec<u8>> {
+        let mut result = Vec::with_capacity(data.len() / 2 + Serializer::marker_len());
+        result.extend_from_slice(self.marker().as_bytes());
+
+        match self {
+            Self::Deflate => {
+                let mut encoder =
+                    flate2::write::DeflateEncoder::new(result, flate2::Compression::default());
+                encoder
+                    .write_all(data)
+                    .and_then(|_| encoder.finish())
+                    .map_err(|e| Error::new(ErrorKind::Serialization, "can't deflate message", e))
+            }
+        }
+    }
+
+    //expects the data without the marker. Fails if the result is bigger than the limit
+    pub fn decompress(&self, data: &[u8], limit: usize) -> Result<Vec<u8>> {
+        let mut result = Vec::new();
+
+        let read = match self {
+            Self::Deflate => flate2::read::DeflateDecoder::new(data)
+                .take(limit as u64 + 1)
+                .read_to_end(&mut result)
+                .map_err(|e| Error::new(ErrorKind::Serialization, "can't inflate message", e))?,
+        };
+
+        if read > limit {
+            Err(Error::described(
+                ErrorKind::Serialization,
+                &format!("decompressed message exceeds the limit of {} bytes", limit),
+            ))
+        } else {
+            Ok(result)
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Config {
+    pub algorithms: Vec<Algorithm>, //in the order of preference
+    pub threshold: usize,           //messages smaller than this are sent uncompressed
+    pub limit: usize,               //max size of a decompressed message
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            algorithms: Algorithm::all(),
+            threshold: 1024,
+            limit: 64 * 1024 * 1024,
+        }
+    }
+}
+
+impl Config {
+    pub fn threshold(self, threshold: usize) -> Self {
+        Self { threshold, ..self }
+    }
+
+    pub fn limit(self, limit: usize) -> Self {
+        Self { limit, ..self }
+    }
+
+    pub fn algorithms(self, algorithms: Vec<Algorithm>) -> Self {
+        Self { algorithms, ..self }
+    }
+}
+
+#[cfg(feature = "client")]
+pub(crate) fn offer(algorithms: &[Algorithm]) -> Vec<u8> {
+    let names: Vec<&str> = algorithms.iter().map(|a| a.name()).collect();
+    format!("{}{}", HANDSHAKE_MARKER, names.join(",")).into_bytes()
+}
+
+//the algorithms of a handshake message, None if it's not one
+#[cfg(any(feature = "client", feature = "service"))]
+pub(crate) fn handshake(message: &[u8]) -> Option<Vec<Algorithm>> {
+    let names = message.strip_prefix(HANDSHAKE_MARKER.as_bytes())?;
+    let names = std::str::from_utf8(names).ok()?;
+
+    Some(names.split(',').filter_map(Algorithm::from_name).collect())
+}
+
+//the first of the offered algorithms that is supported
+#[cfg(feature = "service")]
+pub(crate) fn answer(offered: &[Algorithm], supported: &[Algorithm]) -> Vec<u8> {
+    let picked = offered
+        .iter()
+        .find(|a| supported.contains(a))
+        .map(|a| a.name())
+        .unwrap_or_default();
+
+    format!("{}{}", HANDSHAKE_MARKER, picked).into_bytes()
+}
@@ -0,0 +1,95 @@
+//===------------ service.rs --------------------------------------------===//
+//  Copyright 2021, Tesseract Systems, Inc.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//===----------------------------------------------------------------------===//
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use crate::serialize::Serializer;
+
+use crate::service::processor::error_response;
+use crate::service::BoundTransport;
+use crate::service::Transport;
+use crate::service::TransportProcessor;
+
+use super::{answer, handshake, Algorithm, Config};
+
+struct CompressedProcessor {
+    processor: Arc<dyn TransportProcessor + Send + Sync>,
+    config: Config,
+}
+
+impl CompressedProcessor {
+    async fn process_compressed(self: Arc<Self>, algorithm: Algorithm, data: &[u8]) -> Vec<u8> {
+        let request = match algorithm.decompress(data, self.config.limit) {
+            Ok(request) => request,
+            Err(error) => return error_response(Serializer::default(), None, error),
+        };
+
+        let response = Arc::clone(&self.processor).process(&request).await;
+
+        if response.len() >= self.config.threshold {
+            algorithm.compress(&response).unwrap_or(response)
+        } else {
+            response
+        }
+    }
+}
+
+#[async_trait]
+impl TransportProcessor for CompressedProcessor {
+    async fn process(self: Arc<Self>, data: &[u8]) -> Vec<u8> {
+        if let Some(offered) = handshake(data) {
+            return answer(&offered, &self.config.algorithms);
+        }
+
+        match Algorithm::read_marker(data) {
+            Some((algorithm, data)) if self.config.algorithms.contains(&algorithm) => {
+                self.process_compressed(algorithm, data).await
+            }
+            _ => Arc::clone(&self.processor).process(data).await,
+        }
+    }
+}
+
+pub struct CompressedTransport<T: Transport> {
+    transport: T,
+    config: Config,
+}
+
+impl<T: Transport> CompressedTransport<T> {
+    pub fn new(transport: T) -> Self {
+        Self::new_with_config(transport, Config::default())
+    }
+
+    pub fn new_with_config(transport: T, config: Config) -> Self {
+        Self { transport, config }
+    }
+}
+
+impl<T: Transport> Transport for CompressedTransport<T> {
+    fn bind(
+        self,
+        processor: Arc<dyn TransportProcessor + Send + Sync>,
+    ) -> Box<dyn BoundTransport + Send> {
+        let processor = CompressedProcessor {
+            processor,
+            config: self.config,
+        };
+
+        self.transport.bind(Arc::new(processor))
+    }
+}
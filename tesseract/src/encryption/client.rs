@@ -0,0 +1,204 @@
+//===------------ client.rs --------------------------------------------===//
+//  Copyright 2021, Tesseract Systems, Inc.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//===----------------------------------------------------------------------===//
+
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+
+use crate::Protocol;
+use crate::Result;
+
+use crate::client::transport::{Endpoint, Status};
+use crate::client::Connection;
+use crate::client::Transport;
+
+use super::{
+    commit, crypto_error, is_encrypted, random, Confirmation, Hello, Identity, PublicKey, Reveal,
+    Session, SessionInfo, Welcome,
+};
+
+//Encrypts the requests and decrypts the responses of the wrapped connection.
+//The handshake is made before the first request and again after the wallet loses the session
+pub struct EncryptedConnection {
+    connection: Arc<dyn Connection + Sync + Send>,
+    identity: Identity,
+    wallet: Option<PublicKey>,
+    handshake: futures::lock::Mutex<()>,
+    session: Mutex<Option<Session>>,
+}
+
+impl EncryptedConnection {
+    //the wallet's key, if known, is checked in the handshake
+    pub fn new(
+        connection: Box<dyn Connection + Sync + Send>,
+        identity: Identity,
+        wallet: Option<PublicKey>,
+    ) -> Self {
+        Self {
+            connection: Arc::from(connection),
+            identity,
+            wallet,
+            handshake: futures::lock::Mutex::new(()),
+            session: Mutex::new(None),
+        }
+    }
+
+    //None until the handshake is made
+    pub fn session(&self) -> Option<SessionInfo> {
+        self.session
+            .lock()
+            .unwrap()
+            .as_ref()
+            .map(|session| session.info().clone())
+    }
+
+    pub async fn handshake(&self) -> Result<SessionInfo> {
+        let _handshake = self.handshake.lock().await;
+
+        if let Some(info) = self.session() {
+            return Ok(info);
+        }
+
+        let ephemeral = Identity::generate()?;
+        let nonce = random()?;
+        let hello = Hello {
+            ephemeral: ephemeral.public_key(),
+            identity: self.identity.public_key(),
+            commitment: commit(&nonce),
+        }
+        .encode();
+
+        Arc::clone(&self.connection).send(hello.clone()).await?;
+        let welcome = Arc::clone(&self.connection).receive().await?;
+
+        if let Some(wallet) = self.wallet {
+            if Welcome::decode(&welcome)?.identity != wallet {
+                return Err(crypto_error(
+                    "the wallet's identity doesn't match the expected one",
+                ));
+            }
+        }
+
+        let session = Session::dapp(&ephemeral, &self.identity, &hello, &welcome, &nonce)?;
+        let info = session.info().clone();
+
+        //revealed only now, when the wallet's part can't be changed anymore
+        let reveal = Reveal { session: info.id, nonce }.encode();
+        Arc::clone(&self.connection).send(reveal).await?;
+        let confirmation = Arc::clone(&self.connection).receive().await?;
+        if Confirmation::decode(&confirmation)?.session != info.id {
+            return Err(crypto_error("the wallet has confirmed another session"));
+        }
+
+        *self.session.lock().unwrap() = Some(session);
+
+        Ok(info)
+    }
+}
+
+#[async_trait]
+impl Connection for EncryptedConnection {
+    async fn send(self: Arc<Self>, request: Vec<u8>) -> Result<()> {
+        self.handshake().await?;
+
+        let sealed = match self.session.lock().unwrap().as_mut() {
+            Some(session) => session.seal(&request)?,
+            None => return Err(crypto_error("the session is closed")),
+        };
+
+        Arc::clone(&self.connection).send(sealed).await
+    }
+
+    async fn receive(self: Arc<Self>) -> Result<Vec<u8>> {
+        let response = Arc::clone(&self.connection).receive().await?;
+        let mut session = self.session.lock().unwrap();
+
+        match session.as_mut() {
+            Some(current) if is_encrypted(&response) => current.open(&response),
+            _ => {
+                //i.e. the wallet has restarted and doesn't know the session anymore.
+                //The next request makes a new one
+                *session = None;
+                Err(crypto_error("the wallet has sent an unencrypted response"))
+            }
+        }
+    }
+}
+
+pub struct EncryptedTransport<T: Transport> {
+    transport: Arc<T>,
+    identity: Identity,
+    wallet: Option<PublicKey>,
+}
+
+impl<T: Transport> EncryptedTransport<T> {
+    pub fn new(transport: T, identity: Identity) -> Self {
+        Self {
+            transport: Arc::new(transport),
+            identity,
+            wallet: None,
+        }
+    }
+
+    //only the wallet with this identity is talked to
+    pub fn wallet(self, wallet: PublicKey) -> Self {
+        Self {
+            wallet: Some(wallet),
+            ..self
+        }
+    }
+
+    fn wrap(
+        &self,
+        connection: Box<dyn Connection + Sync + Send>,
+    ) -> Box<dyn Connection + Sync + Send> {
+        Box::new(EncryptedConnection::new(
+            connection,
+            self.identity.clone(),
+            self.wallet,
+        ))
+    }
+}
+
+#[async_trait]
+impl<T: Transport + Sync + Send> Transport for EncryptedTransport<T> {
+    fn id(&self) -> String {
+        self.transport.id()
+    }
+
+    async fn status(self: Arc<Self>, protocol: Box<dyn Protocol>) -> Status {
+        Arc::clone(&self.transport).status(protocol).await
+    }
+
+    fn connect(&self, protocol: Box<dyn Protocol>) -> Box<dyn Connection + Sync + Send> {
+        self.wrap(self.transport.connect(protocol))
+    }
+
+    async fn endpoints(self: Arc<Self>, protocol: Box<dyn Protocol>) -> Vec<Endpoint>
+    where
+        Self: 'static,
+    {
+        Arc::clone(&self.transport).endpoints(protocol).await
+    }
+
+    fn connect_endpoint(
+        &self,
+        endpoint: &str,
+        protocol: Box<dyn Protocol>,
+    ) -> Box<dyn Connection + Sync + Send> {
+        self.wrap(self.transport.connect_endpoint(endpoint, protocol))
+    }
+}
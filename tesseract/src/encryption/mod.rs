@@ -0,0 +1,585 @@
+//===------------ mod.rs --------------------------------------------===//
+//  Copyright 2021, Tesseract Systems, Inc.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//===----------------------------------------------------------------------===//
+
+//End-to-end encryption of the marked messages over any transport.
+//
+//Both sides have a long-term X25519 Identity. The dApp starts every connection with a handshake
+//before its first request, the wallet answers it with a new session id:
+//  hello:   "e2eh" | dApp's ephemeral key (32) | dApp's identity key (32) | commitment (32)
+//  welcome: "e2eh" | session id (16) | wallet's ephemeral key (32) | wallet's identity key (32)
+//The commitment is the SHA-256 of a random nonce the dApp reveals once it has the welcome,
+//and the wallet confirms the reveal. Only then the session can be used:
+//  reveal:       "e2eh" | session id (16) | nonce (32)
+//  confirmation: "e2eh" | session id (16)
+//The nonce goes into the transcript of the session (see SessionInfo), but not into its keys.
+//The keys of both directions are derived with HKDF-SHA256 from three X25519 agreements
+//(ephemeral-ephemeral, dApp identity-wallet ephemeral, dApp ephemeral-wallet identity),
+//salted with the SHA-256 of the handshake. So only the holders of the identities the peers
+//have sent can read the session, and the earlier sessions stay secret if an identity leaks.
+//Every message after the handshake is sealed with ChaCha20Poly1305:
+//  "e2ee" | session id (16) | counter (u64) | ciphertext
+//The counter is the nonce. It grows with every message and the receiver rejects the counters
+//it has seen already (or the ones too old to tell), so the messages can't be replayed.
+//
+//The handshake proves that the peer holds the identity it has sent, but not whose identity it is:
+//the dApp has to check the wallet's key (i.e. the one received on pairing), or the wallet the dApp's.
+
+#[cfg(feature = "client")]
+pub mod client;
+
+#[cfg(feature = "service")]
+pub mod service;
+
+use std::fmt;
+use std::str::FromStr;
+
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use hkdf::Hkdf;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use sha2::{Digest, Sha256};
+use x25519_dalek::StaticSecret;
+
+use crate::{Error, ErrorKind, Result};
+
+pub const HANDSHAKE_MARKER: &str = "e2eh";
+pub const MESSAGE_MARKER: &str = "e2ee";
+
+pub const KEY_LEN: usize = 32;
+pub const SESSION_ID_LEN: usize = 16;
+pub const NONCE_LEN: usize = 32;
+
+const MARKER_LEN: usize = 4;
+const COUNTER_LEN: usize = 8;
+const INFO: &[u8] = b"tesseract-e2e-v1";
+
+pub type SessionId = [u8; SESSION_ID_LEN];
+
+pub(crate) fn crypto_error(description: &str) -> Error {
+    Error::described(ErrorKind::Transport, description)
+}
+
+pub(crate) fn random<const N: usize>() -> Result<[u8; N]> {
+    let mut bytes = [0u8; N];
+    getrandom::getrandom(&mut bytes).map_err(|e| {
+        Error::described(ErrorKind::Weird, &format!("can't get random bytes: {}", e))
+    })?;
+    Ok(bytes)
+}
+
+fn hex(data: &[u8]) -> String {
+    data.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+//Serialized as a hex string
+#[derive(Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct PublicKey([u8; KEY_LEN]);
+
+impl PublicKey {
+    pub fn from_bytes(bytes: [u8; KEY_LEN]) -> Self {
+        Self(bytes)
+    }
+
+    pub fn as_bytes(&self) -> &[u8; KEY_LEN] {
+        &self.0
+    }
+}
+
+impl fmt::Display for PublicKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", hex(&self.0))
+    }
+}
+
+impl fmt::Debug for PublicKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "PublicKey({})", self)
+    }
+}
+
+fn parse_key(s: &str) -> Option<[u8; KEY_LEN]> {
+    if s.len() != KEY_LEN * 2 || !s.is_ascii() {
+        return None;
+    }
+
+    let mut bytes = [0u8; KEY_LEN];
+    for (i, byte) in bytes.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&s[i * 2..i * 2 + 2], 16).ok()?;
+    }
+    Some(bytes)
+}
+
+impl FromStr for PublicKey {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        parse_key(s).map(Self).ok_or_else(|| {
+            Error::described(
+                ErrorKind::Serialization,
+                &format!("invalid public key: {}", s),
+            )
+        })
+    }
+}
+
+impl Serialize for PublicKey {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for PublicKey {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+//A long-term X25519 key pair. Keep the secret bytes as safe as any other key
+#[derive(Clone)]
+pub struct Identity {
+    secret: StaticSecret,
+}
+
+impl Identity {
+    pub fn generate() -> Result<Self> {
+        random().map(Self::from_bytes)
+    }
+
+    pub fn from_bytes(secret: [u8; KEY_LEN]) -> Self {
+        Self {
+            secret: StaticSecret::from(secret),
+        }
+    }
+
+    pub fn to_bytes(&self) -> [u8; KEY_LEN] {
+        self.secret.to_bytes()
+    }
+
+    pub fn public_key(&self) -> PublicKey {
+        PublicKey(x25519_dalek::PublicKey::from(&self.secret).to_bytes())
+    }
+
+    fn agree(&self, public: &PublicKey) -> Result<[u8; KEY_LEN]> {
+        let shared = self
+            .secret
+            .diffie_hellman(&x25519_dalek::PublicKey::from(public.0));
+
+        //a low order point from the peer would make the secret predictable
+        if shared.was_contributory() {
+            Ok(shared.to_bytes())
+        } else {
+            Err(crypto_error("the peer has sent an invalid key"))
+        }
+    }
+}
+
+//Serialized as a hex string of the secret, to be stored along with the pairings
+impl Serialize for Identity {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_str(&hex(&self.to_bytes()))
+    }
+}
+
+impl<'de> Deserialize<'de> for Identity {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        parse_key(&s)
+            .map(Self::from_bytes)
+            .ok_or_else(|| serde::de::Error::custom("invalid identity"))
+    }
+}
+
+impl fmt::Debug for Identity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Identity({})", self.public_key())
+    }
+}
+
+fn read_session(data: &[u8]) -> SessionId {
+    let mut session = [0u8; SESSION_ID_LEN];
+    session.copy_from_slice(&data[..SESSION_ID_LEN]);
+    session
+}
+
+fn read_key(data: &[u8]) -> PublicKey {
+    let mut key = [0u8; KEY_LEN];
+    key.copy_from_slice(&data[..KEY_LEN]);
+    PublicKey(key)
+}
+
+pub fn is_handshake(data: &[u8]) -> bool {
+    data.get(..MARKER_LEN) == Some(HANDSHAKE_MARKER.as_bytes())
+}
+
+pub fn is_encrypted(data: &[u8]) -> bool {
+    data.get(..MARKER_LEN) == Some(MESSAGE_MARKER.as_bytes())
+}
+
+//The handshake and the sessions are made by the client and service wrappers,
+//but are public for the transports that have to do it on their own
+//the dApp's commitment to its nonce
+pub fn commit(nonce: &[u8; NONCE_LEN]) -> [u8; 32] {
+    Sha256::digest(nonce).into()
+}
+
+pub struct Hello {
+    pub ephemeral: PublicKey,
+    pub identity: PublicKey,
+    pub commitment: [u8; 32],
+}
+
+impl Hello {
+    pub const LEN: usize = MARKER_LEN + KEY_LEN * 2 + 32;
+
+    pub fn encode(&self) -> Vec<u8> {
+        [
+            HANDSHAKE_MARKER.as_bytes(),
+            &self.ephemeral.0,
+            &self.identity.0,
+            &self.commitment,
+        ]
+        .concat()
+    }
+
+    pub fn decode(data: &[u8]) -> Result<Self> {
+        if !is_handshake(data) || data.len() != Self::LEN {
+            return Err(crypto_error("malformed handshake"));
+        }
+
+        let mut commitment = [0u8; 32];
+        commitment.copy_from_slice(&data[MARKER_LEN + KEY_LEN * 2..]);
+
+        Ok(Self {
+            ephemeral: read_key(&data[MARKER_LEN..]),
+            identity: read_key(&data[MARKER_LEN + KEY_LEN..]),
+            commitment,
+        })
+    }
+}
+
+pub struct Welcome {
+    pub session: SessionId,
+    pub ephemeral: PublicKey,
+    pub identity: PublicKey,
+}
+
+impl Welcome {
+    pub const LEN: usize = MARKER_LEN + SESSION_ID_LEN + KEY_LEN * 2;
+
+    pub fn encode(&self) -> Vec<u8> {
+        [
+            HANDSHAKE_MARKER.as_bytes(),
+            &self.session,
+            &self.ephemeral.0,
+            &self.identity.0,
+        ]
+        .concat()
+    }
+
+    pub fn decode(data: &[u8]) -> Result<Self> {
+        if !is_handshake(data) || data.len() != Self::LEN {
+            return Err(crypto_error("malformed handshake response"));
+        }
+
+        Ok(Self {
+            session: read_session(&data[MARKER_LEN..]),
+            ephemeral: read_key(&data[MARKER_LEN + SESSION_ID_LEN..]),
+            identity: read_key(&data[MARKER_LEN + SESSION_ID_LEN + KEY_LEN..]),
+        })
+    }
+}
+
+pub struct Reveal {
+    pub session: SessionId,
+    pub nonce: [u8; NONCE_LEN],
+}
+
+impl Reveal {
+    pub const LEN: usize = MARKER_LEN + SESSION_ID_LEN + NONCE_LEN;
+
+    pub fn encode(&self) -> Vec<u8> {
+        [HANDSHAKE_MARKER.as_bytes(), &self.session, &self.nonce].concat()
+    }
+
+    pub fn decode(data: &[u8]) -> Result<Self> {
+        if !is_handshake(data) || data.len() != Self::LEN {
+            return Err(crypto_error("malformed handshake reveal"));
+        }
+
+        let mut nonce = [0u8; NONCE_LEN];
+        nonce.copy_from_slice(&data[MARKER_LEN + SESSION_ID_LEN..]);
+
+        Ok(Self {
+            session: read_session(&data[MARKER_LEN..]),
+            nonce,
+        })
+    }
+}
+
+pub struct Confirmation {
+    pub session: SessionId,
+}
+
+impl Confirmation {
+    pub const LEN: usize = MARKER_LEN + SESSION_ID_LEN;
+
+    pub fn encode(&self) -> Vec<u8> {
+        [HANDSHAKE_MARKER.as_bytes(), &self.session].concat()
+    }
+
+    pub fn decode(data: &[u8]) -> Result<Self> {
+        if !is_handshake(data) || data.len() != Self::LEN {
+            return Err(crypto_error("malformed handshake confirmation"));
+        }
+
+        Ok(Self {
+            session: read_session(&data[MARKER_LEN..]),
+        })
+    }
+}
+
+//What is known about an established session
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SessionInfo {
+    pub id: SessionId,
+    //the identity of the other side
+    pub peer: PublicKey,
+    //SHA-256 of the handshake, with the revealed nonce. Both sides have the same one unless someone
+    //is in the middle, who has to send its welcome before it learns the nonce
+    pub transcript: [u8; 32],
+}
+
+//the counters seen recently, to reject the replayed messages
+#[derive(Default)]
+struct ReplayWindow {
+    highest: u64,
+    seen: u64, //bit n is set if highest - n was seen
+}
+
+impl ReplayWindow {
+    fn is_fresh(&self, counter: u64) -> bool {
+        if counter > self.highest {
+            true
+        } else {
+            let age = self.highest - counter;
+            age < 64 && self.seen & (1 << age) == 0
+        }
+    }
+
+    fn mark(&mut self, counter: u64) {
+        if counter > self.highest {
+            let shift = counter - self.highest;
+            self.seen = if shift >= 64 { 0 } else { self.seen << shift };
+            self.seen |= 1;
+            self.highest = counter;
+        } else {
+            self.seen |= 1 << (self.highest - counter);
+        }
+    }
+}
+
+//One side of an established session
+pub struct Session {
+    info: SessionInfo,
+    seal: ChaCha20Poly1305,
+    open: ChaCha20Poly1305,
+    counter: u64,
+    window: ReplayWindow,
+}
+
+impl Session {
+    //dApp's session. The keys of the wallet are taken from its welcome
+    pub fn dapp(
+        ephemeral: &Identity,
+        identity: &Identity,
+        hello: &[u8],
+        welcome: &[u8],
+        nonce: &[u8; NONCE_LEN],
+    ) -> Result<Self> {
+        let parsed = Welcome::decode(welcome)?;
+        let agreements = [
+            ephemeral.agree(&parsed.ephemeral)?,
+            identity.agree(&parsed.ephemeral)?,
+            ephemeral.agree(&parsed.identity)?,
+        ];
+        let (to_wallet, to_dapp) = Self::derive(&agreements, hello, welcome);
+
+        Ok(Self::new(
+            parsed.session,
+            parsed.identity,
+            Self::transcript(&[hello, welcome, nonce]),
+            &to_wallet,
+            &to_dapp,
+        ))
+    }
+
+    //wallet's session, once the dApp has revealed the nonce it has committed to in the hello
+    pub fn wallet(
+        ephemeral: &Identity,
+        identity: &Identity,
+        hello: &[u8],
+        welcome: &[u8],
+        nonce: &[u8; NONCE_LEN],
+    ) -> Result<Self> {
+        let parsed_hello = Hello::decode(hello)?;
+        let parsed_welcome = Welcome::decode(welcome)?;
+        if commit(nonce) != parsed_hello.commitment {
+            return Err(crypto_error("the dApp has revealed another nonce than committed"));
+        }
+        let agreements = [
+            ephemeral.agree(&parsed_hello.ephemeral)?,
+            ephemeral.agree(&parsed_hello.identity)?,
+            identity.agree(&parsed_hello.ephemeral)?,
+        ];
+        let (to_wallet, to_dapp) = Self::derive(&agreements, hello, welcome);
+
+        Ok(Self::new(
+            parsed_welcome.session,
+            parsed_hello.identity,
+            Self::transcript(&[hello, welcome, nonce]),
+            &to_dapp,
+            &to_wallet,
+        ))
+    }
+
+    pub fn info(&self) -> &SessionInfo {
+        &self.info
+    }
+
+    fn transcript(parts: &[&[u8]]) -> [u8; 32] {
+        parts
+            .iter()
+            .fold(Sha256::new(), |digest, part| digest.chain_update(part))
+            .finalize()
+            .into()
+    }
+
+    //(dApp to wallet, wallet to dApp) keys
+    fn derive(
+        agreements: &[[u8; KEY_LEN]; 3],
+        hello: &[u8],
+        welcome: &[u8],
+    ) -> ([u8; KEY_LEN], [u8; KEY_LEN]) {
+        let hkdf = Hkdf::<Sha256>::new(
+            Some(&Self::transcript(&[hello, welcome])),
+            &agreements.concat(),
+        );
+
+        let mut keys = [0u8; KEY_LEN * 2];
+        hkdf.expand(INFO, &mut keys)
+            .expect("64 bytes is a valid HKDF-SHA256 output length");
+
+        let mut to_wallet = [0u8; KEY_LEN];
+        let mut to_dapp = [0u8; KEY_LEN];
+        to_wallet.copy_from_slice(&keys[..KEY_LEN]);
+        to_dapp.copy_from_slice(&keys[KEY_LEN..]);
+        (to_wallet, to_dapp)
+    }
+
+    fn new(
+        id: SessionId,
+        peer: PublicKey,
+        transcript: [u8; 32],
+        seal: &[u8; KEY_LEN],
+        open: &[u8; KEY_LEN],
+    ) -> Self {
+        Self {
+            info: SessionInfo {
+                id,
+                peer,
+                transcript,
+            },
+            seal: ChaCha20Poly1305::new(Key::from_slice(seal)),
+            open: ChaCha20Poly1305::new(Key::from_slice(open)),
+            counter: 0,
+            window: ReplayWindow::default(),
+        }
+    }
+
+    fn nonce(counter: u64) -> [u8; 12] {
+        let mut nonce = [0u8; 12];
+        nonce[4..].copy_from_slice(&counter.to_be_bytes());
+        nonce
+    }
+
+    pub fn seal(&mut self, message: &[u8]) -> Result<Vec<u8>> {
+        self.counter = self
+            .counter
+            .checked_add(1)
+            .ok_or_else(|| crypto_error("the session has run out of nonces"))?;
+
+        let mut header = Vec::with_capacity(MARKER_LEN + SESSION_ID_LEN + COUNTER_LEN);
+        header.extend_from_slice(MESSAGE_MARKER.as_bytes());
+        header.extend_from_slice(&self.info.id);
+        header.extend_from_slice(&self.counter.to_be_bytes());
+
+        let sealed = self
+            .seal
+            .encrypt(
+                Nonce::from_slice(&Self::nonce(self.counter)),
+                Payload {
+                    msg: message,
+                    aad: &header,
+                },
+            )
+            .map_err(|_| crypto_error("can't encrypt message"))?;
+
+        header.extend_from_slice(&sealed);
+        Ok(header)
+    }
+
+    pub fn open(&mut self, data: &[u8]) -> Result<Vec<u8>> {
+        let header_len = MARKER_LEN + SESSION_ID_LEN + COUNTER_LEN;
+        if session_id(data) != Some(self.info.id) || data.len() < header_len {
+            return Err(crypto_error("the message is not of this session"));
+        }
+
+        let mut counter = [0u8; COUNTER_LEN];
+        counter.copy_from_slice(&data[MARKER_LEN + SESSION_ID_LEN..header_len]);
+        let counter = u64::from_be_bytes(counter);
+
+        if counter == 0 || !self.window.is_fresh(counter) {
+            return Err(crypto_error("replayed message"));
+        }
+
+        let (header, sealed) = data.split_at(header_len);
+        let message = self
+            .open
+            .decrypt(
+                Nonce::from_slice(&Self::nonce(counter)),
+                Payload {
+                    msg: sealed,
+                    aad: header,
+                },
+            )
+            .map_err(|_| crypto_error("can't decrypt message"))?;
+
+        self.window.mark(counter);
+        Ok(message)
+    }
+}
+
+//the session id of an encrypted message
+pub fn session_id(data: &[u8]) -> Option<SessionId> {
+    if !is_encrypted(data) {
+        return None;
+    }
+
+    let mut id = [0u8; SESSION_ID_LEN];
+    id.copy_from_slice(data.get(MARKER_LEN..MARKER_LEN + SESSION_ID_LEN)?);
+    Some(id)
+}
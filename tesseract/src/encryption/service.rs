@@ -0,0 +1,303 @@
+//===------------ service.rs --------------------------------------------===//
+//  Copyright 2021, Tesseract Systems, Inc.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//===----------------------------------------------------------------------===//
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+
+use crate::serialize::Serializer;
+use crate::Result;
+
+use crate::service::processor::error_response;
+use crate::service::BoundTransport;
+use crate::service::Context;
+use crate::service::Transport;
+use crate::service::TransportProcessor;
+
+use super::{
+    crypto_error, is_handshake, random, session_id, Confirmation, Hello, Identity, PublicKey,
+    Reveal, Session, SessionId, SessionInfo, Welcome,
+};
+
+pub const DEFAULT_MAX_SESSIONS: usize = 1024;
+pub const DEFAULT_MAX_HANDSHAKES: usize = 256;
+
+pub type Authorize = Box<dyn Fn(&PublicKey) -> bool + Send + Sync>;
+pub type OnSession = Box<dyn Fn(&SessionInfo) + Send + Sync>;
+
+//the handshake waiting for the dApp to reveal its nonce
+struct Handshake {
+    ephemeral: Identity,
+    hello: Vec<u8>,
+    welcome: Vec<u8>,
+}
+
+//the handshakes and the sessions are limited apart, so that unfinished handshakes can't push out the sessions
+#[derive(Default)]
+struct Sessions {
+    handshakes: HashMap<SessionId, Handshake>,
+    pending: VecDeque<SessionId>, //the oldest first
+    sessions: HashMap<SessionId, Session>,
+    used: VecDeque<SessionId>, //the least recently used first
+}
+
+impl Sessions {
+    fn start(&mut self, id: SessionId, handshake: Handshake, max: usize) {
+        while self.pending.len() >= max.max(1) {
+            if let Some(oldest) = self.pending.pop_front() {
+                self.handshakes.remove(&oldest);
+            }
+        }
+        self.pending.push_back(id);
+        self.handshakes.insert(id, handshake);
+    }
+
+    fn finish(&mut self, id: &SessionId) -> Option<Handshake> {
+        let handshake = self.handshakes.remove(id)?;
+        self.pending.retain(|pending| pending != id);
+        Some(handshake)
+    }
+
+    fn insert(&mut self, id: SessionId, session: Session, max: usize) {
+        while self.used.len() >= max.max(1) {
+            if let Some(unused) = self.used.pop_front() {
+                self.sessions.remove(&unused);
+            }
+        }
+        self.used.push_back(id);
+        self.sessions.insert(id, session);
+    }
+
+    fn touch(&mut self, id: &SessionId) -> Option<&mut Session> {
+        if let Some(position) = self.used.iter().position(|used| used == id) {
+            self.used.remove(position);
+            self.used.push_back(*id);
+        }
+        self.sessions.get_mut(id)
+    }
+}
+
+//Decrypts the requests and encrypts the responses. The plain requests are answered with an error.
+//The processor works within the transport's context with the SessionInfo added to it
+pub struct EncryptedProcessor {
+    processor: Arc<dyn TransportProcessor + Send + Sync>,
+    identity: Identity,
+    authorize: Option<Authorize>,
+    on_session: Option<OnSession>,
+    max_sessions: usize,
+    max_handshakes: usize,
+    sessions: Mutex<Sessions>,
+}
+
+impl EncryptedProcessor {
+    pub fn new(
+        processor: Arc<dyn TransportProcessor + Send + Sync>,
+        identity: Identity,
+        authorize: Option<Authorize>,
+        on_session: Option<OnSession>,
+        max_sessions: usize,
+        max_handshakes: usize,
+    ) -> Self {
+        Self {
+            processor,
+            identity,
+            authorize,
+            on_session,
+            max_sessions,
+            max_handshakes,
+            sessions: Mutex::new(Sessions::default()),
+        }
+    }
+
+    fn welcome(&self, hello: &[u8]) -> Result<Vec<u8>> {
+        let dapp = Hello::decode(hello)?.identity;
+        if let Some(authorize) = &self.authorize {
+            if !authorize(&dapp) {
+                return Err(crypto_error("the dApp is not authorized"));
+            }
+        }
+
+        let ephemeral = Identity::generate()?;
+        let id = random()?;
+        let welcome = Welcome {
+            session: id,
+            ephemeral: ephemeral.public_key(),
+            identity: self.identity.public_key(),
+        }
+        .encode();
+
+        let handshake = Handshake {
+            ephemeral,
+            hello: hello.to_vec(),
+            welcome: welcome.clone(),
+        };
+        self.sessions
+            .lock()
+            .unwrap()
+            .start(id, handshake, self.max_handshakes);
+
+        Ok(welcome)
+    }
+
+    fn confirm(&self, reveal: &[u8]) -> Result<Vec<u8>> {
+        let reveal = Reveal::decode(reveal)?;
+        let handshake = self
+            .sessions
+            .lock()
+            .unwrap()
+            .finish(&reveal.session)
+            .ok_or_else(|| crypto_error("unknown handshake"))?;
+
+        let session = Session::wallet(
+            &handshake.ephemeral,
+            &self.identity,
+            &handshake.hello,
+            &handshake.welcome,
+            &reveal.nonce,
+        )?;
+        if let Some(on_session) = &self.on_session {
+            on_session(session.info());
+        }
+        self.sessions
+            .lock()
+            .unwrap()
+            .insert(reveal.session, session, self.max_sessions);
+
+        Ok(Confirmation {
+            session: reveal.session,
+        }
+        .encode())
+    }
+
+    fn with_session<R>(
+        &self,
+        id: &SessionId,
+        f: impl FnOnce(&mut Session) -> Result<R>,
+    ) -> Result<R> {
+        match self.sessions.lock().unwrap().touch(id) {
+            Some(session) => f(session),
+            None => Err(crypto_error("unknown session")),
+        }
+    }
+
+    async fn process_encrypted(self: Arc<Self>, id: SessionId, data: &[u8]) -> Result<Vec<u8>> {
+        let (request, info) = self.with_session(&id, |session| {
+            Ok((session.open(data)?, session.info().clone()))
+        })?;
+
+        let response = Context::current()
+            .with(info)
+            .scope(Arc::clone(&self.processor).process(&request))
+            .await;
+
+        self.with_session(&id, |session| session.seal(&response))
+    }
+}
+
+#[async_trait]
+impl TransportProcessor for EncryptedProcessor {
+    async fn process(self: Arc<Self>, data: &[u8]) -> Vec<u8> {
+        let result = if is_handshake(data) {
+            if data.len() == Reveal::LEN {
+                self.confirm(data)
+            } else {
+                self.welcome(data)
+            }
+        } else if let Some(id) = session_id(data) {
+            self.process_encrypted(id, data).await
+        } else {
+            Err(crypto_error("the wallet accepts only encrypted sessions"))
+        };
+
+        result.unwrap_or_else(|error| error_response(Serializer::default(), None, error))
+    }
+}
+
+pub struct EncryptedTransport<T: Transport> {
+    transport: T,
+    identity: Identity,
+    authorize: Option<Authorize>,
+    on_session: Option<OnSession>,
+    max_sessions: usize,
+    max_handshakes: usize,
+}
+
+impl<T: Transport> EncryptedTransport<T> {
+    pub fn new(transport: T, identity: Identity) -> Self {
+        Self {
+            transport,
+            identity,
+            authorize: None,
+            on_session: None,
+            max_sessions: DEFAULT_MAX_SESSIONS,
+            max_handshakes: DEFAULT_MAX_HANDSHAKES,
+        }
+    }
+
+    //decides which dApp identities can make a session. Any can by default
+    pub fn authorize<F: Fn(&PublicKey) -> bool + Send + Sync + 'static>(
+        self,
+        authorize: F,
+    ) -> Self {
+        Self {
+            authorize: Some(Box::new(authorize)),
+            ..self
+        }
+    }
+
+    //called for every new session, before the dApp gets the handshake confirmation
+    pub fn on_session<F: Fn(&SessionInfo) + Send + Sync + 'static>(self, on_session: F) -> Self {
+        Self {
+            on_session: Some(Box::new(on_session)),
+            ..self
+        }
+    }
+
+    //the least recently used sessions are forgotten above the limit
+    pub fn max_sessions(self, max_sessions: usize) -> Self {
+        Self {
+            max_sessions,
+            ..self
+        }
+    }
+
+    //the oldest unfinished handshakes are forgotten above the limit. They don't count against the sessions
+    pub fn max_handshakes(self, max_handshakes: usize) -> Self {
+        Self {
+            max_handshakes,
+            ..self
+        }
+    }
+}
+
+impl<T: Transport> Transport for EncryptedTransport<T> {
+    fn bind(
+        self,
+        processor: Arc<dyn TransportProcessor + Send + Sync>,
+    ) -> Box<dyn BoundTransport + Send> {
+        let processor = EncryptedProcessor::new(
+            processor,
+            self.identity,
+            self.authorize,
+            self.on_session,
+            self.max_sessions,
+            self.max_handshakes,
+        );
+
+        self.transport.bind(Arc::new(processor))
+    }
+}
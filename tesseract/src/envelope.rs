@@ -16,20 +16,64 @@
 
 use serde::{Deserialize, Serialize};
 
+use super::error::{Error, ErrorKind, Result};
+
+//The version of the envelopes format this crate speaks.
+//Missing version means v1, as it was sent by the peers made before the field existed.
+//Unknown fields are ignored by the decoders, so new optional fields don't need a version bump.
+pub const VERSION: u32 = 1;
+
+fn version_v1() -> u32 {
+    1
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct RequestEnvelope<R> {
+    #[serde(default = "version_v1")]
+    pub version: u32,
+
     pub protocol: String,
     pub method: String,
-    pub id: u32,
+    //64 bit ids are wire compatible with the peers reading them as u32 while below 2^32
+    pub id: u64,
 
     pub request: R,
 }
 
 #[derive(Serialize, Deserialize)]
 pub struct ResponseEnvelope<R> {
+    #[serde(default = "version_v1")]
+    pub version: u32,
+
     //None means that the error occured before the request_id could be obtained.
     //I.e. the wallet couldn't parse the request at all
-    pub id: Option<u32>,
+    pub id: Option<u64>,
 
     pub response: super::response::Response<R>,
 }
+
+//The part of any envelope (of any version) that must be read before the rest of it
+#[derive(Deserialize)]
+pub struct EnvelopeHeader {
+    #[serde(default = "version_v1")]
+    pub version: u32,
+
+    #[serde(default)]
+    pub id: Option<u64>,
+}
+
+impl EnvelopeHeader {
+    pub fn check(&self) -> Result<()> {
+        if (1..=VERSION).contains(&self.version) {
+            Ok(())
+        } else {
+            Err(Error::described(
+                ErrorKind::UnsupportedVersion,
+                &format!(
+                    "envelope version {} is not supported. Supported versions are 1 to {}",
+                    self.version, VERSION
+                ),
+            ))
+        }
+    }
+}
@@ -23,7 +23,14 @@ use serde::{Deserialize, Serialize};
 pub enum ErrorKind {
     Cancelled,
     Serialization,
+    Transport,
+    UnsupportedVersion,
     Weird,
+    //the local files, i.e. the session store
+    Storage,
+    //a kind added by a newer peer
+    #[serde(other)]
+    Unknown,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
@@ -80,6 +87,10 @@ impl fmt::Display for ErrorKind {
             ErrorKind::Cancelled => "Cancelled".to_owned(),
             ErrorKind::Weird => "Weird".to_owned(),
             ErrorKind::Serialization => "Serialization".to_owned(),
+            ErrorKind::Transport => "Transport".to_owned(),
+            ErrorKind::UnsupportedVersion => "UnsupportedVersion".to_owned(),
+            ErrorKind::Storage => "Storage".to_owned(),
+            ErrorKind::Unknown => "Unknown".to_owned(),
         };
 
         write!(f, "{}", strrepr)
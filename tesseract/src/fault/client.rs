@@ -0,0 +1,210 @@
+//===------------ client.rs --------------------------------------------===//
+//  Copyright 2021, Tesseract Systems, Inc.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//===----------------------------------------------------------------------===//
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+
+use futures_timer::Delay;
+
+use crate::Protocol;
+use crate::{Error, ErrorKind, Result};
+
+use crate::client::transport::{Endpoint, Status};
+use crate::client::Connection;
+use crate::client::Transport;
+
+use super::{Faults, Rng};
+
+const UNAVAILABLE: &str = "unavailable by fault injection";
+
+//shared by the transport and all its connections, so that a seed defines the whole run
+type SharedRng = Arc<Mutex<Rng>>;
+
+struct State {
+    //whether each of the requests sent and not received yet was dropped
+    dropped: VecDeque<bool>,
+    //duplicated and reordered responses to be received before the wallet's next one
+    stash: VecDeque<Vec<u8>>,
+}
+
+struct FaultyConnection {
+    connection: Arc<dyn Connection + Sync + Send>,
+    faults: Faults,
+    rng: SharedRng,
+    state: Mutex<State>,
+}
+
+impl FaultyConnection {
+    fn chance(&self, probability: f64) -> bool {
+        self.rng.lock().unwrap().chance(probability)
+    }
+
+    async fn delay(&self) {
+        let latency = self.rng.lock().unwrap().duration(&self.faults.latency);
+
+        if !latency.is_zero() {
+            Delay::new(latency).await;
+        }
+    }
+
+    fn corrupt(&self, mut response: Vec<u8>) -> Vec<u8> {
+        let mut rng = self.rng.lock().unwrap();
+
+        if !response.is_empty() && rng.chance(self.faults.corrupt) {
+            let bit = rng.below(response.len() as u64 * 8) as usize;
+            response[bit / 8] ^= 1 << (bit % 8);
+        }
+
+        response
+    }
+}
+
+#[async_trait]
+impl Connection for FaultyConnection {
+    async fn send(self: Arc<Self>, request: Vec<u8>) -> Result<()> {
+        self.delay().await;
+
+        let dropped = self.chance(self.faults.drop);
+        self.state.lock().unwrap().dropped.push_back(dropped);
+
+        if dropped {
+            Ok(())
+        } else {
+            Arc::clone(&self.connection).send(request).await
+        }
+    }
+
+    async fn receive(self: Arc<Self>) -> Result<Vec<u8>> {
+        self.delay().await;
+
+        let (stashed, dropped) = {
+            let mut state = self.state.lock().unwrap();
+
+            match state.stash.pop_front() {
+                Some(stashed) => (Some(stashed), false),
+                None => (None, state.dropped.pop_front().unwrap_or(false)),
+            }
+        };
+
+        if let Some(stashed) = stashed {
+            return Ok(self.corrupt(stashed));
+        }
+
+        if dropped {
+            Delay::new(self.faults.drop_timeout).await;
+            return Err(Error::described(
+                ErrorKind::Transport,
+                "the request has been dropped by fault injection",
+            ));
+        }
+
+        let mut response = Arc::clone(&self.connection).receive().await?;
+
+        let next_is_coming = self.state.lock().unwrap().dropped.front() == Some(&false);
+        if next_is_coming && self.chance(self.faults.reorder) {
+            self.state.lock().unwrap().dropped.pop_front();
+
+            let next = Arc::clone(&self.connection).receive().await?;
+            self.state.lock().unwrap().stash.push_back(response);
+            response = next;
+        }
+
+        if self.chance(self.faults.duplicate) {
+            self.state.lock().unwrap().stash.push_back(response.clone());
+        }
+
+        Ok(self.corrupt(response))
+    }
+}
+
+pub struct FaultyTransport<T: Transport> {
+    transport: Arc<T>,
+    faults: Faults,
+    rng: SharedRng,
+}
+
+impl<T: Transport> FaultyTransport<T> {
+    pub fn new(transport: T, faults: Faults) -> Self {
+        Self {
+            transport: Arc::new(transport),
+            rng: Arc::new(Mutex::new(Rng::new(faults.seed))),
+            faults,
+        }
+    }
+
+    fn unavailable(&self) -> bool {
+        self.rng.lock().unwrap().chance(self.faults.unavailable)
+    }
+
+    fn wrap(
+        &self,
+        connection: Box<dyn Connection + Sync + Send>,
+    ) -> Box<dyn Connection + Sync + Send> {
+        Box::new(FaultyConnection {
+            connection: Arc::from(connection),
+            faults: self.faults.clone(),
+            rng: Arc::clone(&self.rng),
+            state: Mutex::new(State {
+                dropped: VecDeque::new(),
+                stash: VecDeque::new(),
+            }),
+        })
+    }
+}
+
+#[async_trait]
+impl<T: Transport + Sync + Send> Transport for FaultyTransport<T> {
+    fn id(&self) -> String {
+        self.transport.id()
+    }
+
+    async fn status(self: Arc<Self>, protocol: Box<dyn Protocol>) -> Status {
+        if self.unavailable() {
+            Status::Unavailable(UNAVAILABLE.to_owned())
+        } else {
+            Arc::clone(&self.transport).status(protocol).await
+        }
+    }
+
+    fn connect(&self, protocol: Box<dyn Protocol>) -> Box<dyn Connection + Sync + Send> {
+        self.wrap(self.transport.connect(protocol))
+    }
+
+    async fn endpoints(self: Arc<Self>, protocol: Box<dyn Protocol>) -> Vec<Endpoint>
+    where
+        Self: 'static,
+    {
+        let mut endpoints = Arc::clone(&self.transport).endpoints(protocol).await;
+
+        for endpoint in endpoints.iter_mut() {
+            if self.unavailable() {
+                endpoint.status = Status::Unavailable(UNAVAILABLE.to_owned());
+            }
+        }
+
+        endpoints
+    }
+
+    fn connect_endpoint(
+        &self,
+        endpoint: &str,
+        protocol: Box<dyn Protocol>,
+    ) -> Box<dyn Connection + Sync + Send> {
+        self.wrap(self.transport.connect_endpoint(endpoint, protocol))
+    }
+}
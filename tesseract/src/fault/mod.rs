@@ -0,0 +1,123 @@
+//===------------ mod.rs --------------------------------------------===//
+//  Copyright 2021, Tesseract Systems, Inc.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//===----------------------------------------------------------------------===//
+
+//Fault injection for testing how a dApp copes with a misbehaving wallet link.
+//
+//client::FaultyTransport wraps any client transport, and the connections it makes, and injects
+//the faults configured with Faults. All the randomness comes from a PRNG seeded with Faults::seed,
+//so the same seed and the same sequence of calls give the same faults on every run.
+//A dropped request is lost the same way it would be on a real link: the response doesn't come.
+//The receive fails with a Transport error after Faults::drop_timeout, as a dApp's own timeout would,
+//rather than hanging the calls queued after it forever.
+
+#[cfg(feature = "client")]
+pub mod client;
+
+use std::ops::Range;
+use std::time::Duration;
+
+pub(crate) use crate::rng::Rng;
+
+pub const DEFAULT_DROP_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Faults {
+    latency: Range<Duration>,
+    drop: f64,
+    drop_timeout: Duration,
+    duplicate: f64,
+    reorder: f64,
+    corrupt: f64,
+    unavailable: f64,
+    seed: u64,
+}
+
+//The probabilities are from 0.0 (never) to 1.0 (always). Nothing is injected by default
+impl Default for Faults {
+    fn default() -> Self {
+        Self {
+            latency: Duration::ZERO..Duration::ZERO,
+            drop: 0.0,
+            drop_timeout: DEFAULT_DROP_TIMEOUT,
+            duplicate: 0.0,
+            reorder: 0.0,
+            corrupt: 0.0,
+            unavailable: 0.0,
+            seed: 0,
+        }
+    }
+}
+
+impl Faults {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            ..Self::default()
+        }
+    }
+
+    //added to every send and receive, picked from the range
+    pub fn latency(self, latency: Range<Duration>) -> Self {
+        Self { latency, ..self }
+    }
+
+    //the request is not delivered to the wallet
+    pub fn drop(self, drop: f64) -> Self {
+        Self { drop, ..self }
+    }
+
+    //how long the response to a dropped request is waited for before the receive fails
+    pub fn drop_timeout(self, drop_timeout: Duration) -> Self {
+        Self {
+            drop_timeout,
+            ..self
+        }
+    }
+
+    //the response is received once more, in place of the next one
+    pub fn duplicate(self, duplicate: f64) -> Self {
+        Self { duplicate, ..self }
+    }
+
+    //the response is swapped with the next one, if there is a next one sent already
+    pub fn reorder(self, reorder: f64) -> Self {
+        Self { reorder, ..self }
+    }
+
+    //a random bit of the response is flipped
+    pub fn corrupt(self, corrupt: f64) -> Self {
+        Self { corrupt, ..self }
+    }
+
+    //the transport reports Status::Unavailable regardless of the wallet
+    pub fn unavailable(self, unavailable: f64) -> Self {
+        Self {
+            unavailable,
+            ..self
+        }
+    }
+}
+
+impl Rng {
+    pub(crate) fn chance(&mut self, probability: f64) -> bool {
+        probability > 0.0 && self.next_f64() < probability
+    }
+
+    pub(crate) fn duration(&mut self, range: &Range<Duration>) -> Duration {
+        let span = range.end.saturating_sub(range.start).as_nanos() as u64;
+        range.start + Duration::from_nanos(self.below(span))
+    }
+}
@@ -0,0 +1,387 @@
+//===------------ fragment.rs --------------------------------------------===//
+//  Copyright 2021, Tesseract Systems, Inc.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//===----------------------------------------------------------------------===//
+
+//Fragmentation of marked messages for the links that can't carry them in one piece
+//(QR codes, deep-link URLs, BLE characteristics).
+//
+//A fragment is framed with its own 4 byte marker ("frag"), so it can never be confused with
+//a plain "json" or "cbor" message, followed by the big endian header:
+//
+//  message id (u32) | index (u16) | count (u16) | checksum (u32) | payload
+//
+//The checksum is the CRC-32 of the whole message and is repeated in every fragment,
+//so the fragments of different messages with the same id don't get mixed silently.
+//The fragments can be reassembled in any order. This module is only the framing, the
+//transports decide how the fragments are carried and when the incomplete messages are given up.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use crate::serialize::{Limits, Serializer};
+use crate::{Error, ErrorKind, Result};
+
+pub const MARKER: &str = "frag";
+pub const HEADER_LEN: usize = 16; //marker included
+
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(60);
+pub const DEFAULT_MAX_MESSAGES: usize = 16;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Fragment {
+    pub message: u32,
+    pub index: u16,
+    pub count: u16,
+    pub checksum: u32,
+    pub payload: Vec<u8>,
+}
+
+impl Fragment {
+    pub fn is_fragment(data: &[u8]) -> bool {
+        data.get(0..Serializer::marker_len()) == Some(MARKER.as_bytes())
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let mut result = Vec::with_capacity(HEADER_LEN + self.payload.len());
+        result.extend_from_slice(MARKER.as_bytes());
+        result.extend_from_slice(&self.message.to_be_bytes());
+        result.extend_from_slice(&self.index.to_be_bytes());
+        result.extend_from_slice(&self.count.to_be_bytes());
+        result.extend_from_slice(&self.checksum.to_be_bytes());
+        result.extend_from_slice(&self.payload);
+        result
+    }
+
+    pub fn decode(data: &[u8]) -> Result<Self> {
+        if !Self::is_fragment(data) {
+            return Err(Error::described(
+                ErrorKind::Serialization,
+                "the data is not a fragment",
+            ));
+        }
+        if data.len() < HEADER_LEN {
+            return Err(Error::described(
+                ErrorKind::Serialization,
+                &format!("fragment is too short: {} bytes", data.len()),
+            ));
+        }
+
+        let u16_at = |at: usize| u16::from_be_bytes([data[at], data[at + 1]]);
+        let u32_at =
+            |at: usize| u32::from_be_bytes([data[at], data[at + 1], data[at + 2], data[at + 3]]);
+
+        let fragment = Self {
+            message: u32_at(4),
+            index: u16_at(8),
+            count: u16_at(10),
+            checksum: u32_at(12),
+            payload: data[HEADER_LEN..].to_vec(),
+        };
+
+        if fragment.index >= fragment.count {
+            Err(Error::described(
+                ErrorKind::Serialization,
+                &format!(
+                    "fragment index {} is out of {} fragments",
+                    fragment.index, fragment.count
+                ),
+            ))
+        } else {
+            Ok(fragment)
+        }
+    }
+}
+
+//Splits the message into the fragments no longer than max_len bytes each (header included).
+//An empty message is sent as one fragment with no payload
+pub fn split(message: &[u8], id: u32, max_len: usize) -> Result<Vec<Fragment>> {
+    if max_len <= HEADER_LEN {
+        return Err(Error::described(
+            ErrorKind::Serialization,
+            &format!(
+                "fragment length {} leaves no room for the payload after the header",
+                max_len
+            ),
+        ));
+    }
+
+    let payload_len = max_len - HEADER_LEN;
+    let count = message.len().div_ceil(payload_len).max(1);
+    let count = u16::try_from(count).map_err(|_| {
+        Error::described(
+            ErrorKind::Serialization,
+            &format!(
+                "message of {} bytes needs {} fragments, more than {}",
+                message.len(),
+                count,
+                u16::MAX
+            ),
+        )
+    })?;
+
+    let checksum = crc32(message);
+    let mut chunks: Vec<&[u8]> = message.chunks(payload_len).collect();
+    if chunks.is_empty() {
+        chunks.push(&[]);
+    }
+
+    Ok(chunks
+        .into_iter()
+        .enumerate()
+        .map(|(index, payload)| Fragment {
+            message: id,
+            index: index as u16,
+            count,
+            checksum,
+            payload: payload.to_vec(),
+        })
+        .collect())
+}
+
+struct Partial {
+    count: u16,
+    checksum: u32,
+    fragments: Vec<Option<Vec<u8>>>,
+    bytes: usize,
+    started: Instant,
+}
+
+//Collects the fragments of several messages at once. The incomplete messages are limited
+//in number and in the total size of their fragments: the oldest ones are dropped to make room
+pub struct Reassembler {
+    timeout: Duration,
+    max_messages: usize,
+    max_bytes: usize,
+    partial: HashMap<u32, Partial>,
+    bytes: usize,
+}
+
+impl Default for Reassembler {
+    fn default() -> Self {
+        Self::new(DEFAULT_TIMEOUT)
+    }
+}
+
+impl Reassembler {
+    //incomplete messages older than the timeout (since their first fragment) are expired
+    pub fn new(timeout: Duration) -> Self {
+        Self {
+            timeout,
+            max_messages: DEFAULT_MAX_MESSAGES,
+            max_bytes: Limits::DEFAULT.max_size,
+            partial: HashMap::new(),
+            bytes: 0,
+        }
+    }
+
+    pub fn timeout(self, timeout: Duration) -> Self {
+        Self { timeout, ..self }
+    }
+
+    //incomplete messages kept at once
+    pub fn max_messages(self, max_messages: usize) -> Self {
+        Self {
+            max_messages,
+            ..self
+        }
+    }
+
+    //of the fragments of all the incomplete messages. A message bigger than that can't be reassembled
+    pub fn max_bytes(self, max_bytes: usize) -> Self {
+        Self { max_bytes, ..self }
+    }
+
+    //returns the message once its last missing fragment is pushed.
+    //Repeated fragments are ignored, so the links repeating them in a loop (i.e. animated QR) are fine
+    pub fn push(&mut self, fragment: Fragment) -> Result<Option<Vec<u8>>> {
+        self.push_at(fragment, Instant::now())
+    }
+
+    pub fn push_at(&mut self, fragment: Fragment, now: Instant) -> Result<Option<Vec<u8>>> {
+        self.expire_at(now);
+
+        if fragment.index >= fragment.count {
+            return Err(Error::described(
+                ErrorKind::Serialization,
+                &format!(
+                    "fragment index {} is out of {} fragments",
+                    fragment.index, fragment.count
+                ),
+            ));
+        }
+
+        if fragment.payload.len() > self.max_bytes {
+            self.remove(fragment.message);
+            return Err(too_big(fragment.message, self.max_bytes));
+        }
+
+        if !self.partial.contains_key(&fragment.message) {
+            while self.partial.len() >= self.max_messages.max(1) {
+                self.remove_oldest(fragment.message);
+            }
+        }
+
+        let partial = self
+            .partial
+            .entry(fragment.message)
+            .or_insert_with(|| Partial {
+                count: fragment.count,
+                checksum: fragment.checksum,
+                fragments: vec![None; fragment.count as usize],
+                bytes: 0,
+                started: now,
+            });
+
+        if partial.count != fragment.count || partial.checksum != fragment.checksum {
+            return Err(Error::described(
+                ErrorKind::Serialization,
+                &format!(
+                    "fragment {} doesn't belong to the message {} being reassembled",
+                    fragment.index, fragment.message
+                ),
+            ));
+        }
+
+        if partial.fragments[fragment.index as usize].is_none() {
+            let len = fragment.payload.len();
+
+            //the other messages make room for this one
+            while self.bytes + len > self.max_bytes {
+                if !self.remove_oldest(fragment.message) {
+                    self.remove(fragment.message);
+                    return Err(too_big(fragment.message, self.max_bytes));
+                }
+            }
+
+            let partial = self
+                .partial
+                .get_mut(&fragment.message)
+                .expect("the partial message was just added");
+            partial.fragments[fragment.index as usize] = Some(fragment.payload);
+            partial.bytes += len;
+            self.bytes += len;
+        }
+
+        let complete = self
+            .partial
+            .get(&fragment.message)
+            .is_some_and(|partial| partial.fragments.iter().all(Option::is_some));
+        if !complete {
+            return Ok(None);
+        }
+
+        let partial = self
+            .remove(fragment.message)
+            .expect("the partial message was just updated");
+        let message: Vec<u8> = partial.fragments.into_iter().flatten().flatten().collect();
+
+        if crc32(&message) == partial.checksum {
+            Ok(Some(message))
+        } else {
+            Err(Error::described(
+                ErrorKind::Serialization,
+                &format!("checksum mismatch in the message {}", fragment.message),
+            ))
+        }
+    }
+
+    //decodes the data and pushes it as a fragment
+    pub fn push_encoded(&mut self, data: &[u8]) -> Result<Option<Vec<u8>>> {
+        self.push(Fragment::decode(data)?)
+    }
+
+    //indices of the fragments not received yet. None if the message is not being reassembled
+    pub fn missing(&self, message: u32) -> Option<Vec<u16>> {
+        self.partial.get(&message).map(|partial| {
+            partial
+                .fragments
+                .iter()
+                .enumerate()
+                .filter(|(_, f)| f.is_none())
+                .map(|(index, _)| index as u16)
+                .collect()
+        })
+    }
+
+    //ids of the messages being reassembled
+    pub fn pending(&self) -> Vec<u32> {
+        self.partial.keys().copied().collect()
+    }
+
+    //drops the incomplete messages that timed out and returns their ids
+    pub fn expire(&mut self) -> Vec<u32> {
+        self.expire_at(Instant::now())
+    }
+
+    pub fn expire_at(&mut self, now: Instant) -> Vec<u32> {
+        let timeout = self.timeout;
+        let expired: Vec<u32> = self
+            .partial
+            .iter()
+            .filter(|(_, p)| now.saturating_duration_since(p.started) >= timeout)
+            .map(|(id, _)| *id)
+            .collect();
+
+        for id in &expired {
+            self.remove(*id);
+        }
+
+        expired
+    }
+
+    fn remove(&mut self, message: u32) -> Option<Partial> {
+        let partial = self.partial.remove(&message)?;
+        self.bytes -= partial.bytes;
+        Some(partial)
+    }
+
+    //the one started first, except the given message. False if there is none
+    fn remove_oldest(&mut self, except: u32) -> bool {
+        let oldest = self
+            .partial
+            .iter()
+            .filter(|(id, _)| **id != except)
+            .min_by_key(|(_, partial)| partial.started)
+            .map(|(id, _)| *id);
+
+        match oldest {
+            Some(id) => self.remove(id).is_some(),
+            None => false,
+        }
+    }
+}
+
+fn too_big(message: u32, max_bytes: usize) -> Error {
+    Error::described(
+        ErrorKind::Serialization,
+        &format!(
+            "the message {} exceeds the limit of {} bytes being reassembled",
+            message, max_bytes
+        ),
+    )
+}
+
+//CRC-32 (IEEE), bitwise. The messages are small enough for it not to matter
+pub fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for byte in data {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xEDB8_8320 & mask);
+        }
+    }
+    !crc
+}
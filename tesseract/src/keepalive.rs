@@ -0,0 +1,88 @@
+//===------------ keepalive.rs --------------------------------------------===//
+//  Copyright 2021, Tesseract Systems, Inc.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//===----------------------------------------------------------------------===//
+
+//Ping/pong keepalive of the long-lived connections, so a wallet that has crashed is noticed
+//before the next call hangs.
+//
+//Every interval the dApp sends a ping over each idle connection. A connection that doesn't answer
+//within the timeout is dropped and the service is disconnected, the next call connects again.
+//The wallets answer a ping with a pong, though any response proves the wallet alive, so the wallets
+//built without keepalive don't get disconnected. The connections busy with a request are not pinged.
+
+use std::time::Duration;
+
+#[cfg(feature = "client")]
+use std::sync::Arc;
+
+#[cfg(feature = "client")]
+use futures::future::BoxFuture;
+
+use crate::{Error, ErrorKind, Result};
+
+pub const PING: &[u8; 4] = b"ping";
+pub const PONG: &[u8; 4] = b"pong";
+
+pub const DEFAULT_INTERVAL: Duration = Duration::from_secs(30);
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+pub fn is_ping(message: &[u8]) -> bool {
+    message == PING
+}
+
+pub fn is_pong(message: &[u8]) -> bool {
+    message == PONG
+}
+
+//runs the keepalive task of a connection, i.e. |task| { tokio::spawn(task); }
+#[cfg(feature = "client")]
+pub type Spawn = Arc<dyn Fn(BoxFuture<'static, ()>) + Send + Sync>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeepAlive {
+    pub(crate) interval: Duration,
+    pub(crate) timeout: Duration,
+}
+
+impl Default for KeepAlive {
+    fn default() -> Self {
+        Self {
+            interval: DEFAULT_INTERVAL,
+            timeout: DEFAULT_TIMEOUT,
+        }
+    }
+}
+
+impl KeepAlive {
+    pub fn new(interval: Duration, timeout: Duration) -> Result<Self> {
+        Self::default().interval(interval)?.timeout(timeout)
+    }
+
+    //between the pings of an idle connection. A zero one would ping nonstop
+    pub fn interval(self, interval: Duration) -> Result<Self> {
+        if interval.is_zero() {
+            return Err(Error::described(ErrorKind::Weird, "the keepalive interval can't be zero"));
+        }
+        Ok(Self { interval, ..self })
+    }
+
+    //for the response to a ping. A zero one would drop the connection on every ping
+    pub fn timeout(self, timeout: Duration) -> Result<Self> {
+        if timeout.is_zero() {
+            return Err(Error::described(ErrorKind::Weird, "the keepalive timeout can't be zero"));
+        }
+        Ok(Self { timeout, ..self })
+    }
+}
@@ -29,6 +29,33 @@ pub mod service;
 #[cfg(feature = "transports")]
 pub mod transports;
 
+#[cfg(feature = "compression")]
+pub mod compression;
+
+#[cfg(feature = "fault-injection")]
+pub mod fault;
+
+#[cfg(feature = "record-replay")]
+pub mod replay;
+
+#[cfg(feature = "keepalive")]
+pub mod keepalive;
+
+#[cfg(feature = "encryption")]
+pub mod encryption;
+
+#[cfg(feature = "pairing")]
+pub mod pairing;
+
+#[cfg(feature = "fragmentation")]
+pub mod fragment;
+
+#[cfg(feature = "qr")]
+pub mod qr;
+
+#[cfg(any(feature = "fault-injection", feature = "qr"))]
+mod rng;
+
 pub use error::Error;
 pub use error::ErrorKind;
 pub use error::Result;
@@ -0,0 +1,111 @@
+//===------------ client.rs --------------------------------------------===//
+//  Copyright 2021, Tesseract Systems, Inc.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//===----------------------------------------------------------------------===//
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::client::Connection;
+use crate::encryption::client::EncryptedConnection;
+use crate::encryption::{random, Identity, PublicKey};
+use crate::Result;
+
+use super::{now, sas, Credentials, PairingUri, DEFAULT_TTL};
+
+//The dApp side of a pairing
+pub struct Pairing {
+    identity: Identity,
+    uri: PairingUri,
+}
+
+impl Pairing {
+    //a new pairing over a new topic of the relay
+    pub fn new(identity: Identity, relay: &str, protocols: &[&str]) -> Result<Self> {
+        let topic: [u8; 16] = random()?;
+        let uri = PairingUri {
+            key: identity.public_key(),
+            relay: relay.to_owned(),
+            topic: topic.iter().map(|byte| format!("{:02x}", byte)).collect(),
+            protocols: protocols.iter().map(|p| (*p).to_owned()).collect(),
+            expires: now() + DEFAULT_TTL.as_secs(),
+        };
+
+        Ok(Self { identity, uri })
+    }
+
+    //how long the wallet has to accept the URI, from now
+    pub fn ttl(self, ttl: Duration) -> Self {
+        let uri = PairingUri {
+            expires: now() + ttl.as_secs(),
+            ..self.uri
+        };
+
+        Self { uri, ..self }
+    }
+
+    //to be shown to the user
+    pub fn uri(&self) -> &PairingUri {
+        &self.uri
+    }
+
+    //makes the handshake with the wallet that has accepted the URI. The connection
+    //must go to the topic of the URI (i.e. made by the relay transport)
+    pub async fn handshake(
+        self,
+        connection: Box<dyn Connection + Sync + Send>,
+    ) -> Result<PendingPairing> {
+        self.uri.check_expiry()?;
+
+        let connection = Arc::new(EncryptedConnection::new(
+            connection,
+            self.identity.clone(),
+            None,
+        ));
+        let session = connection.handshake().await?;
+
+        Ok(PendingPairing {
+            sas: sas(&session),
+            credentials: Credentials {
+                identity: self.identity,
+                peer: session.peer,
+                relay: self.uri.relay,
+                topic: self.uri.topic,
+                protocols: self.uri.protocols,
+                paired_at: now(),
+            },
+        })
+    }
+}
+
+//The pairing waiting for the user to compare the SAS. Dropping it rejects the pairing
+pub struct PendingPairing {
+    sas: String,
+    credentials: Credentials,
+}
+
+impl PendingPairing {
+    pub fn sas(&self) -> &str {
+        &self.sas
+    }
+
+    pub fn wallet(&self) -> PublicKey {
+        self.credentials.peer
+    }
+
+    //the user has seen the same SAS on the wallet
+    pub fn confirm(self) -> Credentials {
+        self.credentials
+    }
+}
@@ -0,0 +1,186 @@
+//===------------ mod.rs --------------------------------------------===//
+//  Copyright 2021, Tesseract Systems, Inc.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//===----------------------------------------------------------------------===//
+
+//Out-of-band pairing of a dApp with a wallet for the encrypted sessions.
+//
+//The dApp makes a pairing URI and shows it (i.e. as a QR code):
+//  tesseract:pair?v=1&key=<dApp's public key>&relay=<relay address>&topic=<relay topic>
+//    &protocols=<protocol,protocol>&expires=<unix time in seconds>
+//The wallet accepts the URI, joins the relay topic and lets only the dApp's key make a session.
+//The dApp makes the handshake over the topic (see the encryption module) and learns the wallet's key.
+//
+//Nobody can read the session, but a relay in the middle could have made the handshake with both sides.
+//So both show the short authentication string (SAS) of their session and the user confirms they match.
+//The SAS includes the nonce the dApp reveals only after the welcome (see the encryption module),
+//so the one in the middle can't try the welcomes until both codes match.
+//The confirmed pairing gives the Credentials to be stored and used for the later sessions.
+
+#[cfg(feature = "client")]
+pub mod client;
+
+#[cfg(feature = "service")]
+pub mod service;
+
+use std::fmt;
+use std::str::FromStr;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use url::Url;
+
+use crate::encryption::{Identity, PublicKey, SessionInfo};
+use crate::{Error, ErrorKind, Result};
+
+pub const SCHEME: &str = "tesseract";
+pub const PAIR_PATH: &str = "pair";
+pub const VERSION: u32 = 1;
+
+pub const DEFAULT_TTL: Duration = Duration::from_secs(5 * 60);
+
+const SAS_INFO: &[u8] = b"tesseract-sas-v1";
+
+fn malformed(description: &str) -> Error {
+    Error::described(
+        ErrorKind::Serialization,
+        &format!("malformed pairing URI: {}", description),
+    )
+}
+
+pub(crate) fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|now| now.as_secs())
+        .unwrap_or_default()
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PairingUri {
+    pub key: PublicKey,
+    pub relay: String,
+    pub topic: String,
+    pub protocols: Vec<String>,
+    pub expires: u64, //unix time in seconds
+}
+
+impl PairingUri {
+    pub fn is_expired(&self) -> bool {
+        now() >= self.expires
+    }
+
+    pub fn check_expiry(&self) -> Result<()> {
+        if self.is_expired() {
+            Err(Error::described(
+                ErrorKind::Cancelled,
+                "the pairing has expired",
+            ))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl fmt::Display for PairingUri {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut uri = Url::parse(&format!("{}:{}", SCHEME, PAIR_PATH)).map_err(|_| fmt::Error)?;
+
+        uri.query_pairs_mut()
+            .append_pair("v", &VERSION.to_string())
+            .append_pair("key", &self.key.to_string())
+            .append_pair("relay", &self.relay)
+            .append_pair("topic", &self.topic)
+            .append_pair("protocols", &self.protocols.join(","))
+            .append_pair("expires", &self.expires.to_string());
+
+        write!(f, "{}", uri)
+    }
+}
+
+impl FromStr for PairingUri {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let uri = Url::parse(s)
+            .map_err(|e| Error::new(ErrorKind::Serialization, "can't parse pairing URI", e))?;
+
+        if uri.scheme() != SCHEME || uri.path() != PAIR_PATH {
+            return Err(malformed("not a Tesseract pairing URI"));
+        }
+
+        let param = |name: &str| {
+            uri.query_pairs()
+                .find(|(key, _)| key == name)
+                .map(|(_, value)| value.into_owned())
+                .ok_or_else(|| malformed(&format!("no '{}'", name)))
+        };
+
+        let version: u32 = param("v")?
+            .parse()
+            .map_err(|_| malformed("invalid version"))?;
+        if version != VERSION {
+            return Err(Error::described(
+                ErrorKind::UnsupportedVersion,
+                &format!("unsupported pairing version: {}", version),
+            ));
+        }
+
+        let protocols: Vec<String> = param("protocols")?
+            .split(',')
+            .filter(|protocol| !protocol.is_empty())
+            .map(str::to_owned)
+            .collect();
+        if protocols.is_empty() {
+            return Err(malformed("no protocols"));
+        }
+
+        let topic = param("topic")?;
+        if topic.is_empty() {
+            return Err(malformed("empty topic"));
+        }
+
+        Ok(Self {
+            key: param("key")?.parse()?,
+            relay: param("relay")?,
+            topic,
+            protocols,
+            expires: param("expires")?
+                .parse()
+                .map_err(|_| malformed("invalid expiry"))?,
+        })
+    }
+}
+
+//Six digits for the user to compare on both sides. They only match if both sides have the same session
+pub fn sas(session: &SessionInfo) -> String {
+    let digest = Sha256::new()
+        .chain_update(SAS_INFO)
+        .chain_update(session.transcript)
+        .finalize();
+    let number = u32::from_be_bytes([digest[0], digest[1], digest[2], digest[3]]);
+
+    format!("{:06}", number % 1_000_000)
+}
+
+//What a side needs to talk to its peer again. Contains the secret identity, so store it safely
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Credentials {
+    pub identity: Identity,
+    pub peer: PublicKey,
+    pub relay: String,
+    pub topic: String,
+    pub protocols: Vec<String>,
+    pub paired_at: u64, //unix time in seconds
+}
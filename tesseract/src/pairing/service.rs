@@ -0,0 +1,105 @@
+//===------------ service.rs --------------------------------------------===//
+//  Copyright 2021, Tesseract Systems, Inc.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//===----------------------------------------------------------------------===//
+
+use std::sync::{Arc, Mutex};
+
+use crate::encryption::service::EncryptedTransport;
+use crate::encryption::{Identity, SessionInfo};
+use crate::service::Transport;
+use crate::{Error, ErrorKind, Result};
+
+use super::{now, sas, Credentials, PairingUri};
+
+//The wallet side of a pairing
+pub struct Pairing {
+    identity: Identity,
+    uri: PairingUri,
+    protocols: Vec<String>,
+    session: Arc<Mutex<Option<SessionInfo>>>,
+}
+
+impl Pairing {
+    //fails if the URI is malformed, expired or asks for none of the protocols the wallet supports
+    pub fn accept(uri: &str, identity: Identity, protocols: &[&str]) -> Result<Self> {
+        let uri: PairingUri = uri.parse()?;
+        uri.check_expiry()?;
+
+        let protocols: Vec<String> = uri
+            .protocols
+            .iter()
+            .filter(|protocol| protocols.contains(&protocol.as_str()))
+            .cloned()
+            .collect();
+        if protocols.is_empty() {
+            return Err(Error::described(
+                ErrorKind::Cancelled,
+                "the dApp asks for none of the supported protocols",
+            ));
+        }
+
+        Ok(Self {
+            identity,
+            uri,
+            protocols,
+            session: Arc::new(Mutex::new(None)),
+        })
+    }
+
+    pub fn uri(&self) -> &PairingUri {
+        &self.uri
+    }
+
+    //the protocols of the URI the wallet supports
+    pub fn protocols(&self) -> &[String] {
+        &self.protocols
+    }
+
+    //wraps the transport to the topic of the URI (i.e. the relay one).
+    //Only the dApp of the URI can make a session over it
+    pub fn transport<T: Transport>(&self, transport: T) -> EncryptedTransport<T> {
+        let dapp = self.uri.key;
+        let session = Arc::clone(&self.session);
+
+        EncryptedTransport::new(transport, self.identity.clone())
+            .authorize(move |key| *key == dapp)
+            .on_session(move |info| *session.lock().unwrap() = Some(info.clone()))
+    }
+
+    //None until the dApp makes the handshake
+    pub fn session(&self) -> Option<SessionInfo> {
+        self.session.lock().unwrap().clone()
+    }
+
+    pub fn sas(&self) -> Option<String> {
+        self.session().map(|session| sas(&session))
+    }
+
+    //the user has seen the same SAS on the dApp
+    pub fn confirm(&self) -> Result<Credentials> {
+        let session = self.session().ok_or_else(|| {
+            Error::described(ErrorKind::Cancelled, "the dApp hasn't connected yet")
+        })?;
+
+        Ok(Credentials {
+            identity: self.identity.clone(),
+            peer: session.peer,
+            relay: self.uri.relay.clone(),
+            topic: self.uri.topic.clone(),
+            protocols: self.protocols.clone(),
+            paired_at: now(),
+        })
+    }
+}
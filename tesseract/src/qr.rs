@@ -0,0 +1,399 @@
+//===------------ qr.rs --------------------------------------------------===//
+//  Copyright 2021, Tesseract Systems, Inc.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//===----------------------------------------------------------------------===//
+
+//Animated QR frames for the air-gapped wallets.
+//
+//A marked message is split into equal fragments (the last one is zero padded) and sent as
+//an endless sequence of text frames:
+//
+//  TESSERACT/<seq>-<count>/<BASE32 of: message length (u32) | checksum (u32) | part>
+//
+//The text is uppercase base32 without padding, so it fits the compact alphanumeric QR mode.
+//The frames 1..=count carry the fragments as is. The frames after that are fountain coded:
+//each is the XOR of a pseudo-random set of fragments, picked by a PRNG seeded with the frame
+//number and the checksum, so the decoder knows the set without it being sent.
+//The frames can be scanned in any order and any of them can be missed: the decoder
+//completes the message once it has seen enough of them.
+
+use std::mem;
+
+use data_encoding::BASE32_NOPAD;
+
+use crate::fragment::crc32;
+use crate::rng::Rng;
+use crate::serialize::Limits;
+use crate::{Error, ErrorKind, Result};
+
+pub const PREFIX: &str = "TESSERACT";
+pub const DEFAULT_FRAGMENT_LEN: usize = 200;
+
+const HEADER_LEN: usize = 8;
+
+fn malformed(frame: &str, reason: &str) -> Error {
+    Error::described(
+        ErrorKind::Serialization,
+        &format!("malformed QR frame '{}': {}", frame, reason),
+    )
+}
+
+fn xor(into: &mut [u8], other: &[u8]) {
+    into.iter_mut().zip(other).for_each(|(a, b)| *a ^= b);
+}
+
+//the degree (number of the fragments mixed in a frame) is picked with the probability of 1/degree
+fn choose_degree(rng: &mut Rng, count: usize) -> usize {
+    let total: f64 = (1..=count).map(|d| 1.0 / d as f64).sum();
+    let mut point = rng.next_f64() * total;
+
+    for degree in 1..=count {
+        point -= 1.0 / degree as f64;
+        if point < 0.0 {
+            return degree;
+        }
+    }
+    count
+}
+
+//indices of the fragments XORed into the frame, sorted
+fn choose_fragments(seq: u32, count: usize, checksum: u32) -> Vec<usize> {
+    if seq as usize <= count {
+        return vec![seq as usize - 1];
+    }
+
+    let mut rng = Rng::new(((checksum as u64) << 32) | seq as u64);
+    let degree = choose_degree(&mut rng, count);
+
+    let mut indices: Vec<usize> = (0..count).collect();
+    for i in 0..degree {
+        let j = i + rng.below((count - i) as u64) as usize;
+        indices.swap(i, j);
+    }
+    indices.truncate(degree);
+    indices.sort_unstable();
+    indices
+}
+
+pub struct Encoder {
+    fragments: Vec<Vec<u8>>,
+    len: u32,
+    checksum: u32,
+    seq: u32,
+}
+
+impl Encoder {
+    pub fn new(message: &[u8], fragment_len: usize) -> Result<Self> {
+        if fragment_len == 0 {
+            return Err(Error::described(
+                ErrorKind::Serialization,
+                "QR fragment length can't be zero",
+            ));
+        }
+        let len = u32::try_from(message.len()).map_err(|_| {
+            Error::described(
+                ErrorKind::Serialization,
+                &format!("message of {} bytes is too big for QR", message.len()),
+            )
+        })?;
+
+        let mut fragments: Vec<Vec<u8>> = message
+            .chunks(fragment_len)
+            .map(|chunk| {
+                let mut fragment = chunk.to_vec();
+                fragment.resize(fragment_len, 0);
+                fragment
+            })
+            .collect();
+        if fragments.is_empty() {
+            fragments.push(vec![0; fragment_len]);
+        }
+
+        Ok(Self {
+            fragments,
+            len,
+            checksum: crc32(message),
+            seq: 0,
+        })
+    }
+
+    //number of the fragments. A message of a single fragment doesn't need the animation
+    pub fn count(&self) -> usize {
+        self.fragments.len()
+    }
+
+    pub fn is_single(&self) -> bool {
+        self.count() == 1
+    }
+
+    //the frame number seq, starting from 1. Any number can be made, there is no last frame
+    pub fn frame(&self, seq: u32) -> String {
+        let seq = seq.max(1);
+
+        let mut part = vec![0; self.fragments[0].len()];
+        for index in choose_fragments(seq, self.count(), self.checksum) {
+            xor(&mut part, &self.fragments[index]);
+        }
+
+        let mut body = Vec::with_capacity(HEADER_LEN + part.len());
+        body.extend_from_slice(&self.len.to_be_bytes());
+        body.extend_from_slice(&self.checksum.to_be_bytes());
+        body.extend_from_slice(&part);
+
+        format!(
+            "{}/{}-{}/{}",
+            PREFIX,
+            seq,
+            self.count(),
+            BASE32_NOPAD.encode(&body)
+        )
+    }
+
+    //the frames to show one after another in a loop
+    pub fn next_frame(&mut self) -> String {
+        self.seq = self.seq.checked_add(1).unwrap_or(1);
+        self.frame(self.seq)
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+struct Header {
+    count: usize,
+    len: usize,
+    checksum: u32,
+}
+
+#[derive(Default)]
+pub struct Decoder {
+    limits: Limits,
+    header: Option<Header>,
+    fragments: Vec<Option<Vec<u8>>>,
+    mixed: Vec<(Vec<usize>, Vec<u8>)>,
+    solved: usize,
+    complete: bool,
+}
+
+impl Decoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    //the message length in the frames is checked against max_size before anything is allocated
+    pub fn limits(self, limits: Limits) -> Self {
+        Self { limits, ..self }
+    }
+
+    //number of the fragments of the message. None until the first frame is received
+    pub fn count(&self) -> Option<usize> {
+        self.header.map(|h| h.count)
+    }
+
+    //number of the fragments recovered so far
+    pub fn solved(&self) -> usize {
+        self.solved
+    }
+
+    //bytes kept for the frames that can't be decoded yet. Never over the limits' max_size
+    pub fn pending(&self) -> usize {
+        self.mixed.iter().map(|(indices, part)| Self::mixed_size(indices, part)).sum()
+    }
+
+    fn mixed_size(indices: &[usize], part: &[u8]) -> usize {
+        part.len() + mem::size_of_val(indices)
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.complete
+    }
+
+    //returns the message once enough frames are received. The frames received after
+    //that are ignored. Fails on a frame of another message
+    pub fn receive(&mut self, frame: &str) -> Result<Option<Vec<u8>>> {
+        let (seq, header, part) = Self::parse(frame)?;
+
+        if header.len > self.limits.max_size {
+            return Err(malformed(
+                frame,
+                &format!(
+                    "message length {} exceeds the limit of {} bytes",
+                    header.len, self.limits.max_size
+                ),
+            ));
+        }
+        //tiny fragments of a big message would make the bookkeeping bigger than the message itself
+        if header.count > self.limits.max_size / mem::size_of::<Option<Vec<u8>>>() {
+            return Err(malformed(
+                frame,
+                &format!("too many fragments: {}", header.count),
+            ));
+        }
+
+        match self.header {
+            None => {
+                self.header = Some(header);
+                self.fragments = vec![None; header.count];
+            }
+            Some(current) if current != header => {
+                return Err(malformed(frame, "the frame belongs to another message"));
+            }
+            Some(_) => {}
+        }
+
+        if self.complete {
+            return Ok(None);
+        }
+
+        if self.fragment_len().is_some_and(|len| len != part.len()) {
+            return Err(malformed(frame, "unexpected fragment length"));
+        }
+
+        let indices = choose_fragments(seq, header.count, header.checksum);
+        self.add(indices, part);
+
+        if self.solved < header.count {
+            return Ok(None);
+        }
+
+        self.complete = true;
+        let mut message: Vec<u8> = std::mem::take(&mut self.fragments)
+            .into_iter()
+            .flatten()
+            .flatten()
+            .collect();
+        message.truncate(header.len);
+
+        if crc32(&message) == header.checksum {
+            Ok(Some(message))
+        } else {
+            Err(Error::described(
+                ErrorKind::Serialization,
+                "checksum mismatch in the message decoded from QR frames",
+            ))
+        }
+    }
+
+    fn fragment_len(&self) -> Option<usize> {
+        self.fragments
+            .iter()
+            .flatten()
+            .chain(self.mixed.iter().map(|(_, part)| part))
+            .map(|f| f.len())
+            .next()
+    }
+
+    fn parse(frame: &str) -> Result<(u32, Header, Vec<u8>)> {
+        let mut parts = frame.trim().splitn(3, '/');
+        let (prefix, seq_count, body) = match (parts.next(), parts.next(), parts.next()) {
+            (Some(prefix), Some(seq_count), Some(body)) => (prefix, seq_count, body),
+            _ => return Err(malformed(frame, "expected 3 parts separated with '/'")),
+        };
+
+        if !prefix.eq_ignore_ascii_case(PREFIX) {
+            return Err(malformed(frame, "not a Tesseract frame"));
+        }
+
+        let (seq, count) = seq_count
+            .split_once('-')
+            .and_then(|(seq, count)| Some((seq.parse::<u32>().ok()?, count.parse::<usize>().ok()?)))
+            .filter(|(seq, count)| *seq > 0 && *count > 0)
+            .ok_or_else(|| malformed(frame, "bad frame number"))?;
+
+        let body = BASE32_NOPAD
+            .decode(body.to_ascii_uppercase().as_bytes())
+            .map_err(|e| Error::new(ErrorKind::Serialization, "can't decode QR frame", e))?;
+        if body.len() <= HEADER_LEN {
+            return Err(malformed(frame, "the frame is too short"));
+        }
+
+        let len = u32::from_be_bytes([body[0], body[1], body[2], body[3]]) as usize;
+        let checksum = u32::from_be_bytes([body[4], body[5], body[6], body[7]]);
+        let part = body[HEADER_LEN..].to_vec();
+
+        if len.div_ceil(part.len()).max(1) != count {
+            return Err(malformed(
+                frame,
+                "the message length doesn't match the frame count",
+            ));
+        }
+
+        Ok((
+            seq,
+            Header {
+                count,
+                len,
+                checksum,
+            },
+            part,
+        ))
+    }
+
+    fn add(&mut self, mut indices: Vec<usize>, mut part: Vec<u8>) {
+        let fragments = &self.fragments;
+        indices.retain(|i| match &fragments[*i] {
+            Some(fragment) => {
+                xor(&mut part, fragment);
+                false
+            }
+            None => true,
+        });
+
+        match indices.len() {
+            0 => {}
+            1 => self.solve(indices[0], part),
+            _ => {
+                if self.mixed.iter().any(|(known, _)| *known == indices) {
+                    return;
+                }
+
+                //the oldest frames give way, the fountain keeps sending new ones
+                let size = Self::mixed_size(&indices, &part);
+                if size > self.limits.max_size {
+                    return;
+                }
+                let mut pending = self.pending();
+                while pending + size > self.limits.max_size {
+                    let (indices, part) = self.mixed.remove(0);
+                    pending -= Self::mixed_size(&indices, &part);
+                }
+
+                self.mixed.push((indices, part));
+            }
+        }
+    }
+
+    //a recovered fragment is XORed out of the mixed parts, which may recover more of them
+    fn solve(&mut self, index: usize, fragment: Vec<u8>) {
+        let mut queue = vec![(index, fragment)];
+
+        while let Some((index, fragment)) = queue.pop() {
+            if self.fragments[index].is_some() {
+                continue;
+            }
+
+            self.mixed.retain_mut(|(indices, part)| {
+                if let Some(position) = indices.iter().position(|i| *i == index) {
+                    indices.remove(position);
+                    xor(part, &fragment);
+                }
+                if indices.len() == 1 {
+                    queue.push((indices[0], std::mem::take(part)));
+                }
+                indices.len() > 1
+            });
+
+            self.fragments[index] = Some(fragment);
+            self.solved += 1;
+        }
+    }
+}
@@ -0,0 +1,252 @@
+//===------------ client.rs --------------------------------------------===//
+//  Copyright 2021, Tesseract Systems, Inc.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//===----------------------------------------------------------------------===//
+
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+
+use crate::Protocol;
+use crate::{Error, ErrorKind, Result};
+
+use crate::client::transport::{Endpoint, Status};
+use crate::client::Connection;
+use crate::client::Transport;
+
+use super::{read_cassette, write_exchange, Exchange};
+
+pub const REPLAY: &str = "replay";
+
+struct Recorder {
+    file: Mutex<File>,
+}
+
+impl Recorder {
+    fn record(&self, exchange: &Exchange) -> Result<()> {
+        let mut file = self.file.lock().unwrap();
+
+        write_exchange(&mut *file, exchange)
+            .map_err(|e| Error::new(ErrorKind::Transport, "can't record the exchange", e))
+    }
+}
+
+struct RecordingConnection {
+    connection: Arc<dyn Connection + Sync + Send>,
+    recorder: Arc<Recorder>,
+    //sent and waiting for their responses
+    requests: Mutex<VecDeque<Vec<u8>>>,
+}
+
+#[async_trait]
+impl Connection for RecordingConnection {
+    async fn send(self: Arc<Self>, request: Vec<u8>) -> Result<()> {
+        self.requests.lock().unwrap().push_back(request.clone());
+
+        let result = Arc::clone(&self.connection).send(request).await;
+        if result.is_err() {
+            self.requests.lock().unwrap().pop_back();
+        }
+
+        result
+    }
+
+    async fn receive(self: Arc<Self>) -> Result<Vec<u8>> {
+        let response = Arc::clone(&self.connection).receive().await?;
+
+        let request = self.requests.lock().unwrap().pop_front();
+        if let Some(request) = request {
+            self.recorder.record(&Exchange {
+                request,
+                response: response.clone(),
+            })?;
+        }
+
+        Ok(response)
+    }
+}
+
+//Records into a new cassette, all the connections of the transport into the same one
+pub struct RecordingTransport<T: Transport> {
+    transport: Arc<T>,
+    recorder: Arc<Recorder>,
+}
+
+impl<T: Transport> RecordingTransport<T> {
+    pub fn create<P: AsRef<Path>>(transport: T, cassette: P) -> io::Result<Self> {
+        Ok(Self {
+            transport: Arc::new(transport),
+            recorder: Arc::new(Recorder {
+                file: Mutex::new(File::create(cassette)?),
+            }),
+        })
+    }
+
+    fn wrap(&self, connection: Box<dyn Connection + Sync + Send>) -> Box<dyn Connection + Sync + Send> {
+        Box::new(RecordingConnection {
+            connection: Arc::from(connection),
+            recorder: Arc::clone(&self.recorder),
+            requests: Mutex::new(VecDeque::new()),
+        })
+    }
+}
+
+#[async_trait]
+impl<T: Transport + Sync + Send> Transport for RecordingTransport<T> {
+    fn id(&self) -> String {
+        self.transport.id()
+    }
+
+    async fn status(self: Arc<Self>, protocol: Box<dyn Protocol>) -> Status {
+        Arc::clone(&self.transport).status(protocol).await
+    }
+
+    fn connect(&self, protocol: Box<dyn Protocol>) -> Box<dyn Connection + Sync + Send> {
+        self.wrap(self.transport.connect(protocol))
+    }
+
+    async fn endpoints(self: Arc<Self>, protocol: Box<dyn Protocol>) -> Vec<Endpoint>
+    where
+        Self: 'static,
+    {
+        Arc::clone(&self.transport).endpoints(protocol).await
+    }
+
+    fn connect_endpoint(
+        &self,
+        endpoint: &str,
+        protocol: Box<dyn Protocol>,
+    ) -> Box<dyn Connection + Sync + Send> {
+        self.wrap(self.transport.connect_endpoint(endpoint, protocol))
+    }
+}
+
+//A request that differs from the recorded one, or comes after the cassette has ended (expected is None)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mismatch {
+    pub index: usize,
+    pub expected: Option<Vec<u8>>,
+    pub actual: Vec<u8>,
+}
+
+struct Player {
+    exchanges: Mutex<VecDeque<Exchange>>,
+    played: Mutex<usize>,
+    mismatches: Mutex<Vec<Mismatch>>,
+}
+
+impl Player {
+    //the recorded response if the request is the recorded one. The exchange is used up either way
+    fn play(&self, request: &[u8]) -> Result<Vec<u8>> {
+        let exchange = self.exchanges.lock().unwrap().pop_front();
+
+        let index = {
+            let mut played = self.played.lock().unwrap();
+            *played += 1;
+            *played - 1
+        };
+
+        match exchange {
+            Some(exchange) if exchange.request == request => Ok(exchange.response),
+            exchange => {
+                let description = match &exchange {
+                    Some(_) => format!("request #{} doesn't match the recorded one", index),
+                    None => format!("request #{} is beyond the recording", index),
+                };
+
+                self.mismatches.lock().unwrap().push(Mismatch {
+                    index,
+                    expected: exchange.map(|exchange| exchange.request),
+                    actual: request.to_vec(),
+                });
+
+                Err(Error::described(ErrorKind::Transport, &description))
+            }
+        }
+    }
+}
+
+struct ReplayConnection {
+    player: Arc<Player>,
+    responses: Mutex<VecDeque<Vec<u8>>>,
+}
+
+#[async_trait]
+impl Connection for ReplayConnection {
+    async fn send(self: Arc<Self>, request: Vec<u8>) -> Result<()> {
+        let response = self.player.play(&request)?;
+        self.responses.lock().unwrap().push_back(response);
+        Ok(())
+    }
+
+    async fn receive(self: Arc<Self>) -> Result<Vec<u8>> {
+        self.responses.lock().unwrap().pop_front().ok_or_else(|| {
+            Error::described(ErrorKind::Transport, "no request has been sent")
+        })
+    }
+}
+
+//Plays one cassette for all its connections, in the order the requests come.
+//The clones share the cassette, so a test can keep one to check the mismatches after the session
+#[derive(Clone)]
+pub struct ReplayTransport {
+    player: Arc<Player>,
+}
+
+impl ReplayTransport {
+    pub fn open<P: AsRef<Path>>(cassette: P) -> io::Result<Self> {
+        Ok(Self::new(read_cassette(cassette)?))
+    }
+
+    pub fn new(exchanges: Vec<Exchange>) -> Self {
+        Self {
+            player: Arc::new(Player {
+                exchanges: Mutex::new(exchanges.into()),
+                played: Mutex::new(0),
+                mismatches: Mutex::new(Vec::new()),
+            }),
+        }
+    }
+
+    pub fn mismatches(&self) -> Vec<Mismatch> {
+        self.player.mismatches.lock().unwrap().clone()
+    }
+
+    //the exchanges that haven't been played yet
+    pub fn remaining(&self) -> usize {
+        self.player.exchanges.lock().unwrap().len()
+    }
+}
+
+#[async_trait]
+impl Transport for ReplayTransport {
+    fn id(&self) -> String {
+        REPLAY.to_owned()
+    }
+
+    async fn status(self: Arc<Self>, _: Box<dyn Protocol>) -> Status {
+        Status::Ready
+    }
+
+    fn connect(&self, _: Box<dyn Protocol>) -> Box<dyn Connection + Sync + Send> {
+        Box::new(ReplayConnection {
+            player: Arc::clone(&self.player),
+            responses: Mutex::new(VecDeque::new()),
+        })
+    }
+}
@@ -0,0 +1,102 @@
+//===------------ mod.rs --------------------------------------------===//
+//  Copyright 2021, Tesseract Systems, Inc.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//===----------------------------------------------------------------------===//
+
+//Recording of the exchanges with a real wallet and their replay in the tests that can't have one.
+//
+//client::RecordingTransport wraps any client transport and writes every marked request and
+//the response to it into a cassette. client::ReplayTransport serves the responses from
+//the cassette back in the same order, as long as the requests are the same as recorded.
+//
+//A cassette has an exchange per line, each a JSON object:
+//  {"request":{"text":"json{...}"},"response":{"base64":"Y2Jvc..."}}
+//The messages that are valid UTF-8 (i.e. JSON ones) are kept as text to be readable in fixtures.
+
+#[cfg(feature = "client")]
+pub mod client;
+
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::Path;
+
+use data_encoding::BASE64;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum Data {
+    Text(String),
+    Base64(String),
+}
+
+impl Data {
+    fn new(message: &[u8]) -> Self {
+        match std::str::from_utf8(message) {
+            Ok(text) => Self::Text(text.to_owned()),
+            Err(_) => Self::Base64(BASE64.encode(message)),
+        }
+    }
+
+    fn bytes(&self) -> io::Result<Vec<u8>> {
+        match self {
+            Self::Text(text) => Ok(text.as_bytes().to_vec()),
+            Self::Base64(data) => BASE64
+                .decode(data.as_bytes())
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e)),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct Line {
+    request: Data,
+    response: Data,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Exchange {
+    pub request: Vec<u8>,
+    pub response: Vec<u8>,
+}
+
+pub fn read_cassette<P: AsRef<Path>>(path: P) -> io::Result<Vec<Exchange>> {
+    let reader = BufReader::new(File::open(path)?);
+
+    reader
+        .lines()
+        .filter(|line| !matches!(line, Ok(line) if line.trim().is_empty()))
+        .map(|line| {
+            let line: Line = serde_json::from_str(&line?)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+            Ok(Exchange {
+                request: line.request.bytes()?,
+                response: line.response.bytes()?,
+            })
+        })
+        .collect()
+}
+
+pub(crate) fn write_exchange<W: Write>(writer: &mut W, exchange: &Exchange) -> io::Result<()> {
+    let line = Line {
+        request: Data::new(&exchange.request),
+        response: Data::new(&exchange.response),
+    };
+
+    serde_json::to_writer(&mut *writer, &line)?;
+    writer.write_all(b"\n")?;
+    writer.flush()
+}
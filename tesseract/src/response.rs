@@ -16,7 +16,7 @@
 
 use serde::{Deserialize, Serialize};
 
-use super::error::{Error, Result};
+use super::error::{Error, ErrorKind, Result};
 
 #[derive(Serialize, Deserialize)]
 #[serde(tag = "status")]
@@ -24,6 +24,9 @@ use super::error::{Error, Result};
 pub enum Response<R> {
     Ok(R),
     Error(Error),
+    //a status introduced by a newer peer
+    #[serde(other)]
+    Unknown,
 }
 
 impl<R> Response<R> {
@@ -31,6 +34,10 @@ impl<R> Response<R> {
         match self {
             Self::Ok(response) => Ok(response),
             Self::Error(error) => Err(error),
+            Self::Unknown => Err(Error::described(
+                ErrorKind::Serialization,
+                "the response has an unknown status",
+            )),
         }
     }
 
@@ -68,15 +68,31 @@ impl Serializer {
         }
     }
 
+    //MIME type of the data serialized with the serializer, for the transports that need one (i.e. HTTP)
+    #[inline]
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            Self::Json => "application/json",
+            Self::Cbor => "application/cbor",
+        }
+    }
+
     #[inline]
     pub fn marker_len() -> usize {
         4
     }
 
-    pub fn read_marker<'a>(from: &'a [u8]) -> Result<(Self, &'a [u8])> {
-        let marker = &from[0..Self::marker_len()];
+    pub fn read_marker(from: &[u8]) -> Result<(Self, &[u8])> {
+        if from.len() < Self::marker_len() {
+            return Err(Error::described(
+                ErrorKind::Serialization,
+                &format!("message is too short to contain a marker: {} bytes", from.len()),
+            ));
+        }
 
-        Self::from_marker(marker).map(|s| (s, &from[Self::marker_len()..]))
+        let (marker, data) = from.split_at(Self::marker_len());
+
+        Self::from_marker(marker).map(|s| (s, data))
     }
 
     //could be optimized (probably) with Write, though good enough for now
@@ -111,8 +127,224 @@ impl Serializer {
     }
 
     pub fn deserialize_marked<'de, T: Deserialize<'de>>(from: &'de [u8]) -> Result<(T, Self)> {
+        Self::deserialize_marked_limited(from, &Limits::default())
+    }
+
+    //checks the size and the nesting of the data before deserializing it
+    pub fn deserialize_limited<'de, T: Deserialize<'de>>(
+        &self,
+        from: &'de [u8],
+        limits: &Limits,
+    ) -> Result<T> {
+        limits.check(*self, from)?;
+        self.deserialize(from)
+    }
+
+    pub fn deserialize_marked_limited<'de, T: Deserialize<'de>>(
+        from: &'de [u8],
+        limits: &Limits,
+    ) -> Result<(T, Self)> {
         let (serializer, data) = Self::read_marker(from)?;
 
-        serializer.deserialize(data).map(|t| (t, serializer))
+        serializer
+            .deserialize_limited(data, limits)
+            .map(|t| (t, serializer))
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Limits {
+    pub max_size: usize,  //max size of a message in bytes
+    pub max_depth: usize, //max nesting of arrays and maps (objects)
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+impl Limits {
+    pub const DEFAULT: Self = Self {
+        max_size: 64 * 1024 * 1024,
+        max_depth: 64,
+    };
+
+    pub fn max_size(self, max_size: usize) -> Self {
+        Self { max_size, ..self }
+    }
+
+    pub fn max_depth(self, max_depth: usize) -> Self {
+        Self { max_depth, ..self }
+    }
+
+    pub fn check_size(&self, data: &[u8]) -> Result<()> {
+        if data.len() > self.max_size {
+            Err(Error::described(
+                ErrorKind::Serialization,
+                &format!(
+                    "message size {} exceeds the limit of {} bytes",
+                    data.len(),
+                    self.max_size
+                ),
+            ))
+        } else {
+            Ok(())
+        }
+    }
+
+    //a quick scan that doesn't allocate anything besides a stack of open containers
+    pub fn check(&self, serializer: Serializer, data: &[u8]) -> Result<()> {
+        self.check_size(data)?;
+
+        let depth = match serializer {
+            Serializer::Json => json_depth(data),
+            Serializer::Cbor => cbor_depth(data, self.max_depth)?,
+        };
+
+        if depth > self.max_depth {
+            Err(Error::described(
+                ErrorKind::Serialization,
+                &format!("message nesting exceeds the limit of {}", self.max_depth),
+            ))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+//malformed JSON is left for serde_json to report
+fn json_depth(data: &[u8]) -> usize {
+    let mut depth = 0usize;
+    let mut max = 0usize;
+    let mut in_string = false;
+    let mut escaped = false;
+
+    for byte in data {
+        if in_string {
+            match byte {
+                _ if escaped => escaped = false,
+                b'\\' => escaped = true,
+                b'"' => in_string = false,
+                _ => (),
+            }
+        } else {
+            match byte {
+                b'"' => in_string = true,
+                b'[' | b'{' => {
+                    depth += 1;
+                    max = max.max(depth);
+                }
+                b']' | b'}' => depth = depth.saturating_sub(1),
+                _ => (),
+            }
+        }
+    }
+
+    max
+}
+
+//walks the headers of the first CBOR item. Stops as soon as the limit is exceeded,
+//so that a malicious input can't make the stack grow
+fn cbor_depth(data: &[u8], limit: usize) -> Result<usize> {
+    let truncated = || Error::described(ErrorKind::Serialization, "truncated CBOR data");
+
+    //remaining items of every open container. None is for the indefinite length ones
+    let mut open: Vec<Option<u64>> = Vec::new();
+    let mut max = 0usize;
+    let mut pos = 0usize;
+
+    loop {
+        let initial = *data.get(pos).ok_or_else(truncated)?;
+        pos += 1;
+
+        let major = initial >> 5;
+        let info = initial & 0x1f;
+
+        let complete = if initial == 0xff {
+            match open.pop() {
+                Some(None) => true,
+                _ => {
+                    return Err(Error::described(
+                        ErrorKind::Serialization,
+                        "unexpected CBOR break",
+                    ))
+                }
+            }
+        } else {
+            let argument = match info {
+                0..=23 => Some(info as u64),
+                24..=27 => {
+                    let len = 1usize << (info - 24);
+                    let bytes = data.get(pos..pos + len).ok_or_else(truncated)?;
+                    pos += len;
+                    Some(bytes.iter().fold(0u64, |acc, b| (acc << 8) | *b as u64))
+                }
+                31 => None,
+                _ => {
+                    return Err(Error::described(
+                        ErrorKind::Serialization,
+                        "invalid CBOR additional info",
+                    ))
+                }
+            };
+
+            match (major, argument) {
+                (2 | 3, Some(len)) => {
+                    let len = usize::try_from(len).map_err(|_| truncated())?;
+                    pos = pos
+                        .checked_add(len)
+                        .filter(|end| *end <= data.len())
+                        .ok_or_else(truncated)?;
+                    true
+                }
+                (2..=5, None) => {
+                    open.push(None);
+                    false
+                }
+                (4, Some(0)) | (5, Some(0)) => true,
+                (4, Some(len)) => {
+                    open.push(Some(len));
+                    false
+                }
+                (5, Some(len)) => {
+                    open.push(Some(len.saturating_mul(2)));
+                    false
+                }
+                (6, Some(_)) => {
+                    open.push(Some(1));
+                    false
+                }
+                (0 | 1 | 7, Some(_)) => true,
+                _ => {
+                    return Err(Error::described(
+                        ErrorKind::Serialization,
+                        "invalid CBOR item header",
+                    ))
+                }
+            }
+        };
+
+        max = max.max(open.len());
+        if max > limit {
+            return Ok(max);
+        }
+
+        if complete {
+            loop {
+                match open.last_mut() {
+                    None => return Ok(max),
+                    Some(None) => break,
+                    Some(Some(remaining)) => {
+                        *remaining -= 1;
+                        if *remaining == 0 {
+                            open.pop();
+                        } else {
+                            break;
+                        }
+                    }
+                }
+            }
+        }
     }
 }
@@ -0,0 +1,97 @@
+//===------------ context.rs --------------------------------------------===//
+//  Copyright 2021, Tesseract Systems, Inc.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//===----------------------------------------------------------------------===//
+
+//What a transport knows about the dApp on the other side of the connection (i.e. its address or
+//process credentials). Transports process every request within the context of its connection,
+//so that the executors and the services can check who is asking with Context::current().
+
+use std::any::{Any, TypeId};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context as TaskContext, Poll};
+
+thread_local! {
+    static CURRENT: RefCell<Context> = RefCell::new(Context::new());
+}
+
+#[derive(Clone, Default)]
+pub struct Context {
+    values: Arc<HashMap<TypeId, Arc<dyn Any + Send + Sync>>>,
+}
+
+impl Context {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with<T: Any + Send + Sync>(self, value: T) -> Self {
+        let mut values = (*self.values).clone();
+        values.insert(TypeId::of::<T>(), Arc::new(value));
+
+        Self {
+            values: Arc::new(values),
+        }
+    }
+
+    pub fn get<T: Any + Send + Sync>(&self) -> Option<&T> {
+        self.values
+            .get(&TypeId::of::<T>())
+            .and_then(|value| value.downcast_ref())
+    }
+
+    //the context of the request being processed. Empty outside of a request
+    pub fn current() -> Self {
+        CURRENT.with(|current| current.borrow().clone())
+    }
+
+    //makes the context current whenever the future is polled. Works with any executor
+    pub fn scope<F: Future>(self, future: F) -> Scoped<F> {
+        Scoped {
+            context: self,
+            future: Box::pin(future),
+        }
+    }
+}
+
+pub struct Scoped<F: Future> {
+    context: Context,
+    future: Pin<Box<F>>,
+}
+
+//restores the previous context even if the future panics
+struct Restore(Option<Context>);
+
+impl Drop for Restore {
+    fn drop(&mut self) {
+        if let Some(previous) = self.0.take() {
+            CURRENT.with(|current| *current.borrow_mut() = previous);
+        }
+    }
+}
+
+impl<F: Future> Future for Scoped<F> {
+    type Output = F::Output;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut TaskContext<'_>) -> Poll<Self::Output> {
+        let previous = CURRENT.with(|current| current.replace(self.context.clone()));
+        let _restore = Restore(Some(previous));
+
+        self.future.as_mut().poll(cx)
+    }
+}
@@ -22,11 +22,13 @@ use futures::future::Future;
 
 use serde::{de::DeserializeOwned, Serialize};
 
-use crate::envelope::{RequestEnvelope, ResponseEnvelope};
+use crate::envelope::{RequestEnvelope, ResponseEnvelope, VERSION};
 use crate::error::Result;
 use crate::response::Response;
 use crate::serialize::Serializer;
 
+use super::processor::error_response;
+
 #[async_trait]
 pub trait Executor: Send + Sync {
     async fn call(self: Arc<Self>, serializer: Serializer, method: &str, data: &[u8]) -> Vec<u8>;
@@ -61,15 +63,21 @@ where
         data: &[u8],
         caller: impl FnOnce(Req) -> F + Send + 'a,
     ) -> Vec<u8> {
-        let RequestEnvelope { request, id, .. } = serializer.deserialize(data).unwrap(); //TODO: error handling
+        let RequestEnvelope { request, id, .. } = match serializer.deserialize(data) {
+            Ok(envelope) => envelope,
+            Err(error) => return error_response(serializer, None, error),
+        };
 
         let response = caller(request).await;
 
         let envelope = ResponseEnvelope {
+            version: VERSION,
             id: Some(id),
             response: Response::from_result(response),
         };
 
-        serializer.serialize(&envelope, true).unwrap() //TODO: error handling
+        serializer
+            .serialize(&envelope, true)
+            .unwrap_or_else(|error| error_response(serializer, Some(id), error))
     }
 }
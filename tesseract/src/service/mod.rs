@@ -14,6 +14,7 @@
 //  limitations under the License.
 //===----------------------------------------------------------------------===//
 
+pub mod context;
 pub mod executor;
 pub mod processor;
 pub mod service;
@@ -22,6 +23,7 @@ pub mod transport;
 
 pub use tesseract::Tesseract;
 
+pub use context::Context;
 pub use executor::Executor;
 pub use executor::MethodExecutor;
 pub use service::Service;
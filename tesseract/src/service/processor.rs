@@ -22,21 +22,28 @@ use async_trait::async_trait;
 use futures::lock::Mutex;
 use serde::de::IgnoredAny;
 
-use crate::envelope::RequestEnvelope;
-use crate::error::Result;
-use crate::serialize::Serializer;
+use crate::envelope::{EnvelopeHeader, RequestEnvelope, ResponseEnvelope, VERSION};
+use crate::error::{Error, Result};
+use crate::response::Response;
+use crate::serialize::{Limits, Serializer};
 
 use super::executor::Executor;
 use super::transport::TransportProcessor;
 
 pub struct Processor {
     executors: Mutex<HashMap<String, Arc<dyn Executor + Send + Sync>>>,
+    limits: Limits,
 }
 
 impl Processor {
     pub fn new() -> Self {
+        Self::new_with_limits(Limits::default())
+    }
+
+    pub fn new_with_limits(limits: Limits) -> Self {
         Processor {
             executors: Mutex::new(HashMap::new()),
+            limits,
         }
     }
 
@@ -51,20 +58,30 @@ impl Processor {
         }
     }
 
-    async fn process_or_error(self: Arc<Self>, data: &[u8]) -> Result<Vec<u8>> {
-        let (serializer, data) = Serializer::read_marker(data)?;
+    //data is without the marker
+    async fn process_or_error(self: Arc<Self>, serializer: Serializer, data: &[u8]) -> Result<Vec<u8>> {
+        let version = serializer.deserialize_limited::<EnvelopeHeader>(data, &self.limits)?;
+        if let Err(error) = version.check() {
+            return Ok(error_response(serializer, version.id, error));
+        }
+
         let header = serializer.deserialize::<RequestEnvelope<IgnoredAny>>(data)?;
 
         let protocol = header.protocol;
         let method = header.method;
 
         let executors = self.executors.lock().await;
-        let executor = Arc::clone(executors.get(&protocol).ok_or_else(|| {
-            let protocols: Vec<&String> = executors.keys().collect();
-            let description = format!("Can't find service for protocol: {}. Services are registered for the following protocols: {:#?}", &protocol, protocols);
+        let executor = match executors.get(&protocol) {
+            Some(executor) => Arc::clone(executor),
+            None => {
+                let protocols: Vec<&String> = executors.keys().collect();
+                let description = format!("Can't find service for protocol: {}. Services are registered for the following protocols: {:#?}", &protocol, protocols);
 
-            crate::Error::described(crate::ErrorKind::Weird, &description)
-        })?);
+                let error = crate::Error::described(crate::ErrorKind::Weird, &description);
+                return Ok(error_response(serializer, Some(header.id), error));
+            }
+        };
+        drop(executors);
 
         Ok(executor.call(serializer, &method, data).await)
     }
@@ -76,6 +93,35 @@ where
     Self: Sync,
 {
     async fn process(self: Arc<Self>, data: &[u8]) -> Vec<u8> {
-        self.process_or_error(data).await.unwrap() //TODO: error handling
+        #[cfg(feature = "keepalive")]
+        if crate::keepalive::is_ping(data) {
+            return crate::keepalive::PONG.to_vec();
+        }
+
+        let read = self
+            .limits
+            .check_size(data)
+            .and_then(|_| Serializer::read_marker(data));
+
+        //the errors are answered in the request's format, once it's known
+        match read {
+            Ok((serializer, data)) => self
+                .process_or_error(serializer, data)
+                .await
+                .unwrap_or_else(|error| error_response(serializer, None, error)),
+            Err(error) => error_response(Serializer::default(), None, error),
+        }
     }
 }
+
+//None id means the request couldn't be read far enough to get one
+pub(crate) fn error_response(serializer: Serializer, id: Option<u64>, error: Error) -> Vec<u8> {
+    let envelope = ResponseEnvelope::<()> {
+        version: VERSION,
+        id,
+        response: Response::Error(error),
+    };
+
+    //an error envelope always serializes, unless the serializer itself is broken
+    serializer.serialize(&envelope, true).unwrap_or_default()
+}
@@ -16,6 +16,7 @@
 
 use std::sync::Arc;
 
+use crate::serialize::Limits;
 use crate::Protocol;
 
 use super::processor::Processor;
@@ -30,8 +31,13 @@ pub struct Tesseract {
 
 impl Tesseract {
     pub fn new() -> Self {
+        Self::new_with_limits(Limits::default())
+    }
+
+    //limits applied to the requests coming from the dApps
+    pub fn new_with_limits(limits: Limits) -> Self {
         Tesseract {
-            processor: Arc::new(Processor::new()),
+            processor: Arc::new(Processor::new_with_limits(limits)),
             transports: Vec::new(),
         }
     }
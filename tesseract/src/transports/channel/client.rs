@@ -0,0 +1,131 @@
+//===------------ client.rs --------------------------------------------===//
+//  Copyright 2021, Tesseract Systems, Inc.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//===----------------------------------------------------------------------===//
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use futures::channel::mpsc::{UnboundedReceiver, UnboundedSender};
+use futures::lock::Mutex;
+use futures::StreamExt;
+
+use crate::Protocol;
+use crate::{Error, ErrorKind, Result};
+
+use crate::client::transport::Status;
+use crate::client::Connection;
+use crate::client::Transport;
+
+use super::{Channel, CHANNEL};
+
+fn not_bound() -> Error {
+    Error::described(ErrorKind::Transport, "no wallet is bound to the channel")
+}
+
+//linked on the first send and linked again after the wallet is gone and another one is bound
+struct ChannelConnection {
+    channel: Arc<Channel>,
+    requests: Mutex<Option<UnboundedSender<Vec<u8>>>>,
+    responses: Mutex<Option<UnboundedReceiver<Vec<u8>>>>,
+    //the requests sent and not received yet
+    pending: AtomicUsize,
+}
+
+#[async_trait]
+impl Connection for ChannelConnection {
+    async fn send(self: Arc<Self>, request: Vec<u8>) -> Result<()> {
+        if !self.channel.is_bound() {
+            return Err(not_bound());
+        }
+
+        let mut requests = self.requests.lock().await;
+
+        let linked = requests
+            .as_ref()
+            .is_some_and(|requests| !requests.is_closed());
+        if !linked {
+            let (sender, receiver) = self.channel.link().ok_or_else(not_bound)?;
+            *requests = Some(sender);
+            *self.responses.lock().await = Some(receiver);
+        }
+
+        requests
+            .as_ref()
+            .ok_or_else(not_bound)?
+            .unbounded_send(request)
+            .map_err(|_| not_bound())?;
+
+        self.pending.fetch_add(1, Ordering::SeqCst);
+        Ok(())
+    }
+
+    async fn receive(self: Arc<Self>) -> Result<Vec<u8>> {
+        let no_request = || Error::described(ErrorKind::Transport, "no request has been sent");
+
+        self.pending
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |pending| {
+                pending.checked_sub(1)
+            })
+            .map_err(|_| no_request())?;
+
+        let mut responses = self.responses.lock().await;
+        let responses = responses.as_mut().ok_or_else(no_request)?;
+
+        responses.next().await.ok_or_else(|| {
+            Error::described(
+                ErrorKind::Transport,
+                "the wallet has left without a response",
+            )
+        })
+    }
+}
+
+pub struct ChannelTransport {
+    channel: Arc<Channel>,
+}
+
+impl ChannelTransport {
+    pub fn new(channel: &Arc<Channel>) -> Self {
+        Self {
+            channel: Arc::clone(channel),
+        }
+    }
+}
+
+#[async_trait]
+impl Transport for ChannelTransport {
+    fn id(&self) -> String {
+        CHANNEL.to_owned()
+    }
+
+    async fn status(self: Arc<Self>, _: Box<dyn Protocol>) -> Status {
+        if self.channel.is_bound() {
+            Status::Ready
+        } else {
+            Status::Unavailable(not_bound().to_string())
+        }
+    }
+
+    fn connect(&self, _: Box<dyn Protocol>) -> Box<dyn Connection + Sync + Send> {
+        Box::new(ChannelConnection {
+            channel: Arc::clone(&self.channel),
+            requests: Mutex::new(None),
+            responses: Mutex::new(None),
+            pending: AtomicUsize::new(0),
+        })
+    }
+}
@@ -0,0 +1,107 @@
+//===------------ mod.rs --------------------------------------------===//
+//  Copyright 2021, Tesseract Systems, Inc.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//===----------------------------------------------------------------------===//
+
+//In-process transport for the wallets embedded into the same binary as the dApp.
+//A Channel is shared by the wallet's and the dApp's Tesseract instances. Every dApp connection
+//is linked to the wallet with a pair of queues (requests and responses) and gets its responses
+//in the order of its requests. The wallet serves the connections concurrently on a thread of its own,
+//so no runtime is needed. Once the wallet's Tesseract is dropped, the new requests fail with
+//a transport error, while the ones already sent are still completed.
+
+#[cfg(feature = "client")]
+pub mod client;
+
+#[cfg(feature = "service")]
+pub mod service;
+
+use std::sync::Mutex;
+
+use futures::channel::mpsc::{self, UnboundedReceiver, UnboundedSender};
+
+pub const CHANNEL: &str = "channel";
+
+//the wallet's ends of a connection
+struct Link {
+    requests: UnboundedReceiver<Vec<u8>>,
+    responses: UnboundedSender<Vec<u8>>,
+}
+
+type Links = UnboundedSender<Link>;
+
+//the dApp's ends of a connection
+type Ends = (UnboundedSender<Vec<u8>>, UnboundedReceiver<Vec<u8>>);
+
+pub struct Channel {
+    links: Mutex<Option<Links>>,
+}
+
+impl Channel {
+    pub fn new() -> Self {
+        Self {
+            links: Mutex::new(None),
+        }
+    }
+
+    //the wallet bound last is the one serving
+    fn bind(&self, links: Links) {
+        *self.links.lock().unwrap() = Some(links);
+    }
+
+    //only if it's still the bound wallet
+    fn unbind(&self, links: &Links) {
+        let mut bound = self.links.lock().unwrap();
+
+        if bound
+            .as_ref()
+            .is_some_and(|bound| bound.same_receiver(links))
+        {
+            *bound = None;
+        }
+    }
+
+    //a new connection to the bound wallet
+    fn link(&self) -> Option<Ends> {
+        let (requests, wallet_requests) = mpsc::unbounded();
+        let (wallet_responses, responses) = mpsc::unbounded();
+
+        let link = Link {
+            requests: wallet_requests,
+            responses: wallet_responses,
+        };
+
+        self.links
+            .lock()
+            .unwrap()
+            .as_ref()?
+            .unbounded_send(link)
+            .ok()
+            .map(|_| (requests, responses))
+    }
+
+    pub fn is_bound(&self) -> bool {
+        self.links
+            .lock()
+            .unwrap()
+            .as_ref()
+            .is_some_and(|links| !links.is_closed())
+    }
+}
+
+impl Default for Channel {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,123 @@
+//===------------ service.rs --------------------------------------------===//
+//  Copyright 2021, Tesseract Systems, Inc.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//===----------------------------------------------------------------------===//
+
+use std::sync::Arc;
+use std::thread;
+
+use futures::channel::mpsc::{self, UnboundedSender};
+use futures::channel::oneshot;
+use futures::executor::block_on;
+use futures::future::{self, Either, FutureExt, Shared};
+use futures::StreamExt;
+
+use crate::service::BoundTransport;
+use crate::service::Transport;
+use crate::service::TransportProcessor;
+
+use super::{Channel, Link};
+
+//Serves the dApp connections on a thread of its own. The executors run on that thread,
+//outside of any async runtime
+pub struct ChannelTransport {
+    channel: Arc<Channel>,
+}
+
+impl ChannelTransport {
+    pub fn new(channel: &Arc<Channel>) -> Self {
+        Self {
+            channel: Arc::clone(channel),
+        }
+    }
+}
+
+type Stop = Shared<oneshot::Receiver<()>>;
+
+//the requests of a connection one by one. Once stopped, the ones already sent are still answered
+async fn serve(link: Link, processor: Arc<dyn TransportProcessor + Send + Sync>, mut stop: Stop) {
+    let Link {
+        mut requests,
+        responses,
+    } = link;
+
+    let respond = |request: Vec<u8>| {
+        let processor = Arc::clone(&processor);
+        let responses = responses.clone();
+        async move {
+            let response = processor.process(&request).await;
+            responses.unbounded_send(response).is_ok()
+        }
+    };
+
+    loop {
+        match future::select(requests.next(), &mut stop).await {
+            Either::Left((Some(request), _)) => {
+                if !respond(request).await {
+                    return;
+                }
+            }
+            Either::Left((None, _)) => return, //the dApp has dropped the connection
+            Either::Right(_) => break,
+        }
+    }
+
+    requests.close();
+    while let Some(request) = requests.next().await {
+        if !respond(request).await {
+            return;
+        }
+    }
+}
+
+struct BoundChannelTransport {
+    channel: Arc<Channel>,
+    links: UnboundedSender<Link>,
+    //dropped to stop the serving
+    _stop: oneshot::Sender<()>,
+}
+
+impl BoundTransport for BoundChannelTransport {}
+
+impl Drop for BoundChannelTransport {
+    fn drop(&mut self) {
+        self.channel.unbind(&self.links);
+    }
+}
+
+impl Transport for ChannelTransport {
+    fn bind(
+        self,
+        processor: Arc<dyn TransportProcessor + Send + Sync>,
+    ) -> Box<dyn BoundTransport + Send> {
+        let (links, accepted) = mpsc::unbounded();
+        let (stop, stopped) = oneshot::channel();
+        let stopped = stopped.shared();
+
+        self.channel.bind(links.clone());
+
+        //ends once the wallet is unbound and all its connections are done
+        thread::spawn(move || {
+            block_on(accepted.for_each_concurrent(None, |link| {
+                serve(link, Arc::clone(&processor), stopped.clone())
+            }))
+        });
+
+        Box::new(BoundChannelTransport {
+            channel: self.channel,
+            links,
+            _stop: stop,
+        })
+    }
+}
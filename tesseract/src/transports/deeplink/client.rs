@@ -0,0 +1,85 @@
+//===------------ client.rs --------------------------------------------===//
+//  Copyright 2021, Tesseract Systems, Inc.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//===----------------------------------------------------------------------===//
+
+use url::Url;
+
+use crate::{Error, ErrorKind, Result};
+
+use super::{
+    check_len, decode_payload, encode_payload, malformed, param, parse, DEFAULT_MAX_URL_LEN,
+    PAYLOAD_PARAM, REQUEST_HOST, RESPONSE_PARAM, RETURN_PARAM, SCHEME,
+};
+
+//The dApp side: makes the request URLs and reads the responses from the return URLs
+pub struct DeepLink {
+    return_url: Url,
+    max_url_len: usize,
+}
+
+impl DeepLink {
+    //the return URL is where the dApp is opened back by the wallet (i.e. "mydapp://tesseract")
+    pub fn new(return_url: &str) -> Result<Self> {
+        let return_url = Url::parse(return_url)
+            .map_err(|e| Error::new(ErrorKind::Serialization, "can't parse return URL", e))?;
+
+        if return_url.cannot_be_a_base() {
+            return Err(malformed("the return URL can't have a query"));
+        }
+
+        Ok(Self {
+            return_url,
+            max_url_len: DEFAULT_MAX_URL_LEN,
+        })
+    }
+
+    pub fn max_url_len(self, max_url_len: usize) -> Self {
+        Self {
+            max_url_len,
+            ..self
+        }
+    }
+
+    pub fn request_url(&self, request: &[u8]) -> Result<Url> {
+        let mut url = Url::parse(&format!("{}://{}", SCHEME, REQUEST_HOST))
+            .expect("the request URL base is valid");
+
+        url.query_pairs_mut()
+            .append_pair(PAYLOAD_PARAM, &encode_payload(request))
+            .append_pair(RETURN_PARAM, self.return_url.as_str());
+
+        check_len(url.as_str(), self.max_url_len)?;
+        Ok(url)
+    }
+
+    //the marked response. Fails if the URL is not the return URL with a response
+    pub fn parse_response(&self, url: &str) -> Result<Vec<u8>> {
+        let url = parse(url, self.max_url_len)?;
+
+        let base = |url: &Url| {
+            let mut url = url.clone();
+            url.set_query(None);
+            url.set_fragment(None);
+            url
+        };
+        if base(&url) != base(&self.return_url) {
+            return Err(malformed("the URL is not the return URL"));
+        }
+
+        let payload =
+            param(&url, RESPONSE_PARAM)?.ok_or_else(|| malformed("no response in the URL"))?;
+        decode_payload(&payload)
+    }
+}
@@ -0,0 +1,110 @@
+//===------------ mod.rs --------------------------------------------===//
+//  Copyright 2021, Tesseract Systems, Inc.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//===----------------------------------------------------------------------===//
+
+//Deep-link URLs for the mobile wallets invoked by the OS.
+//
+//The dApp opens the request URL, which carries the marked request as unpadded base64url
+//and the URL the wallet has to open with the response:
+//  tesseract://request?payload=<base64url>&return=<percent encoded return URL>
+//The wallet opens the return URL with the marked response appended to its query:
+//  <return URL>?tesseract-response=<base64url>
+//Opening the URLs is up to the app, this module only makes and parses them.
+
+#[cfg(feature = "client")]
+pub mod client;
+
+#[cfg(feature = "service")]
+pub mod service;
+
+#[cfg(any(feature = "client", feature = "service"))]
+use data_encoding::BASE64URL_NOPAD;
+#[cfg(any(feature = "client", feature = "service"))]
+use url::Url;
+
+#[cfg(any(feature = "client", feature = "service"))]
+use crate::{Error, ErrorKind, Result};
+
+pub const SCHEME: &str = "tesseract";
+pub const REQUEST_HOST: &str = "request";
+
+pub const PAYLOAD_PARAM: &str = "payload";
+pub const RETURN_PARAM: &str = "return";
+pub const RESPONSE_PARAM: &str = "tesseract-response";
+
+//the OSes and browsers pass much longer URLs, but some of the apps on the way cut them
+pub const DEFAULT_MAX_URL_LEN: usize = 64 * 1024;
+
+#[cfg(any(feature = "client", feature = "service"))]
+pub(crate) fn malformed(description: &str) -> Error {
+    Error::described(
+        ErrorKind::Serialization,
+        &format!("malformed deep link: {}", description),
+    )
+}
+
+#[cfg(any(feature = "client", feature = "service"))]
+pub(crate) fn check_len(url: &str, max_url_len: usize) -> Result<()> {
+    if url.len() > max_url_len {
+        Err(Error::described(
+            ErrorKind::Transport,
+            &format!(
+                "deep link of {} bytes exceeds the limit of {} bytes",
+                url.len(),
+                max_url_len
+            ),
+        ))
+    } else {
+        Ok(())
+    }
+}
+
+#[cfg(any(feature = "client", feature = "service"))]
+pub(crate) fn parse(url: &str, max_url_len: usize) -> Result<Url> {
+    check_len(url, max_url_len)?;
+    Url::parse(url).map_err(|e| Error::new(ErrorKind::Serialization, "can't parse deep link", e))
+}
+
+#[cfg(any(feature = "client", feature = "service"))]
+pub(crate) fn encode_payload(message: &[u8]) -> String {
+    BASE64URL_NOPAD.encode(message)
+}
+
+#[cfg(any(feature = "client", feature = "service"))]
+//the payload is opaque here, the layers above check it (i.e. a compressed message is not marked)
+pub(crate) fn decode_payload(payload: &str) -> Result<Vec<u8>> {
+    BASE64URL_NOPAD.decode(payload.as_bytes()).map_err(|e| {
+        Error::new(
+            ErrorKind::Serialization,
+            "can't decode deep link payload",
+            e,
+        )
+    })
+}
+
+#[cfg(any(feature = "client", feature = "service"))]
+//the value of the only occurrence of the query parameter
+pub(crate) fn param(url: &Url, name: &str) -> Result<Option<String>> {
+    let mut values = url
+        .query_pairs()
+        .filter(|(key, _)| key == name)
+        .map(|(_, value)| value.into_owned());
+
+    match (values.next(), values.next()) {
+        (value, None) => Ok(value),
+        (Some(_), Some(_)) => Err(malformed(&format!("'{}' is repeated", name))),
+        (None, Some(_)) => unreachable!(),
+    }
+}
@@ -0,0 +1,96 @@
+//===------------ service.rs --------------------------------------------===//
+//  Copyright 2021, Tesseract Systems, Inc.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//===----------------------------------------------------------------------===//
+
+use url::Url;
+
+use crate::{Error, ErrorKind, Result};
+
+use super::{
+    check_len, decode_payload, encode_payload, malformed, param, parse, DEFAULT_MAX_URL_LEN,
+    PAYLOAD_PARAM, REQUEST_HOST, RESPONSE_PARAM, RETURN_PARAM, SCHEME,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Request {
+    pub request: Vec<u8>,
+    //None if the dApp doesn't want the response
+    pub return_url: Option<Url>,
+}
+
+//The wallet side: reads the requests from the URLs it's opened with and makes the response URLs
+pub struct DeepLink {
+    max_url_len: usize,
+}
+
+impl Default for DeepLink {
+    fn default() -> Self {
+        Self {
+            max_url_len: DEFAULT_MAX_URL_LEN,
+        }
+    }
+}
+
+impl DeepLink {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn max_url_len(self, max_url_len: usize) -> Self {
+        Self { max_url_len }
+    }
+
+    pub fn parse_request(&self, url: &str) -> Result<Request> {
+        let url = parse(url, self.max_url_len)?;
+
+        if url.scheme() != SCHEME || url.host_str() != Some(REQUEST_HOST) {
+            return Err(malformed("not a Tesseract request URL"));
+        }
+
+        let payload =
+            param(&url, PAYLOAD_PARAM)?.ok_or_else(|| malformed("no request in the URL"))?;
+        let request = decode_payload(&payload)?;
+
+        let return_url = param(&url, RETURN_PARAM)?
+            .map(|return_url| {
+                Url::parse(&return_url)
+                    .map_err(|e| Error::new(ErrorKind::Serialization, "can't parse return URL", e))
+            })
+            .transpose()?;
+
+        if return_url.as_ref().is_some_and(Url::cannot_be_a_base) {
+            return Err(malformed("the return URL can't have a query"));
+        }
+
+        Ok(Request {
+            request,
+            return_url,
+        })
+    }
+
+    //the return URL with the marked response added to its query
+    pub fn response_url(&self, return_url: &Url, response: &[u8]) -> Result<Url> {
+        if return_url.cannot_be_a_base() {
+            return Err(malformed("the return URL can't have a query"));
+        }
+
+        let mut url = return_url.clone();
+        url.query_pairs_mut()
+            .append_pair(RESPONSE_PARAM, &encode_payload(response));
+
+        check_len(url.as_str(), self.max_url_len)?;
+        Ok(url)
+    }
+}
@@ -0,0 +1,178 @@
+//===------------ client.rs --------------------------------------------===//
+//  Copyright 2021, Tesseract Systems, Inc.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//===----------------------------------------------------------------------===//
+
+use std::collections::VecDeque;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use async_trait::async_trait;
+
+use futures_timer::Delay;
+
+use crate::envelope::EnvelopeHeader;
+use crate::serialize::Serializer;
+use crate::Protocol;
+use crate::{Error, ErrorKind, Result};
+
+use crate::client::transport::Status;
+use crate::client::Connection;
+use crate::client::Transport;
+
+use super::{decode, file_name, io_error, write_file, DEFAULT_POLL_INTERVAL, FILE, REQUEST_EXTENSION, RESPONSE_EXTENSION};
+
+//unique among the connections of all the dApp runs, so that the old responses are never picked up
+fn new_session() -> String {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|time| time.as_nanos() as u64)
+        .unwrap_or_default();
+
+    format!(
+        "{:x}{:x}{:x}",
+        nanos,
+        std::process::id(),
+        COUNTER.fetch_add(1, Ordering::Relaxed)
+    )
+}
+
+struct FileConnection {
+    outbox: PathBuf,
+    inbox: PathBuf,
+    session: String,
+    poll_interval: Duration,
+    timeout: Option<Duration>,
+    //the highest number used in the connection. Numbers the requests without an id to read (compressed or encrypted)
+    counter: AtomicU64,
+    //numbers of the requests written and waiting for their responses
+    requests: Mutex<VecDeque<u64>>,
+}
+
+#[async_trait]
+impl Connection for FileConnection {
+    async fn send(self: Arc<Self>, request: Vec<u8>) -> Result<()> {
+        //the ids only grow, so the opaque requests are numbered after them and the names don't collide
+        let number = match Serializer::deserialize_marked::<EnvelopeHeader>(&request) {
+            Ok((EnvelopeHeader { id: Some(id), .. }, _)) => {
+                self.counter.fetch_max(id, Ordering::Relaxed);
+                id
+            }
+            _ => self.counter.fetch_add(1, Ordering::Relaxed) + 1,
+        };
+
+        let name = file_name(&self.session, number, REQUEST_EXTENSION);
+        write_file(&self.outbox, &name, &request)
+            .map_err(|e| io_error("can't write the request file", e))?;
+
+        self.requests.lock().unwrap().push_back(number);
+        Ok(())
+    }
+
+    async fn receive(self: Arc<Self>) -> Result<Vec<u8>> {
+        let number = self.requests.lock().unwrap().pop_front().ok_or_else(|| {
+            Error::described(ErrorKind::Transport, "no request has been sent")
+        })?;
+
+        let path = self.inbox.join(file_name(&self.session, number, RESPONSE_EXTENSION));
+        let started = Instant::now();
+
+        loop {
+            match fs::read(&path) {
+                Ok(file) => {
+                    let response = decode(&file)?;
+                    let _ = fs::remove_file(&path);
+                    return Ok(response);
+                }
+                Err(e) if e.kind() == io::ErrorKind::NotFound => (),
+                Err(e) => return Err(io_error("can't read the response file", e)),
+            }
+
+            if self.timeout.is_some_and(|timeout| started.elapsed() >= timeout) {
+                return Err(Error::described(
+                    ErrorKind::Transport,
+                    &format!("no response in {}", path.display()),
+                ));
+            }
+
+            Delay::new(self.poll_interval).await;
+        }
+    }
+}
+
+pub struct FileTransport {
+    outbox: PathBuf,
+    inbox: PathBuf,
+    poll_interval: Duration,
+    timeout: Option<Duration>,
+}
+
+impl FileTransport {
+    //the requests are written into the outbox, the responses are expected in the inbox
+    pub fn new<O: Into<PathBuf>, I: Into<PathBuf>>(outbox: O, inbox: I) -> Self {
+        Self {
+            outbox: outbox.into(),
+            inbox: inbox.into(),
+            poll_interval: DEFAULT_POLL_INTERVAL,
+            timeout: None,
+        }
+    }
+
+    pub fn poll_interval(self, poll_interval: Duration) -> Self {
+        Self {
+            poll_interval,
+            ..self
+        }
+    }
+
+    //how long to wait for a response. Forever by default, as a human carries it
+    pub fn timeout(self, timeout: Duration) -> Self {
+        Self {
+            timeout: Some(timeout),
+            ..self
+        }
+    }
+}
+
+#[async_trait]
+impl Transport for FileTransport {
+    fn id(&self) -> String {
+        FILE.to_owned()
+    }
+
+    async fn status(self: Arc<Self>, _: Box<dyn Protocol>) -> Status {
+        match [&self.outbox, &self.inbox].into_iter().find(|dir| !dir.is_dir()) {
+            None => Status::Ready,
+            Some(dir) => Status::Unavailable(format!("{} is not a directory", dir.display())),
+        }
+    }
+
+    fn connect(&self, _: Box<dyn Protocol>) -> Box<dyn Connection + Sync + Send> {
+        Box::new(FileConnection {
+            outbox: self.outbox.clone(),
+            inbox: self.inbox.clone(),
+            session: new_session(),
+            poll_interval: self.poll_interval,
+            timeout: self.timeout,
+            counter: AtomicU64::new(0),
+            requests: Mutex::new(VecDeque::new()),
+        })
+    }
+}
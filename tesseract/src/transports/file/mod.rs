@@ -0,0 +1,113 @@
+//===------------ mod.rs --------------------------------------------===//
+//  Copyright 2021, Tesseract Systems, Inc.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//===----------------------------------------------------------------------===//
+
+//Air-gapped exchange of the marked messages through files (i.e. moved on USB sticks for
+//the cold storage signing). The dApp writes every request into its outbox and waits for
+//the response to appear in its inbox. The wallet watches its inbox and writes the responses
+//into its outbox. Moving the files between the machines is up to the user.
+//
+//The files are named after the dApp's connection and the id of the request envelope:
+//  <session>-<id>.request and <session>-<id>.response
+//The requests with no id to read (i.e. compressed or encrypted) are numbered after the last id instead.
+//Every file starts with a line with the checksum of the message that follows it:
+//  sha256:<hex digest>\n<marked message>
+//The files are written under a temporary name and renamed, so a reader never sees them half-written,
+//but copying onto a stick must be finished before the file is put into the inbox.
+
+#[cfg(feature = "client")]
+pub mod client;
+
+#[cfg(feature = "service")]
+pub mod service;
+
+#[cfg(any(feature = "client", feature = "service"))]
+use std::fs;
+#[cfg(any(feature = "client", feature = "service"))]
+use std::io;
+#[cfg(any(feature = "client", feature = "service"))]
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use sha2::{Digest, Sha256};
+
+use crate::{Error, ErrorKind, Result};
+
+pub const FILE: &str = "file";
+
+pub const REQUEST_EXTENSION: &str = "request";
+pub const RESPONSE_EXTENSION: &str = "response";
+//the wallet renames the request files it can't read
+pub const CORRUPTED_EXTENSION: &str = "corrupted";
+
+pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+const CHECKSUM_PREFIX: &str = "sha256:";
+
+#[cfg(feature = "client")]
+pub(crate) fn io_error(description: &str, error: io::Error) -> Error {
+    Error::new(ErrorKind::Transport, description, error)
+}
+
+fn hex(data: &[u8]) -> String {
+    data.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+pub fn encode(message: &[u8]) -> Vec<u8> {
+    let mut file = format!("{}{}\n", CHECKSUM_PREFIX, hex(&Sha256::digest(message))).into_bytes();
+    file.extend_from_slice(message);
+    file
+}
+
+//the message, if it's intact
+pub fn decode(file: &[u8]) -> Result<Vec<u8>> {
+    let corrupted = |reason: &str| {
+        Error::described(ErrorKind::Transport, &format!("the file is corrupted: {}", reason))
+    };
+
+    let newline = file
+        .iter()
+        .position(|byte| *byte == b'\n')
+        .ok_or_else(|| corrupted("no checksum"))?;
+
+    let checksum = std::str::from_utf8(&file[..newline])
+        .ok()
+        .and_then(|line| line.strip_prefix(CHECKSUM_PREFIX))
+        .ok_or_else(|| corrupted("no checksum"))?;
+
+    let message = &file[newline + 1..];
+
+    if checksum.eq_ignore_ascii_case(&hex(&Sha256::digest(message))) {
+        Ok(message.to_vec())
+    } else {
+        Err(corrupted("checksum mismatch"))
+    }
+}
+
+#[cfg(feature = "client")]
+pub(crate) fn file_name(session: &str, number: u64, extension: &str) -> String {
+    format!("{}-{}.{}", session, number, extension)
+}
+
+#[cfg(any(feature = "client", feature = "service"))]
+pub(crate) fn write_file(directory: &Path, name: &str, message: &[u8]) -> io::Result<PathBuf> {
+    let path = directory.join(name);
+    let temporary = directory.join(format!(".{}.tmp", name));
+
+    fs::write(&temporary, encode(message))?;
+    fs::rename(&temporary, &path)?;
+
+    Ok(path)
+}
@@ -0,0 +1,148 @@
+//===------------ service.rs --------------------------------------------===//
+//  Copyright 2021, Tesseract Systems, Inc.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//===----------------------------------------------------------------------===//
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use futures::executor::block_on;
+
+use crate::service::BoundTransport;
+use crate::service::Transport;
+use crate::service::TransportProcessor;
+
+use super::{decode, write_file, CORRUPTED_EXTENSION, DEFAULT_POLL_INTERVAL, REQUEST_EXTENSION, RESPONSE_EXTENSION};
+
+//Watches the inbox on a thread of its own and processes the requests one by one, in the order of their names.
+//The executors run on that thread, outside of any async runtime
+pub struct FileTransport {
+    inbox: PathBuf,
+    outbox: PathBuf,
+    poll_interval: Duration,
+}
+
+impl FileTransport {
+    //the requests are expected in the inbox, the responses are written into the outbox
+    pub fn new<I: Into<PathBuf>, O: Into<PathBuf>>(inbox: I, outbox: O) -> Self {
+        Self {
+            inbox: inbox.into(),
+            outbox: outbox.into(),
+            poll_interval: DEFAULT_POLL_INTERVAL,
+        }
+    }
+
+    pub fn poll_interval(self, poll_interval: Duration) -> Self {
+        Self {
+            poll_interval,
+            ..self
+        }
+    }
+}
+
+fn requests(inbox: &Path) -> Vec<PathBuf> {
+    let mut requests: Vec<_> = fs::read_dir(inbox)
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|extension| extension == REQUEST_EXTENSION))
+        .collect();
+
+    requests.sort_by_cached_key(|path| order(path));
+    requests
+}
+
+//<session>-<number>: the numbers are compared as such, so -10 comes after -9
+fn order(path: &Path) -> (String, Option<u64>) {
+    let stem = path.file_stem().and_then(|stem| stem.to_str()).unwrap_or_default();
+
+    match stem.rsplit_once('-') {
+        Some((session, number)) => match number.parse() {
+            Ok(number) => (session.to_owned(), Some(number)),
+            Err(_) => (stem.to_owned(), None),
+        },
+        None => (stem.to_owned(), None),
+    }
+}
+
+//the request file is removed once the response is written, so it's never processed twice
+fn process(request: &Path, outbox: &Path, processor: &Arc<dyn TransportProcessor + Send + Sync>) {
+    let message = match fs::read(request) {
+        Ok(file) => decode(&file),
+        Err(_) => return, //maybe being moved, the next poll will tell
+    };
+
+    let message = match message {
+        Ok(message) => message,
+        Err(_) => {
+            let _ = fs::rename(request, request.with_extension(CORRUPTED_EXTENSION));
+            return;
+        }
+    };
+
+    let response = block_on(Arc::clone(processor).process(&message));
+
+    let name = request.with_extension(RESPONSE_EXTENSION);
+    let name = name.file_name().and_then(|name| name.to_str());
+
+    if let Some(name) = name {
+        if write_file(outbox, name, &response).is_ok() {
+            let _ = fs::remove_file(request);
+        }
+    }
+}
+
+struct BoundFileTransport {
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl BoundTransport for BoundFileTransport {}
+
+impl Drop for BoundFileTransport {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+
+        if let Some(thread) = self.thread.take() {
+            thread.thread().unpark();
+            let _ = thread.join();
+        }
+    }
+}
+
+impl Transport for FileTransport {
+    fn bind(self, processor: Arc<dyn TransportProcessor + Send + Sync>) -> Box<dyn BoundTransport + Send> {
+        let stop = Arc::new(AtomicBool::new(false));
+        let stopped = Arc::clone(&stop);
+
+        let thread = thread::spawn(move || {
+            while !stopped.load(Ordering::Relaxed) {
+                for request in requests(&self.inbox) {
+                    process(&request, &self.outbox, &processor);
+                }
+
+                thread::park_timeout(self.poll_interval);
+            }
+        });
+
+        Box::new(BoundFileTransport {
+            stop,
+            thread: Some(thread),
+        })
+    }
+}
@@ -0,0 +1,205 @@
+//===------------ client.rs --------------------------------------------===//
+//  Copyright 2021, Tesseract Systems, Inc.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//===----------------------------------------------------------------------===//
+
+use std::collections::VecDeque;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use futures::lock::Mutex;
+
+use http_body_util::{BodyExt, Full, Limited};
+
+use hyper::body::Bytes;
+use hyper::header::{HeaderValue, AUTHORIZATION, CONTENT_TYPE};
+use hyper::{Method, Request, Uri};
+
+use hyper_util::client::legacy::connect::HttpConnector;
+use hyper_util::client::legacy::Client;
+use hyper_util::rt::TokioExecutor;
+
+use tokio::net::TcpStream;
+
+use crate::Protocol;
+use crate::{Error, ErrorKind, Result};
+
+use crate::client::transport::Status;
+use crate::client::Connection;
+use crate::client::Transport;
+
+use super::{content_type, DEFAULT_MAX_BODY_LEN, DEFAULT_PATH, HTTP};
+
+//Provides the Authorization header for every request (i.e. a token that may be refreshed)
+pub type Authorization = Arc<dyn Fn() -> Option<String> + Send + Sync>;
+
+type HttpClient = Client<HttpConnector, Full<Bytes>>;
+
+fn http_error<E: std::error::Error>(description: &str, error: E) -> Error {
+    Error::new(ErrorKind::Transport, description, error)
+}
+
+//Every send is a POST, the response is kept until it's received
+struct HttpConnection {
+    client: HttpClient,
+    uri: Uri,
+    authorization: Option<Authorization>,
+    max_body_len: usize,
+    responses: Mutex<VecDeque<Vec<u8>>>,
+}
+
+impl HttpConnection {
+    async fn post(&self, body: Vec<u8>) -> Result<Vec<u8>> {
+        let mut request = Request::builder()
+            .method(Method::POST)
+            .uri(self.uri.clone())
+            .header(CONTENT_TYPE, content_type(&body));
+
+        if let Some(authorization) = self.authorization.as_ref().and_then(|a| a()) {
+            let value = HeaderValue::try_from(authorization)
+                .map_err(|e| http_error("invalid authorization header", e))?;
+            request = request.header(AUTHORIZATION, value);
+        }
+
+        let request = request
+            .body(Full::new(Bytes::from(body)))
+            .map_err(|e| http_error("can't build request", e))?;
+
+        let response = self
+            .client
+            .request(request)
+            .await
+            .map_err(|e| http_error("can't reach the wallet", e))?;
+
+        if !response.status().is_success() {
+            return Err(Error::described(
+                ErrorKind::Transport,
+                &format!("the wallet has responded with {}", response.status()),
+            ));
+        }
+
+        let body = Limited::new(response.into_body(), self.max_body_len)
+            .collect()
+            .await
+            .map_err(|e| {
+                Error::described(
+                    ErrorKind::Transport,
+                    &format!("can't read response: {}", e),
+                )
+            })?;
+
+        Ok(body.to_bytes().to_vec())
+    }
+}
+
+#[async_trait]
+impl Connection for HttpConnection {
+    async fn send(self: Arc<Self>, request: Vec<u8>) -> Result<()> {
+        let response = self.post(request).await?;
+        self.responses.lock().await.push_back(response);
+        Ok(())
+    }
+
+    async fn receive(self: Arc<Self>) -> Result<Vec<u8>> {
+        self.responses.lock().await.pop_front().ok_or_else(|| {
+            Error::described(ErrorKind::Transport, "no request has been sent")
+        })
+    }
+}
+
+//Must be used from within a tokio runtime
+pub struct HttpTransport {
+    uri: std::result::Result<Uri, String>,
+    authorization: Option<Authorization>,
+    max_body_len: usize,
+    client: HttpClient,
+}
+
+impl HttpTransport {
+    //i.e. http://127.0.0.1:8080/tesseract. The path is DEFAULT_PATH if the url has none
+    pub fn new(url: &str) -> Self {
+        let uri = url
+            .parse::<Uri>()
+            .map_err(|e| format!("invalid url {}: {}", url, e))
+            .and_then(|uri| {
+                if uri.path() == "/" && !url.ends_with('/') {
+                    let mut parts = uri.into_parts();
+                    parts.path_and_query = Some(DEFAULT_PATH.parse().unwrap());
+                    Uri::from_parts(parts).map_err(|e| format!("invalid url {}: {}", url, e))
+                } else {
+                    Ok(uri)
+                }
+            });
+
+        Self {
+            uri,
+            authorization: None,
+            max_body_len: DEFAULT_MAX_BODY_LEN,
+            client: Client::builder(TokioExecutor::new()).build_http(),
+        }
+    }
+
+    pub fn authorization<F: Fn() -> Option<String> + Send + Sync + 'static>(self, authorization: F) -> Self {
+        Self {
+            authorization: Some(Arc::new(authorization)),
+            ..self
+        }
+    }
+
+    pub fn max_body_len(self, max_body_len: usize) -> Self {
+        Self {
+            max_body_len,
+            ..self
+        }
+    }
+}
+
+#[async_trait]
+impl Transport for HttpTransport {
+    fn id(&self) -> String {
+        HTTP.to_owned()
+    }
+
+    //a POST would be processed, so only the server is checked to be reachable
+    async fn status(self: Arc<Self>, _: Box<dyn Protocol>) -> Status {
+        let uri = match &self.uri {
+            Ok(uri) => uri,
+            Err(e) => return Status::Error(Error::described(ErrorKind::Transport, e)),
+        };
+
+        let host = uri.host().unwrap_or_default();
+        let port = uri.port_u16().unwrap_or(80);
+
+        match TcpStream::connect((host, port)).await {
+            Ok(_) => Status::Ready,
+            Err(e) => Status::Unavailable(format!("can't connect to the wallet at {}: {}", uri, e)),
+        }
+    }
+
+    fn connect(&self, _: Box<dyn Protocol>) -> Box<dyn Connection + Sync + Send> {
+        let uri = match &self.uri {
+            Ok(uri) => uri.clone(),
+            Err(_) => Uri::default(), //fails on send, status has already reported it
+        };
+
+        Box::new(HttpConnection {
+            client: self.client.clone(),
+            uri,
+            authorization: self.authorization.clone(),
+            max_body_len: self.max_body_len,
+            responses: Mutex::new(VecDeque::new()),
+        })
+    }
+}
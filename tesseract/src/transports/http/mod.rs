@@ -0,0 +1,43 @@
+//===------------ mod.rs --------------------------------------------===//
+//  Copyright 2021, Tesseract Systems, Inc.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//===----------------------------------------------------------------------===//
+
+//Plain HTTP transport for the backends driving a wallet signer service.
+//Every request is a POST with a marked envelope as the body, the response body is the marked response
+
+#[cfg(feature = "client")]
+pub mod client;
+
+#[cfg(feature = "service")]
+pub mod service;
+
+#[cfg(any(feature = "client", feature = "service"))]
+use crate::serialize::Serializer;
+
+pub const HTTP: &str = "http";
+
+pub const DEFAULT_PATH: &str = "/tesseract";
+pub const DEFAULT_MAX_BODY_LEN: usize = 64 * 1024 * 1024;
+
+#[cfg(any(feature = "client", feature = "service"))]
+pub(crate) const OCTET_STREAM: &str = "application/octet-stream";
+
+#[cfg(any(feature = "client", feature = "service"))]
+//derived from the serializer marker of the envelope
+pub(crate) fn content_type(body: &[u8]) -> &'static str {
+    Serializer::read_marker(body)
+        .map(|(serializer, _)| serializer.content_type())
+        .unwrap_or(OCTET_STREAM)
+}
@@ -0,0 +1,281 @@
+//===------------ service.rs --------------------------------------------===//
+//  Copyright 2021, Tesseract Systems, Inc.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//===----------------------------------------------------------------------===//
+
+use std::convert::Infallible;
+use std::io;
+use std::net::{SocketAddr, ToSocketAddrs};
+use std::sync::Arc;
+
+use http_body_util::{BodyExt, Full, LengthLimitError, Limited};
+
+use hyper::body::{Bytes, Incoming};
+use hyper::header::{
+    HeaderValue, ACCESS_CONTROL_ALLOW_HEADERS, ACCESS_CONTROL_ALLOW_METHODS, ACCESS_CONTROL_ALLOW_ORIGIN,
+    ALLOW, AUTHORIZATION, CONTENT_TYPE, ORIGIN, VARY,
+};
+use hyper::server::conn::http1;
+use hyper::service::service_fn;
+use hyper::{Method, Request, Response, StatusCode};
+
+use hyper_util::rt::TokioIo;
+
+use tokio::net::TcpListener;
+use tokio::runtime::Handle;
+use tokio::task::JoinSet;
+
+use crate::service::BoundTransport;
+use crate::service::Context;
+use crate::service::Transport;
+use crate::service::TransportProcessor;
+use crate::transports::stream::service::BoundStreamTransport;
+
+use super::{content_type, DEFAULT_MAX_BODY_LEN, DEFAULT_PATH};
+
+//Decides whether a request may be processed by its Authorization header (None if there is none)
+pub type AuthCheck = Arc<dyn Fn(Option<&str>) -> bool + Send + Sync>;
+
+//Decides which requests may be processed by their Origin header (None for the non-browser backends)
+pub type OriginCheck = Arc<dyn Fn(Option<&str>) -> bool + Send + Sync>;
+
+#[derive(Clone)]
+struct Config {
+    path: String,
+    max_body_len: usize,
+    auth: AuthCheck,
+    origin: OriginCheck,
+}
+
+pub struct HttpTransport {
+    listener: std::net::TcpListener,
+    handle: Option<Handle>,
+    config: Config,
+}
+
+impl HttpTransport {
+    //the socket is bound right away, so the errors are reported here rather than on bind.
+    //Every request is authorized until a check is set with auth(), but only the non-browser backends
+    //(without an Origin) are served until the browser ones are allowed with allow_origins() or origin().
+    //Otherwise any website the user visits could post to the signer on the loopback
+    pub fn listen<A: ToSocketAddrs>(address: A) -> io::Result<Self> {
+        let listener = std::net::TcpListener::bind(address)?;
+        listener.set_nonblocking(true)?;
+
+        Ok(Self {
+            listener,
+            handle: None,
+            config: Config {
+                path: DEFAULT_PATH.to_owned(),
+                max_body_len: DEFAULT_MAX_BODY_LEN,
+                auth: Arc::new(|_| true),
+                origin: Arc::new(|origin| origin.is_none()),
+            },
+        })
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    pub fn path(self, path: &str) -> Self {
+        Self {
+            config: Config {
+                path: path.to_owned(),
+                ..self.config
+            },
+            ..self
+        }
+    }
+
+    pub fn max_body_len(self, max_body_len: usize) -> Self {
+        Self {
+            config: Config {
+                max_body_len,
+                ..self.config
+            },
+            ..self
+        }
+    }
+
+    pub fn auth<F: Fn(Option<&str>) -> bool + Send + Sync + 'static>(self, check: F) -> Self {
+        Self {
+            config: Config {
+                auth: Arc::new(check),
+                ..self.config
+            },
+            ..self
+        }
+    }
+
+    //the browser backends served from these origins (i.e. "https://backend.example") and the non-browser ones.
+    //The allowed origins get their CORS preflight answered and can read the responses
+    pub fn allow_origins<I: IntoIterator<Item = S>, S: Into<String>>(self, origins: I) -> Self {
+        let origins: Vec<String> = origins.into_iter().map(Into::into).collect();
+
+        self.origin(move |origin| match origin {
+            Some(origin) => origins.iter().any(|allowed| allowed == origin),
+            None => true,
+        })
+    }
+
+    pub fn origin<F: Fn(Option<&str>) -> bool + Send + Sync + 'static>(self, check: F) -> Self {
+        Self {
+            config: Config {
+                origin: Arc::new(check),
+                ..self.config
+            },
+            ..self
+        }
+    }
+
+    //hyper serves the requests on this runtime rather than on the one bind() is called in
+    pub fn handle(self, handle: Handle) -> Self {
+        Self {
+            handle: Some(handle),
+            ..self
+        }
+    }
+}
+
+fn status(status: StatusCode) -> Response<Full<Bytes>> {
+    let mut response = Response::new(Full::default());
+    *response.status_mut() = status;
+    response
+}
+
+async fn handle(
+    request: Request<Incoming>,
+    processor: Arc<dyn TransportProcessor + Send + Sync>,
+    context: Context,
+    config: Arc<Config>,
+) -> Result<Response<Full<Bytes>>, Infallible> {
+    if request.uri().path() != config.path {
+        return Ok(status(StatusCode::NOT_FOUND));
+    }
+
+    let origin = request.headers().get(ORIGIN).cloned();
+
+    if !(config.origin)(origin.as_ref().and_then(|value| value.to_str().ok())) {
+        return Ok(status(StatusCode::FORBIDDEN));
+    }
+
+    //a browser asks before posting the non-simple content types or the Authorization header
+    let mut response = if origin.is_some() && request.method() == Method::OPTIONS {
+        let mut response = status(StatusCode::NO_CONTENT);
+        let headers = response.headers_mut();
+        headers.insert(ACCESS_CONTROL_ALLOW_METHODS, HeaderValue::from_static("POST"));
+        headers.insert(
+            ACCESS_CONTROL_ALLOW_HEADERS,
+            HeaderValue::from_static("Authorization, Content-Type"),
+        );
+        response
+    } else {
+        serve(request, processor, context, &config).await
+    };
+
+    if let Some(origin) = origin {
+        let headers = response.headers_mut();
+        headers.insert(ACCESS_CONTROL_ALLOW_ORIGIN, origin);
+        headers.insert(VARY, HeaderValue::from_static("Origin"));
+    }
+
+    Ok(response)
+}
+
+async fn serve(
+    request: Request<Incoming>,
+    processor: Arc<dyn TransportProcessor + Send + Sync>,
+    context: Context,
+    config: &Config,
+) -> Response<Full<Bytes>> {
+    if request.method() != Method::POST {
+        let mut response = status(StatusCode::METHOD_NOT_ALLOWED);
+        response.headers_mut().insert(ALLOW, HeaderValue::from_static("POST"));
+        return response;
+    }
+
+    let authorization = request
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok());
+
+    if !(config.auth)(authorization) {
+        return status(StatusCode::UNAUTHORIZED);
+    }
+
+    let body = match Limited::new(request.into_body(), config.max_body_len).collect().await {
+        Ok(body) => body.to_bytes(),
+        Err(e) if e.is::<LengthLimitError>() => return status(StatusCode::PAYLOAD_TOO_LARGE),
+        Err(_) => return status(StatusCode::BAD_REQUEST),
+    };
+
+    let response = context.scope(processor.process(&body)).await;
+
+    let content_type = content_type(&response);
+
+    let mut response = Response::new(Full::new(Bytes::from(response)));
+    response
+        .headers_mut()
+        .insert(CONTENT_TYPE, HeaderValue::from_static(content_type));
+
+    response
+}
+
+async fn accept(
+    listener: std::net::TcpListener,
+    processor: Arc<dyn TransportProcessor + Send + Sync>,
+    config: Arc<Config>,
+) {
+    let listener = match TcpListener::from_std(listener) {
+        Ok(listener) => listener,
+        Err(_) => return,
+    };
+
+    //dropped together with the accept loop, which closes all the connections
+    let mut connections = JoinSet::new();
+
+    loop {
+        tokio::select! {
+            accepted = listener.accept() => {
+                if let Ok((stream, address)) = accepted {
+                    let _ = stream.set_nodelay(true);
+                    let processor = Arc::clone(&processor);
+                    let config = Arc::clone(&config);
+                    let context = Context::new().with(address); //client's SocketAddr
+
+                    let service = service_fn(move |request| {
+                        handle(request, Arc::clone(&processor), context.clone(), Arc::clone(&config))
+                    });
+
+                    connections.spawn(async move {
+                        let _ = http1::Builder::new()
+                            .serve_connection(TokioIo::new(stream), service)
+                            .await;
+                    });
+                }
+            }
+            Some(_) = connections.join_next() => (),
+        }
+    }
+}
+
+impl Transport for HttpTransport {
+    fn bind(self, processor: Arc<dyn TransportProcessor + Send + Sync>) -> Box<dyn BoundTransport + Send> {
+        let handle = self.handle.unwrap_or_else(Handle::current);
+        let task = handle.spawn(accept(self.listener, processor, Arc::new(self.config)));
+
+        Box::new(BoundStreamTransport::new(task))
+    }
+}
@@ -16,3 +16,39 @@
 
 #[cfg(feature = "transport-plt")]
 pub mod plt;
+
+#[cfg(feature = "transport-channel")]
+pub mod channel;
+
+#[cfg(feature = "transport-stream")]
+pub mod stream;
+
+#[cfg(feature = "transport-tcp")]
+pub mod tcp;
+
+#[cfg(all(unix, feature = "transport-uds"))]
+pub mod uds;
+
+#[cfg(all(unix, feature = "transport-xdg"))]
+pub mod xdg;
+
+#[cfg(feature = "transport-ws")]
+pub mod ws;
+
+#[cfg(feature = "transport-http")]
+pub mod http;
+
+#[cfg(feature = "transport-relay")]
+pub mod relay;
+
+#[cfg(feature = "transport-stdio")]
+pub mod stdio;
+
+#[cfg(feature = "transport-native-messaging")]
+pub mod native_messaging;
+
+#[cfg(feature = "transport-file")]
+pub mod file;
+
+#[cfg(feature = "transport-deeplink")]
+pub mod deeplink;
@@ -0,0 +1,179 @@
+//===------------ mod.rs --------------------------------------------===//
+//  Copyright 2021, Tesseract Systems, Inc.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//===----------------------------------------------------------------------===//
+
+//Browser native messaging: the extension talks to the wallet (the native host) over the host's
+//stdin and stdout. Every message is JSON preceded by its length as a native-endian u32.
+//The extension's messages are the JSON Tesseract envelopes without the marker.
+//
+//The browser doesn't accept the messages over 1MB from the host, so the bigger responses are sent
+//as a series of chunks, each of them a message on its own:
+//  {"chunk":{"index":0,"count":3,"data":"<a part of the response JSON>"}}
+//The extension concatenates the data of all the chunks and parses the response from it.
+//The requests are processed one by one, so the chunks of different responses never interleave.
+
+#[cfg(feature = "service")]
+pub mod service;
+
+use std::io;
+
+use serde::{Deserialize, Serialize};
+
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+use crate::{Error, ErrorKind, Result};
+
+pub const NATIVE_MESSAGING: &str = "native-messaging";
+
+//the limit of the browser for the messages from the host
+pub const MAX_HOST_MESSAGE_LEN: usize = 1024 * 1024;
+//the limit of the browser for the messages from the extension
+pub const MAX_EXTENSION_MESSAGE_LEN: usize = 64 * 1024 * 1024;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Chunk {
+    pub index: usize,
+    pub count: usize,
+    pub data: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChunkMessage {
+    pub chunk: Chunk,
+}
+
+fn io_error(description: &str, error: io::Error) -> Error {
+    Error::new(ErrorKind::Transport, description, error)
+}
+
+//None means the browser has closed the stream cleanly between the messages
+pub async fn read_message<R: AsyncRead + Unpin>(
+    reader: &mut R,
+    max_len: usize,
+) -> Result<Option<Vec<u8>>> {
+    let mut header = [0u8; 4];
+    let mut read = 0;
+
+    //a header cut in the middle is a broken message, not a close
+    while read < header.len() {
+        match reader.read(&mut header[read..]).await {
+            Ok(0) if read == 0 => return Ok(None),
+            Ok(0) => {
+                return Err(Error::described(
+                    ErrorKind::Transport,
+                    &format!("the stream has ended after {} bytes of a message length", read),
+                ))
+            }
+            Ok(n) => read += n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => (),
+            Err(e) => return Err(io_error("can't read message length", e)),
+        }
+    }
+
+    let len = u32::from_ne_bytes(header) as usize;
+    if len > max_len {
+        return Err(Error::described(
+            ErrorKind::Transport,
+            &format!("message of {} bytes exceeds the limit of {} bytes", len, max_len),
+        ));
+    }
+
+    let mut message = vec![0u8; len];
+    reader
+        .read_exact(&mut message)
+        .await
+        .map_err(|e| io_error("can't read message", e))?;
+
+    Ok(Some(message))
+}
+
+pub async fn write_message<W: AsyncWrite + Unpin>(writer: &mut W, message: &[u8]) -> Result<()> {
+    let len = u32::try_from(message.len()).map_err(|_| {
+        Error::described(
+            ErrorKind::Transport,
+            &format!("message of {} bytes is too big to be sent", message.len()),
+        )
+    })?;
+
+    writer
+        .write_all(&len.to_ne_bytes())
+        .await
+        .map_err(|e| io_error("can't write message length", e))?;
+    writer
+        .write_all(message)
+        .await
+        .map_err(|e| io_error("can't write message", e))?;
+    writer
+        .flush()
+        .await
+        .map_err(|e| io_error("can't flush message", e))
+}
+
+//the length of the char inside a JSON string, as serde_json escapes it
+fn escaped_len(c: char) -> usize {
+    match c {
+        '"' | '\\' | '\n' | '\r' | '\t' | '\u{08}' | '\u{0c}' => 2,
+        c if (c as u32) < 0x20 => 6,
+        c => c.len_utf8(),
+    }
+}
+
+//splits the JSON message into the chunk messages of at most max_len bytes each.
+//A message that fits is returned as is
+pub fn chunks(message: &str, max_len: usize) -> Result<Vec<Vec<u8>>> {
+    if message.len() <= max_len {
+        return Ok(vec![message.as_bytes().to_vec()]);
+    }
+
+    //{"chunk":{"index":,"count":,"data":""}} and the numbers
+    let overhead = 40 + 2 * 20;
+    let capacity = max_len.checked_sub(overhead).filter(|c| *c > 6).ok_or_else(|| {
+        Error::described(ErrorKind::Transport, "the message limit is too small for chunks")
+    })?;
+
+    let mut parts = Vec::new();
+    let mut start = 0;
+    let mut len = 0;
+
+    for (i, c) in message.char_indices() {
+        let escaped = escaped_len(c);
+        if len + escaped > capacity {
+            parts.push(&message[start..i]);
+            start = i;
+            len = 0;
+        }
+        len += escaped;
+    }
+    parts.push(&message[start..]);
+
+    let count = parts.len();
+
+    parts
+        .into_iter()
+        .enumerate()
+        .map(|(index, data)| {
+            let chunk = ChunkMessage {
+                chunk: Chunk {
+                    index,
+                    count,
+                    data: data.to_owned(),
+                },
+            };
+
+            serde_json::to_vec(&chunk)
+                .map_err(|e| Error::new(ErrorKind::Serialization, "can't serialize chunk", e))
+        })
+        .collect()
+}
@@ -0,0 +1,138 @@
+//===------------ service.rs --------------------------------------------===//
+//  Copyright 2021, Tesseract Systems, Inc.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//===----------------------------------------------------------------------===//
+
+use std::sync::Arc;
+
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::runtime::Handle;
+
+use crate::serialize::Serializer;
+use crate::service::processor::error_response;
+use crate::service::BoundTransport;
+use crate::service::Transport;
+use crate::service::TransportProcessor;
+use crate::{Error, ErrorKind, Result};
+
+use crate::transports::stream::service::BoundStreamTransport;
+
+use super::{chunks, read_message, write_message, MAX_EXTENSION_MESSAGE_LEN, MAX_HOST_MESSAGE_LEN};
+
+type Reader = Box<dyn AsyncRead + Send + Unpin>;
+type Writer = Box<dyn AsyncWrite + Send + Unpin>;
+type OnClose = Box<dyn FnOnce() + Send>;
+
+//Serves the extension the browser has launched the wallet for
+pub struct NativeMessagingTransport {
+    reader: Reader,
+    writer: Writer,
+    handle: Option<Handle>,
+    on_close: Option<OnClose>,
+}
+
+impl NativeMessagingTransport {
+    //over the stdin and the stdout of the process, as the browser launches it
+    pub fn new() -> Self {
+        Self::with_io(tokio::io::stdin(), tokio::io::stdout())
+    }
+
+    pub fn with_io<R, W>(reader: R, writer: W) -> Self
+    where
+        R: AsyncRead + Send + Unpin + 'static,
+        W: AsyncWrite + Send + Unpin + 'static,
+    {
+        Self {
+            reader: Box::new(reader),
+            writer: Box::new(writer),
+            handle: None,
+            on_close: None,
+        }
+    }
+
+    //called when the browser closes the stdin (or it becomes unusable), which is when the host should exit
+    pub fn on_close<F: FnOnce() + Send + 'static>(self, on_close: F) -> Self {
+        Self {
+            on_close: Some(Box::new(on_close)),
+            ..self
+        }
+    }
+
+    //the browser starts the host process, which may bind before its runtime is entered.
+    //Pass the handle then, otherwise bind() looks for the current runtime
+    pub fn handle(self, handle: Handle) -> Self {
+        Self {
+            handle: Some(handle),
+            ..self
+        }
+    }
+}
+
+impl Default for NativeMessagingTransport {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+//the JSON the extension expects, without the marker
+fn unmarked(response: Vec<u8>) -> Vec<u8> {
+    match Serializer::read_marker(&response) {
+        Ok((Serializer::Json, json)) => json.to_vec(),
+        _ => {
+            let error = Error::described(ErrorKind::Weird, "the response is not JSON");
+            let response = error_response(Serializer::Json, None, error);
+            response[Serializer::marker_len()..].to_vec()
+        }
+    }
+}
+
+async fn serve(
+    mut reader: Reader,
+    mut writer: Writer,
+    processor: Arc<dyn TransportProcessor + Send + Sync>,
+) -> Result<()> {
+    while let Some(message) = read_message(&mut reader, MAX_EXTENSION_MESSAGE_LEN).await? {
+        let mut request = Serializer::Json.marker().as_bytes().to_vec();
+        request.extend_from_slice(&message);
+
+        let response = unmarked(Arc::clone(&processor).process(&request).await);
+        let response = String::from_utf8(response).map_err(|e| {
+            Error::new(ErrorKind::Serialization, "the response is not UTF-8", e)
+        })?;
+
+        for chunk in chunks(&response, MAX_HOST_MESSAGE_LEN)? {
+            write_message(&mut writer, &chunk).await?;
+        }
+    }
+
+    Ok(())
+}
+
+impl Transport for NativeMessagingTransport {
+    fn bind(self, processor: Arc<dyn TransportProcessor + Send + Sync>) -> Box<dyn BoundTransport + Send> {
+        let handle = self.handle.unwrap_or_else(Handle::current);
+        let on_close = self.on_close;
+        let (reader, writer) = (self.reader, self.writer);
+
+        let task = handle.spawn(async move {
+            let _ = serve(reader, writer, processor).await;
+
+            if let Some(on_close) = on_close {
+                on_close();
+            }
+        });
+
+        Box::new(BoundStreamTransport::new(task))
+    }
+}
@@ -50,7 +50,7 @@ impl ClientLocalConnection {
 #[async_trait]
 impl Connection for ClientLocalConnection {
     async fn send(self: Arc<Self>, request: Vec<u8>) -> Result<()> {
-        let data = Arc::clone(&self.link).send_receive(request).await;
+        let data = Arc::clone(&self.link).send_receive(request).await?;
         let mut responses = self.responses.lock().await;
         responses.push_back(data);
         Ok(())
@@ -58,7 +58,7 @@ impl Connection for ClientLocalConnection {
 
     async fn receive(self: Arc<Self>) -> Result<Vec<u8>> {
         let mut responses = self.responses.lock().await;
-        match responses.pop_back() {
+        match responses.pop_front() {
             Some(data) => Ok(data),
             None => Err(Error::kinded(ErrorKind::Weird)),
         }
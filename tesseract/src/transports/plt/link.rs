@@ -19,6 +19,7 @@
 use std::sync::{Arc, Mutex};
 
 use crate::service::TransportProcessor;
+use crate::{Error, ErrorKind, Result};
 
 pub struct LocalLink {
     processor: Mutex<Option<Arc<dyn TransportProcessor + Send + Sync>>>,
@@ -40,7 +41,7 @@ impl LocalLink {
         self.processor.lock().unwrap().is_some()
     }
 
-    pub async fn send_receive(self: Arc<Self>, data: Vec<u8>) -> Vec<u8> {
+    pub async fn send_receive(self: Arc<Self>, data: Vec<u8>) -> Result<Vec<u8>> {
         //looks weird, but is a consequence of how futures and scopes work
         let processor = {
             let guard = self.processor.lock().unwrap();
@@ -48,11 +49,14 @@ impl LocalLink {
             match &*guard {
                 Some(processor) => Arc::clone(&processor),
                 None => {
-                    panic!("Link is not connected to the service");
+                    return Err(Error::described(
+                        ErrorKind::Transport,
+                        "Link is not connected to the service",
+                    ));
                 }
             }
         };
 
-        processor.process(&data).await
+        Ok(processor.process(&data).await)
     }
 }
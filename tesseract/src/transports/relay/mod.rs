@@ -0,0 +1,93 @@
+//===------------ mod.rs --------------------------------------------===//
+//  Copyright 2021, Tesseract Systems, Inc.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//===----------------------------------------------------------------------===//
+
+//Relay (mailbox) transport for the dApps and wallets that are not on the same machine.
+//
+//Both sides connect to a relay server over TCP and join a session topic agreed upon out of band
+//(i.e. shown in a QR code), the dApp as Role::Dapp and the wallet as Role::Wallet.
+//The first frame of a connection is the JSON encoded Join, all the frames after it are forwarded
+//to the other side of the topic as is. The frames for a side that is not connected (yet) wait
+//in its mailbox. The relay never looks into the frames, so they can be encrypted end-to-end.
+//The framing is the same as in the stream transports.
+//
+//server::RelayServer is a small reference relay, also built as the tesseract-relay binary.
+
+#[cfg(feature = "client")]
+pub mod client;
+
+#[cfg(feature = "service")]
+pub mod service;
+
+pub mod server;
+
+#[cfg(any(feature = "client", feature = "service"))]
+use std::io;
+#[cfg(any(feature = "client", feature = "service"))]
+use std::net::SocketAddr;
+
+use serde::{Deserialize, Serialize};
+
+#[cfg(any(feature = "client", feature = "service"))]
+use tokio::net::TcpStream;
+
+#[cfg(any(feature = "client", feature = "service"))]
+use crate::transports::stream::write_frame;
+
+pub const RELAY: &str = "relay";
+
+pub const MAX_TOPIC_LEN: usize = 256;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    Dapp,
+    Wallet,
+}
+
+impl Role {
+    //the side the frames of this one are forwarded to
+    pub fn peer(&self) -> Self {
+        match self {
+            Self::Dapp => Self::Wallet,
+            Self::Wallet => Self::Dapp,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Join {
+    pub topic: String,
+    pub role: Role,
+}
+
+pub fn is_valid_topic(topic: &str) -> bool {
+    !topic.is_empty() && topic.len() <= MAX_TOPIC_LEN && topic.chars().all(|c| !c.is_control())
+}
+
+//connects to the relay and joins the topic
+#[cfg(any(feature = "client", feature = "service"))]
+pub(crate) async fn join(address: SocketAddr, topic: String, role: Role) -> io::Result<TcpStream> {
+    let join = serde_json::to_vec(&Join { topic, role })
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+    let mut stream = TcpStream::connect(address).await?;
+    stream.set_nodelay(true)?;
+    write_frame(&mut stream, &join)
+        .await
+        .map_err(io::Error::other)?;
+
+    Ok(stream)
+}
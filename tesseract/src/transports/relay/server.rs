@@ -0,0 +1,367 @@
+//===------------ server.rs --------------------------------------------===//
+//  Copyright 2021, Tesseract Systems, Inc.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//===----------------------------------------------------------------------===//
+
+use std::collections::{HashMap, VecDeque};
+use std::io;
+use std::net::{SocketAddr, ToSocketAddrs};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use tokio::net::{TcpListener, TcpStream};
+use tokio::runtime::Handle;
+use tokio::sync::mpsc::{self, UnboundedSender};
+use tokio::task::{JoinHandle, JoinSet};
+
+use crate::transports::stream::{read_frame, write_frame, DEFAULT_MAX_FRAME_LEN};
+use crate::{Error, ErrorKind, Result};
+
+use super::{is_valid_topic, Join, Role};
+
+pub const DEFAULT_MAX_QUEUED: usize = 64;
+pub const DEFAULT_MAX_MAILBOXES: usize = 4096;
+pub const DEFAULT_MAX_QUEUED_BYTES: usize = 256 * 1024 * 1024;
+pub const DEFAULT_MAILBOX_TTL: Duration = Duration::from_secs(5 * 60);
+
+const MAX_JOIN_LEN: usize = 1024;
+
+#[derive(Clone, Copy)]
+struct Limits {
+    max_frame_len: usize,
+    max_queued: usize,
+    max_mailboxes: usize,
+    max_queued_bytes: usize,
+    mailbox_ttl: Duration,
+}
+
+//the frames for a side of a topic. The peer is the connection of that side, if there is one
+struct Mailbox {
+    peer: Option<(u64, UnboundedSender<Vec<u8>>)>,
+    queue: VecDeque<Vec<u8>>,
+    bytes: usize,
+    //a connection of the side has been here
+    joined: bool,
+    //since when nobody has used the mailbox
+    idle: Instant,
+}
+
+impl Mailbox {
+    fn new(now: Instant) -> Self {
+        Self {
+            peer: None,
+            queue: VecDeque::new(),
+            bytes: 0,
+            joined: false,
+            idle: now,
+        }
+    }
+}
+
+//all the mailboxes of the relay, with the bytes queued in them
+#[derive(Default)]
+struct Post {
+    mailboxes: HashMap<(String, Role), Mailbox>,
+    queued: usize,
+}
+
+impl Post {
+    //the mailbox of a side, a new one if there is room for it
+    fn mailbox(&mut self, side: &(String, Role), limits: &Limits) -> Result<&mut Mailbox> {
+        let now = Instant::now();
+
+        if !self.mailboxes.contains_key(side) {
+            self.expire(now, limits.mailbox_ttl);
+            if self.mailboxes.len() >= limits.max_mailboxes {
+                return Err(Error::described(
+                    ErrorKind::Transport,
+                    "the relay has too many mailboxes",
+                ));
+            }
+        }
+
+        Ok(self
+            .mailboxes
+            .entry(side.clone())
+            .or_insert_with(|| Mailbox::new(now)))
+    }
+
+    //forgets the frames nobody has come for within the ttl
+    fn expire(&mut self, now: Instant, ttl: Duration) {
+        let mut expired = 0;
+        self.mailboxes.retain(|_, mailbox| {
+            let alive = mailbox.peer.is_some() || now.duration_since(mailbox.idle) < ttl;
+            if !alive {
+                expired += mailbox.bytes;
+            }
+            alive
+        });
+        self.queued -= expired;
+    }
+
+    fn clear(&mut self, side: &(String, Role)) -> VecDeque<Vec<u8>> {
+        match self.mailboxes.get_mut(side) {
+            Some(mailbox) => {
+                self.queued -= mailbox.bytes;
+                mailbox.bytes = 0;
+                std::mem::take(&mut mailbox.queue)
+            }
+            None => VecDeque::new(),
+        }
+    }
+}
+
+type Mailboxes = Arc<Mutex<Post>>;
+
+pub struct RelayServer {
+    listener: std::net::TcpListener,
+    handle: Option<Handle>,
+    limits: Limits,
+}
+
+impl RelayServer {
+    pub fn listen<A: ToSocketAddrs>(address: A) -> io::Result<Self> {
+        let listener = std::net::TcpListener::bind(address)?;
+        listener.set_nonblocking(true)?;
+
+        Ok(Self {
+            listener,
+            handle: None,
+            limits: Limits {
+                max_frame_len: DEFAULT_MAX_FRAME_LEN,
+                max_queued: DEFAULT_MAX_QUEUED,
+                max_mailboxes: DEFAULT_MAX_MAILBOXES,
+                max_queued_bytes: DEFAULT_MAX_QUEUED_BYTES,
+                mailbox_ttl: DEFAULT_MAILBOX_TTL,
+            },
+        })
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    //the peers are relayed on this runtime instead of the one bind() is called in
+    pub fn handle(self, handle: Handle) -> Self {
+        Self {
+            handle: Some(handle),
+            ..self
+        }
+    }
+
+    pub fn max_frame_len(self, max_frame_len: usize) -> Self {
+        Self {
+            limits: Limits {
+                max_frame_len,
+                ..self.limits
+            },
+            ..self
+        }
+    }
+
+    //how many frames can wait for a side that is not connected. The connection sending
+    //more than that is closed
+    pub fn max_queued(self, max_queued: usize) -> Self {
+        Self {
+            limits: Limits {
+                max_queued,
+                ..self.limits
+            },
+            ..self
+        }
+    }
+
+    //how many sides of the topics the relay keeps at once, connected or not.
+    //The connections joining or sending to a new one above it are closed
+    pub fn max_mailboxes(self, max_mailboxes: usize) -> Self {
+        Self {
+            limits: Limits {
+                max_mailboxes,
+                ..self.limits
+            },
+            ..self
+        }
+    }
+
+    //how many bytes can wait in all the mailboxes together
+    pub fn max_queued_bytes(self, max_queued_bytes: usize) -> Self {
+        Self {
+            limits: Limits {
+                max_queued_bytes,
+                ..self.limits
+            },
+            ..self
+        }
+    }
+
+    //how long the frames wait for a side that is not connected. Its mailbox is dropped after that
+    pub fn mailbox_ttl(self, mailbox_ttl: Duration) -> Self {
+        Self {
+            limits: Limits {
+                mailbox_ttl,
+                ..self.limits
+            },
+            ..self
+        }
+    }
+
+    //runs the relay in the background until the result is dropped
+    pub fn bind(self) -> BoundRelayServer {
+        let handle = self.handle.clone().unwrap_or_else(Handle::current);
+        BoundRelayServer {
+            task: handle.spawn(self.run()),
+        }
+    }
+
+    //runs the relay on the current task, forever
+    pub async fn run(self) {
+        let listener = match TcpListener::from_std(self.listener) {
+            Ok(listener) => listener,
+            Err(_) => return,
+        };
+
+        let mailboxes = Mailboxes::default();
+        let mut connections = JoinSet::new();
+        let mut next_id = 0u64;
+
+        loop {
+            tokio::select! {
+                accepted = listener.accept() => {
+                    if let Ok((stream, _)) = accepted {
+                        let _ = stream.set_nodelay(true);
+                        let mailboxes = Arc::clone(&mailboxes);
+                        let limits = self.limits;
+                        next_id += 1;
+                        let id = next_id;
+
+                        connections.spawn(async move {
+                            let _ = relay(stream, id, mailboxes, limits).await;
+                        });
+                    }
+                }
+                Some(_) = connections.join_next() => (),
+            }
+        }
+    }
+}
+
+fn deliver(mailboxes: &Mailboxes, to: &(String, Role), frame: Vec<u8>, limits: &Limits) -> Result<()> {
+    let mut post = mailboxes.lock().unwrap();
+    let queued = post.queued;
+    let mailbox = post.mailbox(to, limits)?;
+
+    let frame = match &mailbox.peer {
+        Some((_, peer)) => match peer.send(frame) {
+            Ok(()) => return Ok(()),
+            Err(mpsc::error::SendError(frame)) => frame,
+        },
+        None => frame,
+    };
+
+    if mailbox.queue.len() >= limits.max_queued {
+        return Err(Error::described(
+            ErrorKind::Transport,
+            &format!("the mailbox of the {:?} side of '{}' is full", to.1, to.0),
+        ));
+    }
+    if queued + frame.len() > limits.max_queued_bytes {
+        return Err(Error::described(
+            ErrorKind::Transport,
+            "the relay has too many frames queued",
+        ));
+    }
+
+    let len = frame.len();
+    mailbox.bytes += len;
+    mailbox.idle = Instant::now();
+    mailbox.queue.push_back(frame);
+    post.queued += len;
+    Ok(())
+}
+
+async fn relay(stream: TcpStream, id: u64, mailboxes: Mailboxes, limits: Limits) -> Result<()> {
+    let (mut reader, mut writer) = tokio::io::split(stream);
+
+    let join = match read_frame(&mut reader, MAX_JOIN_LEN).await? {
+        Some(join) => join,
+        None => return Ok(()),
+    };
+    let join: Join = serde_json::from_slice(&join)
+        .map_err(|e| Error::new(ErrorKind::Serialization, "can't read join", e))?;
+    if !is_valid_topic(&join.topic) {
+        return Err(Error::described(ErrorKind::Transport, "invalid topic"));
+    }
+
+    let own = (join.topic.clone(), join.role);
+    let peer = (join.topic, join.role.peer());
+
+    //A new connection of the same side replaces the old one, which is closed when its sender is dropped.
+    //The first connection of a side gets the frames queued for it. The ones queued after a connection
+    //of the side has left were meant for that one (i.e. the responses to its requests), so they are dropped
+    let (sender, mut receiver) = mpsc::unbounded_channel();
+    {
+        let mut post = mailboxes.lock().unwrap();
+        let joined = post.mailbox(&own, &limits)?.joined;
+        let queue = post.clear(&own);
+        if !joined {
+            for frame in queue {
+                let _ = sender.send(frame);
+            }
+        }
+
+        let mailbox = post.mailbox(&own, &limits)?;
+        mailbox.joined = true;
+        mailbox.peer = Some((id, sender));
+    }
+
+    let forward = async {
+        while let Some(frame) = read_frame(&mut reader, limits.max_frame_len).await? {
+            deliver(&mailboxes, &peer, frame, &limits)?;
+        }
+        Ok(())
+    };
+
+    let receive = async {
+        while let Some(frame) = receiver.recv().await {
+            write_frame(&mut writer, &frame).await?;
+        }
+        Ok(())
+    };
+
+    let result = tokio::select! {
+        result = forward => result,
+        result = receive => result,
+    };
+
+    let mut post = mailboxes.lock().unwrap();
+    if let Some(mailbox) = post.mailboxes.get_mut(&own) {
+        if matches!(mailbox.peer, Some((peer, _)) if peer == id) {
+            mailbox.peer = None;
+            mailbox.idle = Instant::now();
+        }
+    }
+
+    result
+}
+
+//Stops the relay and closes all its connections when dropped
+pub struct BoundRelayServer {
+    task: JoinHandle<()>,
+}
+
+impl Drop for BoundRelayServer {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
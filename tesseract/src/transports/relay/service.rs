@@ -0,0 +1,108 @@
+//===------------ service.rs --------------------------------------------===//
+//  Copyright 2021, Tesseract Systems, Inc.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//===----------------------------------------------------------------------===//
+
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::runtime::Handle;
+
+use crate::service::BoundTransport;
+use crate::service::Context;
+use crate::service::Transport;
+use crate::service::TransportProcessor;
+
+use crate::transports::stream::service::{serve, BoundStreamTransport};
+use crate::transports::stream::DEFAULT_MAX_FRAME_LEN;
+
+use super::{join, Join, Role};
+
+//The wallet side of a topic. Connects to the relay on bind and reconnects
+//after the reconnect delay whenever the connection is lost
+pub struct RelayTransport {
+    address: SocketAddr,
+    topic: String,
+    handle: Option<Handle>,
+    reconnect_delay: Duration,
+    max_frame_len: usize,
+}
+
+impl RelayTransport {
+    pub fn new(address: SocketAddr, topic: &str) -> Self {
+        Self {
+            address,
+            topic: topic.to_owned(),
+            handle: None,
+            reconnect_delay: Duration::from_secs(1),
+            max_frame_len: DEFAULT_MAX_FRAME_LEN,
+        }
+    }
+
+    //the relay connection, and reconnecting after it drops, runs on it. The current runtime by default
+    pub fn handle(self, handle: Handle) -> Self {
+        Self {
+            handle: Some(handle),
+            ..self
+        }
+    }
+
+    pub fn reconnect_delay(self, reconnect_delay: Duration) -> Self {
+        Self {
+            reconnect_delay,
+            ..self
+        }
+    }
+
+    pub fn max_frame_len(self, max_frame_len: usize) -> Self {
+        Self {
+            max_frame_len,
+            ..self
+        }
+    }
+}
+
+async fn connect(transport: RelayTransport, processor: Arc<dyn TransportProcessor + Send + Sync>) {
+    loop {
+        if let Ok(stream) = join(transport.address, transport.topic.clone(), Role::Wallet).await {
+            //the relay's SocketAddr and the topic the dApp has joined
+            let context = Context::new().with(transport.address).with(Join {
+                topic: transport.topic.clone(),
+                role: Role::Dapp,
+            });
+            let _ = serve(
+                stream,
+                Arc::clone(&processor),
+                context,
+                transport.max_frame_len,
+            )
+            .await;
+        }
+
+        tokio::time::sleep(transport.reconnect_delay).await;
+    }
+}
+
+impl Transport for RelayTransport {
+    fn bind(
+        self,
+        processor: Arc<dyn TransportProcessor + Send + Sync>,
+    ) -> Box<dyn BoundTransport + Send> {
+        let handle = self.handle.clone().unwrap_or_else(Handle::current);
+        let task = handle.spawn(connect(self, processor));
+
+        Box::new(BoundStreamTransport::new(task))
+    }
+}
@@ -0,0 +1,246 @@
+//===------------ client.rs --------------------------------------------===//
+//  Copyright 2021, Tesseract Systems, Inc.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//===----------------------------------------------------------------------===//
+
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::process::{ExitStatus, Stdio};
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+
+use futures::lock::Mutex;
+
+use tokio::process::{Child, ChildStdin, ChildStdout, Command};
+
+use crate::Protocol;
+use crate::{Error, ErrorKind, Result};
+
+use crate::client::transport::Status;
+use crate::client::Connection;
+use crate::client::Transport;
+
+use crate::transports::stream::{io_error, read_frame, write_frame, DEFAULT_MAX_FRAME_LEN};
+
+use super::STDIO;
+
+//how long a wallet that has closed its stdout is given to exit before it's killed
+const EXIT_TIMEOUT: Duration = Duration::from_secs(1);
+
+#[derive(Clone)]
+struct Program {
+    path: PathBuf,
+    args: Vec<OsString>,
+    envs: Vec<(OsString, OsString)>,
+}
+
+impl Program {
+    fn spawn(&self) -> std::io::Result<Child> {
+        Command::new(&self.path)
+            .args(&self.args)
+            .envs(self.envs.iter().map(|(k, v)| (k, v)))
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .kill_on_drop(true)
+            .spawn()
+    }
+
+    //the same lookup the spawn does: a path as is, a name in PATH
+    fn exists(&self) -> bool {
+        if self.path.components().count() > 1 {
+            return self.path.is_file();
+        }
+
+        std::env::var_os("PATH")
+            .map(|paths| std::env::split_paths(&paths).any(|dir| dir.join(&self.path).is_file()))
+            .unwrap_or(false)
+    }
+}
+
+fn exit_error(status: ExitStatus) -> Error {
+    let description = match status.code() {
+        Some(code) => format!("the wallet has exited with code {}", code),
+        None => format!("the wallet has crashed ({})", status),
+    };
+
+    Error::described(ErrorKind::Transport, &description)
+}
+
+//Spawns the wallet on the first send and respawns it on the next send after it has exited.
+//The wallet is killed when the connection is dropped
+struct ChildConnection {
+    program: Program,
+    max_frame_len: usize,
+    child: Mutex<Option<Child>>,
+    writer: Mutex<Option<ChildStdin>>,
+    reader: Mutex<Option<ChildStdout>>,
+}
+
+impl ChildConnection {
+    fn new(program: Program, max_frame_len: usize) -> Self {
+        Self {
+            program,
+            max_frame_len,
+            child: Mutex::new(None),
+            writer: Mutex::new(None),
+            reader: Mutex::new(None),
+        }
+    }
+
+    //explains why the pipes are broken (if the wallet has exited) and forgets the wallet
+    async fn reset(&self, error: Error) -> Error {
+        *self.reader.lock().await = None;
+        *self.writer.lock().await = None;
+
+        let mut child = match self.child.lock().await.take() {
+            Some(child) => child,
+            None => return error,
+        };
+
+        match tokio::time::timeout(EXIT_TIMEOUT, child.wait()).await {
+            Ok(Ok(status)) => exit_error(status),
+            _ => {
+                let _ = child.kill().await;
+                error
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl Connection for ChildConnection {
+    async fn send(self: Arc<Self>, request: Vec<u8>) -> Result<()> {
+        let mut writer = self.writer.lock().await;
+
+        if writer.is_none() {
+            let mut child = self.program.spawn().map_err(|e| {
+                io_error(&format!("can't start the wallet {}", self.program.path.display()), e)
+            })?;
+
+            *self.reader.lock().await = child.stdout.take();
+            *writer = child.stdin.take();
+            *self.child.lock().await = Some(child);
+        }
+
+        let result = match writer.as_mut() {
+            Some(stdin) => write_frame(stdin, &request).await,
+            None => Err(Error::kinded(ErrorKind::Weird)),
+        };
+
+        match result {
+            Ok(()) => Ok(()),
+            Err(error) => {
+                drop(writer);
+                Err(self.reset(error).await)
+            }
+        }
+    }
+
+    async fn receive(self: Arc<Self>) -> Result<Vec<u8>> {
+        let mut reader = self.reader.lock().await;
+
+        let result = match reader.as_mut() {
+            Some(stdout) => read_frame(stdout, self.max_frame_len).await.and_then(|frame| {
+                frame.ok_or_else(|| {
+                    Error::described(ErrorKind::Transport, "the wallet has closed its stdout")
+                })
+            }),
+            None => Err(Error::described(
+                ErrorKind::Transport,
+                "the wallet is not running",
+            )),
+        };
+
+        match result {
+            Ok(frame) => Ok(frame),
+            Err(error) => {
+                drop(reader);
+                Err(self.reset(error).await)
+            }
+        }
+    }
+}
+
+//Must be used from within a tokio runtime
+pub struct StdioTransport {
+    program: Program,
+    max_frame_len: usize,
+}
+
+impl StdioTransport {
+    //the wallet binary. A name without a path is looked up in PATH
+    pub fn new<P: AsRef<Path>>(program: P) -> Self {
+        Self {
+            program: Program {
+                path: program.as_ref().to_owned(),
+                args: Vec::new(),
+                envs: Vec::new(),
+            },
+            max_frame_len: DEFAULT_MAX_FRAME_LEN,
+        }
+    }
+
+    pub fn arg<S: Into<OsString>>(self, arg: S) -> Self {
+        let mut args = self.program.args;
+        args.push(arg.into());
+
+        Self {
+            program: Program { args, ..self.program },
+            ..self
+        }
+    }
+
+    pub fn env<K: Into<OsString>, V: Into<OsString>>(self, key: K, value: V) -> Self {
+        let mut envs = self.program.envs;
+        envs.push((key.into(), value.into()));
+
+        Self {
+            program: Program { envs, ..self.program },
+            ..self
+        }
+    }
+
+    pub fn max_frame_len(self, max_frame_len: usize) -> Self {
+        Self {
+            max_frame_len,
+            ..self
+        }
+    }
+}
+
+#[async_trait]
+impl Transport for StdioTransport {
+    fn id(&self) -> String {
+        STDIO.to_owned()
+    }
+
+    //the wallet is spawned only when there is something to send
+    async fn status(self: Arc<Self>, _: Box<dyn Protocol>) -> Status {
+        if self.program.exists() {
+            Status::Ready
+        } else {
+            Status::Unavailable(format!(
+                "the wallet {} is not installed",
+                self.program.path.display()
+            ))
+        }
+    }
+
+    fn connect(&self, _: Box<dyn Protocol>) -> Box<dyn Connection + Sync + Send> {
+        Box::new(ChildConnection::new(self.program.clone(), self.max_frame_len))
+    }
+}
@@ -0,0 +1,29 @@
+//===------------ mod.rs --------------------------------------------===//
+//  Copyright 2021, Tesseract Systems, Inc.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//===----------------------------------------------------------------------===//
+
+//A dApp spawns a headless (CLI) wallet and talks to it over the wallet's stdin and stdout,
+//with the length-prefixed frames of transports::stream. The wallet must not print to its stdout
+//anything else, so it should log to stderr, which is inherited from the dApp.
+//The dApp's request is waiting in the stdin already, so the wallet should add its services
+//to Tesseract before the transport.
+
+#[cfg(feature = "client")]
+pub mod client;
+
+#[cfg(feature = "service")]
+pub mod service;
+
+pub const STDIO: &str = "stdio";
@@ -0,0 +1,96 @@
+//===------------ service.rs --------------------------------------------===//
+//  Copyright 2021, Tesseract Systems, Inc.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//===----------------------------------------------------------------------===//
+
+use std::sync::Arc;
+
+use tokio::runtime::Handle;
+
+use crate::service::BoundTransport;
+use crate::service::Context;
+use crate::service::Transport;
+use crate::service::TransportProcessor;
+
+use crate::transports::stream::service::{serve, BoundStreamTransport};
+use crate::transports::stream::DEFAULT_MAX_FRAME_LEN;
+
+type OnClose = Box<dyn FnOnce() + Send>;
+
+//Serves the only dApp, the one that has spawned the wallet, over the process' own stdin and stdout
+pub struct StdioTransport {
+    handle: Option<Handle>,
+    on_close: Option<OnClose>,
+    max_frame_len: usize,
+}
+
+impl StdioTransport {
+    pub fn new() -> Self {
+        Self {
+            handle: None,
+            on_close: None,
+            max_frame_len: DEFAULT_MAX_FRAME_LEN,
+        }
+    }
+
+    //called when the dApp closes the wallet's stdin (or it becomes unusable),
+    //which is the usual moment for a CLI wallet to exit
+    pub fn on_close<F: FnOnce() + Send + 'static>(self, on_close: F) -> Self {
+        Self {
+            on_close: Some(Box::new(on_close)),
+            ..self
+        }
+    }
+
+    //stdin is read on it. A wallet launched as the dApp's child usually binds in main before entering
+    //a runtime, it needs the handle then
+    pub fn handle(self, handle: Handle) -> Self {
+        Self {
+            handle: Some(handle),
+            ..self
+        }
+    }
+
+    pub fn max_frame_len(self, max_frame_len: usize) -> Self {
+        Self {
+            max_frame_len,
+            ..self
+        }
+    }
+}
+
+impl Default for StdioTransport {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Transport for StdioTransport {
+    fn bind(self, processor: Arc<dyn TransportProcessor + Send + Sync>) -> Box<dyn BoundTransport + Send> {
+        let handle = self.handle.unwrap_or_else(Handle::current);
+        let on_close = self.on_close;
+        let max_frame_len = self.max_frame_len;
+
+        let task = handle.spawn(async move {
+            let stdio = tokio::io::join(tokio::io::stdin(), tokio::io::stdout());
+            let _ = serve(stdio, processor, Context::new(), max_frame_len).await;
+
+            if let Some(on_close) = on_close {
+                on_close();
+            }
+        });
+
+        Box::new(BoundStreamTransport::new(task))
+    }
+}
@@ -0,0 +1,110 @@
+//===------------ client.rs --------------------------------------------===//
+//  Copyright 2021, Tesseract Systems, Inc.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//===----------------------------------------------------------------------===//
+
+use std::io;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use futures::future::BoxFuture;
+use futures::lock::Mutex;
+
+use tokio::io::{AsyncRead, AsyncWrite, ReadHalf, WriteHalf};
+
+use crate::client::Connection;
+use crate::{Error, ErrorKind, Result};
+
+use super::{io_error, read_frame, write_frame};
+
+pub type Connector<S> = Box<dyn Fn() -> BoxFuture<'static, io::Result<S>> + Send + Sync>;
+
+//Connects lazily on the first send and reconnects on the next send after a failure
+pub struct StreamConnection<S> {
+    connector: Connector<S>,
+    reader: Mutex<Option<ReadHalf<S>>>,
+    writer: Mutex<Option<WriteHalf<S>>>,
+    max_frame_len: usize,
+}
+
+impl<S: AsyncRead + AsyncWrite + Send + Unpin + 'static> StreamConnection<S> {
+    pub fn new(connector: Connector<S>, max_frame_len: usize) -> Self {
+        Self {
+            connector,
+            reader: Mutex::new(None),
+            writer: Mutex::new(None),
+            max_frame_len,
+        }
+    }
+
+    async fn reset(&self) {
+        *self.reader.lock().await = None;
+        *self.writer.lock().await = None;
+    }
+}
+
+#[async_trait]
+impl<S: AsyncRead + AsyncWrite + Send + Unpin + 'static> Connection for StreamConnection<S> {
+    async fn send(self: Arc<Self>, request: Vec<u8>) -> Result<()> {
+        let mut writer = self.writer.lock().await;
+
+        if writer.is_none() {
+            let stream = (self.connector)()
+                .await
+                .map_err(|e| io_error("can't connect to the wallet", e))?;
+            let (read, write) = tokio::io::split(stream);
+
+            *self.reader.lock().await = Some(read);
+            *writer = Some(write);
+        }
+
+        let result = match writer.as_mut() {
+            Some(stream) => write_frame(stream, &request).await,
+            None => Err(Error::kinded(ErrorKind::Weird)),
+        };
+
+        if result.is_err() {
+            drop(writer);
+            self.reset().await;
+        }
+
+        result
+    }
+
+    async fn receive(self: Arc<Self>) -> Result<Vec<u8>> {
+        let mut reader = self.reader.lock().await;
+
+        let result = match reader.as_mut() {
+            Some(stream) => read_frame(stream, self.max_frame_len)
+                .await
+                .and_then(|frame| {
+                    frame.ok_or_else(|| {
+                        Error::described(ErrorKind::Transport, "the wallet has closed the connection")
+                    })
+                }),
+            None => Err(Error::described(
+                ErrorKind::Transport,
+                "not connected to the wallet",
+            )),
+        };
+
+        if result.is_err() {
+            drop(reader);
+            self.reset().await;
+        }
+
+        result
+    }
+}
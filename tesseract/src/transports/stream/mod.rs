@@ -0,0 +1,117 @@
+//===------------ mod.rs --------------------------------------------===//
+//  Copyright 2021, Tesseract Systems, Inc.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//===----------------------------------------------------------------------===//
+
+//Length-prefixed framing over byte streams (TCP, Unix sockets, pipes, etc.).
+//Every frame is a 4 byte big-endian length followed by the marked message itself.
+
+#[cfg(feature = "client")]
+pub mod client;
+
+#[cfg(feature = "service")]
+pub mod service;
+
+use std::io;
+
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+use crate::{Error, ErrorKind, Result};
+
+pub const DEFAULT_MAX_FRAME_LEN: usize = 64 * 1024 * 1024;
+
+pub(crate) fn io_error(description: &str, error: io::Error) -> Error {
+    Error::new(ErrorKind::Transport, description, error)
+}
+
+//None means the peer has closed the stream cleanly between the frames
+pub async fn read_frame<R: AsyncRead + Unpin>(
+    reader: &mut R,
+    max_len: usize,
+) -> Result<Option<Vec<u8>>> {
+    let mut header = [0u8; 4];
+    let mut read = 0;
+
+    while read < header.len() {
+        match reader.read(&mut header[read..]).await {
+            Ok(0) if read == 0 => return Ok(None),
+            Ok(0) => {
+                return Err(Error::described(
+                    ErrorKind::Transport,
+                    &format!(
+                        "the stream has ended after {} bytes of a frame header",
+                        read
+                    ),
+                ))
+            }
+            Ok(n) => read += n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => (),
+            Err(e) => return Err(io_error("can't read frame header", e)),
+        }
+    }
+
+    let len = u32::from_be_bytes(header) as usize;
+    if len > max_len {
+        return Err(Error::described(
+            ErrorKind::Transport,
+            &format!(
+                "frame of {} bytes exceeds the limit of {} bytes",
+                len, max_len
+            ),
+        ));
+    }
+
+    //grows with the data actually received, not with what the header claims
+    let mut frame = Vec::new();
+    reader
+        .take(len as u64)
+        .read_to_end(&mut frame)
+        .await
+        .map_err(|e| io_error("can't read frame", e))?;
+
+    if frame.len() < len {
+        return Err(Error::described(
+            ErrorKind::Transport,
+            &format!(
+                "the stream has ended after {} bytes of a frame of {} bytes",
+                frame.len(),
+                len
+            ),
+        ));
+    }
+
+    Ok(Some(frame))
+}
+
+pub async fn write_frame<W: AsyncWrite + Unpin>(writer: &mut W, frame: &[u8]) -> Result<()> {
+    let len = u32::try_from(frame.len()).map_err(|_| {
+        Error::described(
+            ErrorKind::Transport,
+            &format!("frame of {} bytes is too big to be sent", frame.len()),
+        )
+    })?;
+
+    writer
+        .write_all(&len.to_be_bytes())
+        .await
+        .map_err(|e| io_error("can't write frame header", e))?;
+    writer
+        .write_all(frame)
+        .await
+        .map_err(|e| io_error("can't write frame", e))?;
+    writer
+        .flush()
+        .await
+        .map_err(|e| io_error("can't flush frame", e))
+}
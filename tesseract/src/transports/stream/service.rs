@@ -0,0 +1,67 @@
+//===------------ service.rs --------------------------------------------===//
+//  Copyright 2021, Tesseract Systems, Inc.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//===----------------------------------------------------------------------===//
+
+use std::sync::Arc;
+
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::task::JoinHandle;
+
+use crate::service::BoundTransport;
+use crate::service::Context;
+use crate::service::TransportProcessor;
+use crate::Result;
+
+use super::{read_frame, write_frame};
+
+//Serves the requests of a single dApp connection one by one until it's closed.
+//The context describes the dApp and is current while its requests are processed
+pub async fn serve<S: AsyncRead + AsyncWrite + Unpin>(
+    stream: S,
+    processor: Arc<dyn TransportProcessor + Send + Sync>,
+    context: Context,
+    max_frame_len: usize,
+) -> Result<()> {
+    let (mut reader, mut writer) = tokio::io::split(stream);
+
+    while let Some(request) = read_frame(&mut reader, max_frame_len).await? {
+        let response = context
+            .clone()
+            .scope(Arc::clone(&processor).process(&request))
+            .await;
+        write_frame(&mut writer, &response).await?;
+    }
+
+    Ok(())
+}
+
+//Stops the listener (and all the connections it has accepted) when Tesseract is dropped
+pub struct BoundStreamTransport {
+    task: JoinHandle<()>,
+}
+
+impl BoundStreamTransport {
+    pub fn new(task: JoinHandle<()>) -> Self {
+        Self { task }
+    }
+}
+
+impl BoundTransport for BoundStreamTransport {}
+
+impl Drop for BoundStreamTransport {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
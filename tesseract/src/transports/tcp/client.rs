@@ -0,0 +1,100 @@
+//===------------ client.rs --------------------------------------------===//
+//  Copyright 2021, Tesseract Systems, Inc.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//===----------------------------------------------------------------------===//
+
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+
+use futures::FutureExt;
+
+use tokio::net::TcpStream;
+
+use crate::Protocol;
+
+use crate::client::transport::Status;
+use crate::client::Connection;
+use crate::client::Transport;
+
+use crate::transports::stream::client::StreamConnection;
+use crate::transports::stream::DEFAULT_MAX_FRAME_LEN;
+
+use super::TCP;
+
+//Must be used from within a tokio runtime
+pub struct TcpTransport {
+    address: SocketAddr,
+    connect_timeout: Duration,
+    max_frame_len: usize,
+}
+
+impl TcpTransport {
+    pub fn new(address: SocketAddr) -> Self {
+        Self {
+            address,
+            connect_timeout: Duration::from_secs(1),
+            max_frame_len: DEFAULT_MAX_FRAME_LEN,
+        }
+    }
+
+    //how long the status check waits for the wallet to accept the connection
+    pub fn connect_timeout(self, connect_timeout: Duration) -> Self {
+        Self {
+            connect_timeout,
+            ..self
+        }
+    }
+
+    pub fn max_frame_len(self, max_frame_len: usize) -> Self {
+        Self {
+            max_frame_len,
+            ..self
+        }
+    }
+}
+
+#[async_trait]
+impl Transport for TcpTransport {
+    fn id(&self) -> String {
+        TCP.to_owned()
+    }
+
+    async fn status(self: Arc<Self>, _: Box<dyn Protocol>) -> Status {
+        let connect = TcpStream::connect(self.address);
+
+        match tokio::time::timeout(self.connect_timeout, connect).await {
+            Ok(Ok(_)) => Status::Ready,
+            Ok(Err(e)) => Status::Unavailable(format!(
+                "can't connect to the wallet at {}: {}",
+                self.address, e
+            )),
+            Err(_) => Status::Unavailable(format!(
+                "the wallet at {} doesn't respond",
+                self.address
+            )),
+        }
+    }
+
+    fn connect(&self, _: Box<dyn Protocol>) -> Box<dyn Connection + Sync + Send> {
+        let address = self.address;
+
+        Box::new(StreamConnection::new(
+            Box::new(move || TcpStream::connect(address).boxed()),
+            self.max_frame_len,
+        ))
+    }
+}
@@ -0,0 +1,110 @@
+//===------------ service.rs --------------------------------------------===//
+//  Copyright 2021, Tesseract Systems, Inc.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//===----------------------------------------------------------------------===//
+
+use std::io;
+use std::net::{SocketAddr, ToSocketAddrs};
+use std::sync::Arc;
+
+use tokio::net::TcpListener;
+use tokio::runtime::Handle;
+use tokio::task::JoinSet;
+
+use crate::service::BoundTransport;
+use crate::service::Context;
+use crate::service::Transport;
+use crate::service::TransportProcessor;
+
+use crate::transports::stream::service::{serve, BoundStreamTransport};
+use crate::transports::stream::DEFAULT_MAX_FRAME_LEN;
+
+pub struct TcpTransport {
+    listener: std::net::TcpListener,
+    handle: Option<Handle>,
+    max_frame_len: usize,
+}
+
+impl TcpTransport {
+    //the socket is bound right away, so the errors are reported here rather than on bind
+    pub fn listen<A: ToSocketAddrs>(address: A) -> io::Result<Self> {
+        let listener = std::net::TcpListener::bind(address)?;
+        listener.set_nonblocking(true)?;
+
+        Ok(Self {
+            listener,
+            handle: None,
+            max_frame_len: DEFAULT_MAX_FRAME_LEN,
+        })
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    //the accept loop and every connection run on it. Without one, bind() has to be called within a tokio runtime
+    pub fn handle(self, handle: Handle) -> Self {
+        Self {
+            handle: Some(handle),
+            ..self
+        }
+    }
+
+    pub fn max_frame_len(self, max_frame_len: usize) -> Self {
+        Self {
+            max_frame_len,
+            ..self
+        }
+    }
+}
+
+async fn accept(
+    listener: std::net::TcpListener,
+    processor: Arc<dyn TransportProcessor + Send + Sync>,
+    max_frame_len: usize,
+) {
+    let listener = match TcpListener::from_std(listener) {
+        Ok(listener) => listener,
+        Err(_) => return,
+    };
+
+    //dropped together with the accept loop, which closes all the connections
+    let mut connections = JoinSet::new();
+
+    loop {
+        tokio::select! {
+            accepted = listener.accept() => {
+                if let Ok((stream, address)) = accepted {
+                    let _ = stream.set_nodelay(true);
+                    let processor = Arc::clone(&processor);
+                    let context = Context::new().with(address); //dApp's SocketAddr
+
+                    connections.spawn(async move {
+                        let _ = serve(stream, processor, context, max_frame_len).await;
+                    });
+                }
+            }
+            Some(_) = connections.join_next() => (),
+        }
+    }
+}
+
+impl Transport for TcpTransport {
+    fn bind(self, processor: Arc<dyn TransportProcessor + Send + Sync>) -> Box<dyn BoundTransport + Send> {
+        let handle = self.handle.unwrap_or_else(Handle::current);
+        let task = handle.spawn(accept(self.listener, processor, self.max_frame_len));
+
+        Box::new(BoundStreamTransport::new(task))
+    }
+}
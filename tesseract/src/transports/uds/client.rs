@@ -0,0 +1,84 @@
+//===------------ client.rs --------------------------------------------===//
+//  Copyright 2021, Tesseract Systems, Inc.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//===----------------------------------------------------------------------===//
+
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use futures::FutureExt;
+
+use tokio::net::UnixStream;
+
+use crate::Protocol;
+
+use crate::client::transport::Status;
+use crate::client::Connection;
+use crate::client::Transport;
+
+use crate::transports::stream::client::StreamConnection;
+use crate::transports::stream::DEFAULT_MAX_FRAME_LEN;
+
+use super::UDS;
+
+//Must be used from within a tokio runtime
+pub struct UdsTransport {
+    path: PathBuf,
+    max_frame_len: usize,
+}
+
+impl UdsTransport {
+    pub fn new<P: Into<PathBuf>>(path: P) -> Self {
+        Self {
+            path: path.into(),
+            max_frame_len: DEFAULT_MAX_FRAME_LEN,
+        }
+    }
+
+    pub fn max_frame_len(self, max_frame_len: usize) -> Self {
+        Self {
+            max_frame_len,
+            ..self
+        }
+    }
+}
+
+#[async_trait]
+impl Transport for UdsTransport {
+    fn id(&self) -> String {
+        UDS.to_owned()
+    }
+
+    async fn status(self: Arc<Self>, _: Box<dyn Protocol>) -> Status {
+        match UnixStream::connect(&self.path).await {
+            Ok(_) => Status::Ready,
+            Err(e) => Status::Unavailable(format!(
+                "can't connect to the wallet at {}: {}",
+                self.path.display(),
+                e
+            )),
+        }
+    }
+
+    fn connect(&self, _: Box<dyn Protocol>) -> Box<dyn Connection + Sync + Send> {
+        let path = self.path.clone();
+
+        Box::new(StreamConnection::new(
+            Box::new(move || UnixStream::connect(path.clone()).boxed()),
+            self.max_frame_len,
+        ))
+    }
+}
@@ -0,0 +1,157 @@
+//===------------ service.rs --------------------------------------------===//
+//  Copyright 2021, Tesseract Systems, Inc.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//===----------------------------------------------------------------------===//
+
+use std::fs;
+use std::io;
+use std::os::unix::fs::FileTypeExt;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use tokio::net::UnixListener;
+use tokio::runtime::Handle;
+use tokio::task::JoinSet;
+
+use crate::service::BoundTransport;
+use crate::service::Context;
+use crate::service::Transport;
+use crate::service::TransportProcessor;
+
+use crate::transports::stream::service::{serve, BoundStreamTransport};
+use crate::transports::stream::DEFAULT_MAX_FRAME_LEN;
+
+use super::PeerCredentials;
+
+pub struct UdsTransport {
+    listener: std::os::unix::net::UnixListener,
+    path: PathBuf,
+    handle: Option<Handle>,
+    max_frame_len: usize,
+}
+
+impl UdsTransport {
+    //a socket left by a crashed wallet is replaced, a live one is an AddrInUse error
+    pub fn listen<P: Into<PathBuf>>(path: P) -> io::Result<Self> {
+        let path = path.into();
+        remove_stale(&path)?;
+
+        let listener = std::os::unix::net::UnixListener::bind(&path)?;
+        listener.set_nonblocking(true)?;
+
+        Ok(Self {
+            listener,
+            path,
+            handle: None,
+            max_frame_len: DEFAULT_MAX_FRAME_LEN,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    //for the socket's accept loop, when the wallet binds the transport from outside a tokio runtime
+    pub fn handle(self, handle: Handle) -> Self {
+        Self {
+            handle: Some(handle),
+            ..self
+        }
+    }
+
+    pub fn max_frame_len(self, max_frame_len: usize) -> Self {
+        Self {
+            max_frame_len,
+            ..self
+        }
+    }
+}
+
+fn remove_stale(path: &Path) -> io::Result<()> {
+    match fs::symlink_metadata(path) {
+        Ok(metadata) if metadata.file_type().is_socket() => {
+            if std::os::unix::net::UnixStream::connect(path).is_ok() {
+                Err(io::Error::new(
+                    io::ErrorKind::AddrInUse,
+                    format!("a wallet is already listening at {}", path.display()),
+                ))
+            } else {
+                fs::remove_file(path)
+            }
+        }
+        _ => Ok(()),
+    }
+}
+
+async fn accept(
+    listener: std::os::unix::net::UnixListener,
+    processor: Arc<dyn TransportProcessor + Send + Sync>,
+    max_frame_len: usize,
+) {
+    let listener = match UnixListener::from_std(listener) {
+        Ok(listener) => listener,
+        Err(_) => return,
+    };
+
+    //dropped together with the accept loop, which closes all the connections
+    let mut connections = JoinSet::new();
+
+    loop {
+        tokio::select! {
+            accepted = listener.accept() => {
+                //a dApp we can't identify is not served at all
+                let Ok((stream, _)) = accepted else { continue };
+                let Ok(credentials) = stream.peer_cred() else { continue };
+
+                let credentials = PeerCredentials {
+                    pid: credentials.pid(),
+                    uid: credentials.uid(),
+                    gid: credentials.gid(),
+                };
+                let processor = Arc::clone(&processor);
+                let context = Context::new().with(credentials);
+
+                connections.spawn(async move {
+                    let _ = serve(stream, processor, context, max_frame_len).await;
+                });
+            }
+            Some(_) = connections.join_next() => (),
+        }
+    }
+}
+
+struct BoundUdsTransport {
+    _listener: BoundStreamTransport,
+    path: PathBuf,
+}
+
+impl BoundTransport for BoundUdsTransport {}
+
+impl Drop for BoundUdsTransport {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+impl Transport for UdsTransport {
+    fn bind(self, processor: Arc<dyn TransportProcessor + Send + Sync>) -> Box<dyn BoundTransport + Send> {
+        let handle = self.handle.unwrap_or_else(Handle::current);
+        let task = handle.spawn(accept(self.listener, processor, self.max_frame_len));
+
+        Box::new(BoundUdsTransport {
+            _listener: BoundStreamTransport::new(task),
+            path: self.path,
+        })
+    }
+}
@@ -0,0 +1,178 @@
+//===------------ client.rs --------------------------------------------===//
+//  Copyright 2021, Tesseract Systems, Inc.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//===----------------------------------------------------------------------===//
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use futures::lock::Mutex;
+use futures::stream::{SplitSink, SplitStream};
+use futures::{SinkExt, StreamExt};
+
+use tokio::net::TcpStream;
+
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::{connect_async_with_config, MaybeTlsStream, WebSocketStream};
+
+use crate::Protocol;
+use crate::{Error, ErrorKind, Result};
+
+use crate::client::transport::Status;
+use crate::client::Connection;
+use crate::client::Transport;
+
+use super::{config, ws_error, DEFAULT_MAX_MESSAGE_LEN, WS};
+
+type Socket = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+//Connects lazily on the first send and reconnects on the next send after a failure
+struct WsConnection {
+    url: String,
+    max_message_len: usize,
+    writer: Mutex<Option<SplitSink<Socket, Message>>>,
+    reader: Mutex<Option<SplitStream<Socket>>>,
+}
+
+impl WsConnection {
+    fn new(url: String, max_message_len: usize) -> Self {
+        Self {
+            url,
+            max_message_len,
+            writer: Mutex::new(None),
+            reader: Mutex::new(None),
+        }
+    }
+
+    async fn reset(&self) {
+        *self.reader.lock().await = None;
+        *self.writer.lock().await = None;
+    }
+}
+
+async fn next_binary(reader: &mut SplitStream<Socket>) -> Result<Vec<u8>> {
+    loop {
+        match reader.next().await {
+            Some(Ok(Message::Binary(data))) => return Ok(data.to_vec()),
+            Some(Ok(Message::Close(_))) | None => {
+                return Err(Error::described(
+                    ErrorKind::Transport,
+                    "the wallet has closed the connection",
+                ))
+            }
+            Some(Ok(_)) => continue, //pings are answered by tungstenite
+            Some(Err(e)) => return Err(ws_error("can't read message", e)),
+        }
+    }
+}
+
+#[async_trait]
+impl Connection for WsConnection {
+    async fn send(self: Arc<Self>, request: Vec<u8>) -> Result<()> {
+        let mut writer = self.writer.lock().await;
+
+        if writer.is_none() {
+            let config = Some(config(self.max_message_len));
+            let (socket, _) = connect_async_with_config(self.url.as_str(), config, true)
+                .await
+                .map_err(|e| ws_error("can't connect to the wallet", e))?;
+            let (write, read) = socket.split();
+
+            *self.reader.lock().await = Some(read);
+            *writer = Some(write);
+        }
+
+        let result = match writer.as_mut() {
+            Some(socket) => socket
+                .send(Message::binary(request))
+                .await
+                .map_err(|e| ws_error("can't send message", e)),
+            None => Err(Error::kinded(ErrorKind::Weird)),
+        };
+
+        if result.is_err() {
+            drop(writer);
+            self.reset().await;
+        }
+
+        result
+    }
+
+    async fn receive(self: Arc<Self>) -> Result<Vec<u8>> {
+        let mut reader = self.reader.lock().await;
+
+        let result = match reader.as_mut() {
+            Some(socket) => next_binary(socket).await,
+            None => Err(Error::described(
+                ErrorKind::Transport,
+                "not connected to the wallet",
+            )),
+        };
+
+        if result.is_err() {
+            drop(reader);
+            self.reset().await;
+        }
+
+        result
+    }
+}
+
+//Must be used from within a tokio runtime
+pub struct WsTransport {
+    url: String,
+    max_message_len: usize,
+}
+
+impl WsTransport {
+    //i.e. ws://127.0.0.1:8080
+    pub fn new(url: &str) -> Self {
+        Self {
+            url: url.to_owned(),
+            max_message_len: DEFAULT_MAX_MESSAGE_LEN,
+        }
+    }
+
+    pub fn max_message_len(self, max_message_len: usize) -> Self {
+        Self {
+            max_message_len,
+            ..self
+        }
+    }
+}
+
+#[async_trait]
+impl Transport for WsTransport {
+    fn id(&self) -> String {
+        WS.to_owned()
+    }
+
+    async fn status(self: Arc<Self>, _: Box<dyn Protocol>) -> Status {
+        match connect_async_with_config(self.url.as_str(), None, true).await {
+            Ok((mut socket, _)) => {
+                let _ = socket.close(None).await;
+                Status::Ready
+            }
+            Err(e) => Status::Unavailable(format!(
+                "can't connect to the wallet at {}: {}",
+                self.url, e
+            )),
+        }
+    }
+
+    fn connect(&self, _: Box<dyn Protocol>) -> Box<dyn Connection + Sync + Send> {
+        Box::new(WsConnection::new(self.url.clone(), self.max_message_len))
+    }
+}
@@ -0,0 +1,51 @@
+//===------------ mod.rs --------------------------------------------===//
+//  Copyright 2021, Tesseract Systems, Inc.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//===----------------------------------------------------------------------===//
+
+//WebSocket transport for the browser dApps, which can't use IPC but can reach a wallet on localhost.
+//Every request and response is a single binary message
+
+#[cfg(feature = "client")]
+pub mod client;
+
+#[cfg(feature = "service")]
+pub mod service;
+
+#[cfg(any(feature = "client", feature = "service"))]
+use tokio_tungstenite::tungstenite;
+
+#[cfg(any(feature = "client", feature = "service"))]
+use crate::{Error, ErrorKind};
+
+pub const WS: &str = "ws";
+
+pub const DEFAULT_MAX_MESSAGE_LEN: usize = 64 * 1024 * 1024;
+
+//The Origin header of the dApp's handshake (absent for the non-browser dApps).
+//Available to the executors and the services through service::Context::current()
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Origin(pub Option<String>);
+
+#[cfg(any(feature = "client", feature = "service"))]
+pub(crate) fn ws_error(description: &str, error: tungstenite::Error) -> Error {
+    Error::new(ErrorKind::Transport, description, error)
+}
+
+#[cfg(any(feature = "client", feature = "service"))]
+pub(crate) fn config(max_message_len: usize) -> tungstenite::protocol::WebSocketConfig {
+    tungstenite::protocol::WebSocketConfig::default()
+        .max_message_size(Some(max_message_len))
+        .max_frame_size(Some(max_message_len))
+}
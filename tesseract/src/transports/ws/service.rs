@@ -0,0 +1,203 @@
+//===------------ service.rs --------------------------------------------===//
+//  Copyright 2021, Tesseract Systems, Inc.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//===----------------------------------------------------------------------===//
+
+use std::io;
+use std::net::{SocketAddr, ToSocketAddrs};
+use std::sync::Arc;
+
+use futures::{SinkExt, StreamExt};
+
+use tokio::net::{TcpListener, TcpStream};
+use tokio::runtime::Handle;
+use tokio::task::JoinSet;
+
+use tokio_tungstenite::accept_hdr_async_with_config;
+use tokio_tungstenite::tungstenite::handshake::server::{ErrorResponse, Request, Response};
+use tokio_tungstenite::tungstenite::http::{header, StatusCode};
+use tokio_tungstenite::tungstenite::Message;
+
+use crate::service::BoundTransport;
+use crate::service::Context;
+use crate::service::Transport;
+use crate::service::TransportProcessor;
+use crate::transports::stream::service::BoundStreamTransport;
+use crate::Result;
+
+use super::{config, ws_error, Origin, DEFAULT_MAX_MESSAGE_LEN};
+
+//Decides which dApps may connect by the Origin header of the handshake (None for the non-browser dApps)
+pub type OriginCheck = Arc<dyn Fn(Option<&str>) -> bool + Send + Sync>;
+
+pub struct WsTransport {
+    listener: std::net::TcpListener,
+    handle: Option<Handle>,
+    origin: OriginCheck,
+    max_message_len: usize,
+}
+
+impl WsTransport {
+    //The socket is bound right away, so the errors are reported here rather than on bind.
+    //Only the non-browser dApps (without an Origin) are allowed until the browser ones are allowed
+    //with allow_origins() or origin(). Otherwise any website the user visits could sign with the wallet
+    pub fn listen<A: ToSocketAddrs>(address: A) -> io::Result<Self> {
+        let listener = std::net::TcpListener::bind(address)?;
+        listener.set_nonblocking(true)?;
+
+        Ok(Self {
+            listener,
+            handle: None,
+            origin: Arc::new(|origin| origin.is_none()),
+            max_message_len: DEFAULT_MAX_MESSAGE_LEN,
+        })
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    //the browser dApps served from these origins (i.e. "https://dapp.example") and the non-browser ones
+    pub fn allow_origins<I: IntoIterator<Item = S>, S: Into<String>>(self, origins: I) -> Self {
+        let origins: Vec<String> = origins.into_iter().map(Into::into).collect();
+
+        self.origin(move |origin| match origin {
+            Some(origin) => origins.iter().any(|allowed| allowed == origin),
+            None => true,
+        })
+    }
+
+    pub fn origin<F: Fn(Option<&str>) -> bool + Send + Sync + 'static>(self, check: F) -> Self {
+        Self {
+            origin: Arc::new(check),
+            ..self
+        }
+    }
+
+    //the websocket handshakes and sessions are spawned here. bind() falls back to the runtime it is called in,
+    //so it panics outside of one
+    pub fn handle(self, handle: Handle) -> Self {
+        Self {
+            handle: Some(handle),
+            ..self
+        }
+    }
+
+    pub fn max_message_len(self, max_message_len: usize) -> Self {
+        Self {
+            max_message_len,
+            ..self
+        }
+    }
+}
+
+fn forbidden() -> ErrorResponse {
+    let mut response = ErrorResponse::new(Some("the origin is not allowed".to_owned()));
+    *response.status_mut() = StatusCode::FORBIDDEN;
+    response
+}
+
+async fn serve(
+    stream: TcpStream,
+    address: SocketAddr,
+    processor: Arc<dyn TransportProcessor + Send + Sync>,
+    origin: OriginCheck,
+    max_message_len: usize,
+) -> Result<()> {
+    let mut allowed = None;
+
+    #[allow(clippy::result_large_err)] //the error type is tungstenite's
+    let check = |request: &Request, response: Response| {
+        let header = request
+            .headers()
+            .get(header::ORIGIN)
+            .and_then(|origin| origin.to_str().ok());
+
+        if origin(header) {
+            allowed = Some(Origin(header.map(str::to_owned)));
+            Ok(response)
+        } else {
+            Err(forbidden())
+        }
+    };
+
+    let mut socket = accept_hdr_async_with_config(stream, check, Some(config(max_message_len)))
+        .await
+        .map_err(|e| ws_error("handshake failed", e))?;
+
+    let context = Context::new()
+        .with(address) //dApp's SocketAddr
+        .with(allowed.unwrap_or(Origin(None)));
+
+    while let Some(message) = socket.next().await {
+        let request = match message.map_err(|e| ws_error("can't read message", e))? {
+            Message::Binary(request) => request,
+            Message::Close(_) => break,
+            _ => continue, //pings are answered by tungstenite
+        };
+
+        let response = context
+            .clone()
+            .scope(Arc::clone(&processor).process(&request))
+            .await;
+
+        socket
+            .send(Message::binary(response))
+            .await
+            .map_err(|e| ws_error("can't send message", e))?;
+    }
+
+    Ok(())
+}
+
+async fn accept(
+    listener: std::net::TcpListener,
+    processor: Arc<dyn TransportProcessor + Send + Sync>,
+    origin: OriginCheck,
+    max_message_len: usize,
+) {
+    let listener = match TcpListener::from_std(listener) {
+        Ok(listener) => listener,
+        Err(_) => return,
+    };
+
+    //dropped together with the accept loop, which closes all the connections
+    let mut connections = JoinSet::new();
+
+    loop {
+        tokio::select! {
+            accepted = listener.accept() => {
+                if let Ok((stream, address)) = accepted {
+                    let _ = stream.set_nodelay(true);
+                    let processor = Arc::clone(&processor);
+                    let origin = Arc::clone(&origin);
+
+                    connections.spawn(async move {
+                        let _ = serve(stream, address, processor, origin, max_message_len).await;
+                    });
+                }
+            }
+            Some(_) = connections.join_next() => (),
+        }
+    }
+}
+
+impl Transport for WsTransport {
+    fn bind(self, processor: Arc<dyn TransportProcessor + Send + Sync>) -> Box<dyn BoundTransport + Send> {
+        let handle = self.handle.unwrap_or_else(Handle::current);
+        let task = handle.spawn(accept(self.listener, processor, self.origin, self.max_message_len));
+
+        Box::new(BoundStreamTransport::new(task))
+    }
+}
@@ -0,0 +1,198 @@
+//===------------ client.rs --------------------------------------------===//
+//  Copyright 2021, Tesseract Systems, Inc.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//===----------------------------------------------------------------------===//
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use futures::FutureExt;
+
+use tokio::net::UnixStream;
+
+use crate::Protocol;
+
+use crate::client::transport::{Endpoint, Status};
+use crate::client::Connection;
+use crate::client::Transport;
+
+use crate::transports::stream::client::StreamConnection;
+use crate::transports::stream::DEFAULT_MAX_FRAME_LEN;
+
+use super::{default_directory, is_valid_id, manifest_path, registered, socket_path, Manifest, XDG};
+
+fn read_manifest(directory: &Path, id: &str) -> Option<Manifest> {
+    let manifest = fs::read(manifest_path(directory, id)).ok()?;
+    serde_json::from_slice(&manifest).ok()
+}
+
+//the first registered wallet supporting the protocol
+fn find_wallet(directory: &Path, protocol: &str) -> io::Result<PathBuf> {
+    registered(directory)
+        .into_iter()
+        .find(|id| read_manifest(directory, id).is_some_and(|m| m.supports(protocol)))
+        .map(|id| socket_path(directory, &id))
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("no wallet supporting {} is registered", protocol),
+            )
+        })
+}
+
+//Must be used from within a tokio runtime
+pub struct XdgTransport {
+    directory: Option<PathBuf>,
+    max_frame_len: usize,
+}
+
+impl XdgTransport {
+    //looks for the wallets in $XDG_RUNTIME_DIR/tesseract
+    pub fn new() -> Self {
+        Self {
+            directory: default_directory().ok(),
+            max_frame_len: DEFAULT_MAX_FRAME_LEN,
+        }
+    }
+
+    pub fn directory<P: Into<PathBuf>>(self, directory: P) -> Self {
+        Self {
+            directory: Some(directory.into()),
+            ..self
+        }
+    }
+
+    pub fn max_frame_len(self, max_frame_len: usize) -> Self {
+        Self {
+            max_frame_len,
+            ..self
+        }
+    }
+
+    fn stream_connection<F>(&self, socket: F) -> Box<dyn Connection + Sync + Send>
+    where
+        F: Fn() -> io::Result<PathBuf> + Send + Sync + 'static,
+    {
+        Box::new(StreamConnection::new(
+            Box::new(move || {
+                let socket = socket();
+                async move { UnixStream::connect(socket?).await }.boxed()
+            }),
+            self.max_frame_len,
+        ))
+    }
+}
+
+impl Default for XdgTransport {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+async fn status(directory: &Path, id: &str, manifest: &Manifest, protocol: &str) -> Status {
+    if !manifest.supports(protocol) {
+        return Status::Unavailable(format!("{} doesn't support {}", manifest.name, protocol));
+    }
+
+    match UnixStream::connect(socket_path(directory, id)).await {
+        Ok(_) => Status::Ready,
+        Err(e) => Status::Unavailable(format!("{} is not running: {}", manifest.name, e)),
+    }
+}
+
+#[async_trait]
+impl Transport for XdgTransport {
+    fn id(&self) -> String {
+        XDG.to_owned()
+    }
+
+    async fn status(self: Arc<Self>, protocol: Box<dyn Protocol>) -> Status {
+        let endpoints = self.endpoints(protocol).await;
+
+        if endpoints.iter().any(|e| e.status == Status::Ready) {
+            Status::Ready
+        } else {
+            Status::Unavailable("no running wallet supports the protocol".to_owned())
+        }
+    }
+
+    fn connect(&self, protocol: Box<dyn Protocol>) -> Box<dyn Connection + Sync + Send> {
+        let directory = self.directory.clone();
+        let protocol = protocol.id();
+
+        self.stream_connection(move || match &directory {
+            Some(directory) => find_wallet(directory, &protocol),
+            None => default_directory().and_then(|directory| find_wallet(&directory, &protocol)),
+        })
+    }
+
+    async fn endpoints(self: Arc<Self>, protocol: Box<dyn Protocol>) -> Vec<Endpoint>
+    where
+        Self: 'static,
+    {
+        //without wallets the transport reports itself, so that the dApp can tell why
+        let nothing = |reason: String| vec![Endpoint::new(XDG.to_owned(), Status::Unavailable(reason))];
+
+        let directory = match &self.directory {
+            Some(directory) => directory,
+            None => return nothing("XDG_RUNTIME_DIR is not set".to_owned()),
+        };
+        let protocol = protocol.id();
+
+        let mut endpoints = Vec::new();
+
+        for id in registered(directory) {
+            let manifest = match read_manifest(directory, &id) {
+                Some(manifest) => manifest,
+                None => continue,
+            };
+
+            endpoints.push(Endpoint {
+                id: format!("{}/{}", XDG, id),
+                status: status(directory, &id, &manifest, &protocol).await,
+                name: Some(manifest.name),
+                icon: manifest.icon.map(|icon| icon.to_string_lossy().into_owned()),
+            });
+        }
+
+        if endpoints.is_empty() {
+            nothing(format!("no wallets are registered in {}", directory.display()))
+        } else {
+            endpoints
+        }
+    }
+
+    fn connect_endpoint(
+        &self,
+        endpoint: &str,
+        protocol: Box<dyn Protocol>,
+    ) -> Box<dyn Connection + Sync + Send> {
+        let id = endpoint
+            .strip_prefix(XDG)
+            .and_then(|id| id.strip_prefix('/'))
+            .filter(|id| is_valid_id(id))
+            .map(str::to_owned);
+
+        match (id, self.directory.clone()) {
+            (Some(id), Some(directory)) => {
+                self.stream_connection(move || Ok(socket_path(&directory, &id)))
+            }
+            _ => self.connect(protocol),
+        }
+    }
+}
@@ -0,0 +1,125 @@
+//===------------ mod.rs --------------------------------------------===//
+//  Copyright 2021, Tesseract Systems, Inc.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//===----------------------------------------------------------------------===//
+
+//Discovery of the wallets installed on a Linux desktop. Every running wallet registers
+//a socket and a manifest in $XDG_RUNTIME_DIR/tesseract/:
+//  <wallet>.sock - the Unix socket the wallet listens on (see transports::uds)
+//  <wallet>.json - the manifest describing the wallet
+//The dApp's transport enumerates them and reports every wallet as a separate endpoint.
+
+#[cfg(feature = "client")]
+pub mod client;
+
+#[cfg(feature = "service")]
+pub mod service;
+
+use std::env;
+use std::io;
+use std::path::PathBuf;
+
+#[cfg(any(feature = "client", feature = "service"))]
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+pub const XDG: &str = "xdg";
+
+const DIRECTORY: &str = "tesseract";
+#[cfg(any(feature = "client", feature = "service"))]
+const MANIFEST_EXTENSION: &str = "json";
+#[cfg(any(feature = "client", feature = "service"))]
+const SOCKET_EXTENSION: &str = "sock";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Manifest {
+    pub name: String,
+    pub protocols: Vec<String>, //ids of the supported protocols
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub icon: Option<PathBuf>,
+}
+
+impl Manifest {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_owned(),
+            protocols: Vec::new(),
+            icon: None,
+        }
+    }
+
+    pub fn protocol(self, protocol: &str) -> Self {
+        let mut protocols = self.protocols;
+        protocols.push(protocol.to_owned());
+
+        Self { protocols, ..self }
+    }
+
+    pub fn icon<P: Into<PathBuf>>(self, icon: P) -> Self {
+        Self {
+            icon: Some(icon.into()),
+            ..self
+        }
+    }
+
+    pub fn supports(&self, protocol: &str) -> bool {
+        self.protocols.iter().any(|p| p == protocol)
+    }
+}
+
+//$XDG_RUNTIME_DIR/tesseract
+pub fn default_directory() -> io::Result<PathBuf> {
+    env::var_os("XDG_RUNTIME_DIR")
+        .filter(|dir| !dir.is_empty())
+        .map(|dir| PathBuf::from(dir).join(DIRECTORY))
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "XDG_RUNTIME_DIR is not set"))
+}
+
+#[cfg(any(feature = "client", feature = "service"))]
+//wallet ids become file names, so only a safe subset of characters is allowed
+pub(crate) fn is_valid_id(id: &str) -> bool {
+    !id.is_empty()
+        && !id.starts_with('.')
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.')
+}
+
+//wallet ids of the manifests in the directory, sorted
+#[cfg(feature = "client")]
+pub(crate) fn registered(directory: &Path) -> Vec<String> {
+    let mut ids: Vec<_> = std::fs::read_dir(directory)
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.ok()?.file_name().into_string().ok()?;
+            let id = name.strip_suffix(MANIFEST_EXTENSION)?.strip_suffix('.')?;
+            is_valid_id(id).then(|| id.to_owned())
+        })
+        .collect();
+
+    ids.sort();
+    ids
+}
+
+#[cfg(any(feature = "client", feature = "service"))]
+pub(crate) fn manifest_path(directory: &Path, id: &str) -> PathBuf {
+    directory.join(format!("{}.{}", id, MANIFEST_EXTENSION))
+}
+
+#[cfg(any(feature = "client", feature = "service"))]
+pub(crate) fn socket_path(directory: &Path, id: &str) -> PathBuf {
+    directory.join(format!("{}.{}", id, SOCKET_EXTENSION))
+}
@@ -0,0 +1,123 @@
+//===------------ service.rs --------------------------------------------===//
+//  Copyright 2021, Tesseract Systems, Inc.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//===----------------------------------------------------------------------===//
+
+use std::fs;
+use std::io;
+use std::os::unix::fs::DirBuilderExt;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use tokio::runtime::Handle;
+
+use crate::service::BoundTransport;
+use crate::service::Transport;
+use crate::service::TransportProcessor;
+
+use crate::transports::uds::service::UdsTransport;
+
+use super::{default_directory, is_valid_id, manifest_path, socket_path, Manifest};
+
+//removes the manifest, so that the dApps stop seeing the wallet
+struct Registration {
+    manifest: PathBuf,
+}
+
+impl Registration {
+    fn new(directory: &Path, id: &str, manifest: &Manifest) -> io::Result<Self> {
+        let path = manifest_path(directory, id);
+        let temporary = directory.join(format!(".{}.tmp", id));
+
+        let manifest = serde_json::to_vec_pretty(manifest)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+        //the dApps never read a half-written manifest
+        fs::write(&temporary, manifest)?;
+        fs::rename(&temporary, &path)?;
+
+        Ok(Self { manifest: path })
+    }
+}
+
+impl Drop for Registration {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.manifest);
+    }
+}
+
+//The wallet is registered until the transport (or the bound transport) is dropped
+pub struct XdgTransport {
+    registration: Registration,
+    uds: UdsTransport,
+}
+
+impl XdgTransport {
+    //registers in $XDG_RUNTIME_DIR/tesseract
+    pub fn register(id: &str, manifest: Manifest) -> io::Result<Self> {
+        Self::register_in(default_directory()?, id, manifest)
+    }
+
+    pub fn register_in<P: AsRef<Path>>(directory: P, id: &str, manifest: Manifest) -> io::Result<Self> {
+        let directory = directory.as_ref();
+
+        if !is_valid_id(id) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("invalid wallet id: {}", id),
+            ));
+        }
+
+        fs::DirBuilder::new()
+            .recursive(true)
+            .mode(0o700)
+            .create(directory)?;
+
+        //listening before the manifest is visible
+        let uds = UdsTransport::listen(socket_path(directory, id))?;
+        let registration = Registration::new(directory, id, &manifest)?;
+
+        Ok(Self { registration, uds })
+    }
+
+    pub fn handle(self, handle: Handle) -> Self {
+        Self {
+            uds: self.uds.handle(handle),
+            ..self
+        }
+    }
+
+    pub fn max_frame_len(self, max_frame_len: usize) -> Self {
+        Self {
+            uds: self.uds.max_frame_len(max_frame_len),
+            ..self
+        }
+    }
+}
+
+struct BoundXdgTransport {
+    _registration: Registration, //dropped first
+    _socket: Box<dyn BoundTransport + Send>,
+}
+
+impl BoundTransport for BoundXdgTransport {}
+
+impl Transport for XdgTransport {
+    fn bind(self, processor: Arc<dyn TransportProcessor + Send + Sync>) -> Box<dyn BoundTransport + Send> {
+        Box::new(BoundXdgTransport {
+            _registration: self.registration,
+            _socket: self.uds.bind(processor),
+        })
+    }
+}
@@ -0,0 +1,179 @@
+//===------------ channel.rs ------------------------------------------===//
+//  Copyright 2021, Tesseract Systems, Inc.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//===----------------------------------------------------------------------===//
+
+mod common;
+
+use std::sync::{mpsc, Arc, Mutex};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use futures::executor::block_on;
+use futures::future::join_all;
+
+use tesseract_one::client::delegate::SingleTransportDelegate;
+use tesseract_one::client::transport::Status;
+use tesseract_one::client::{Connection, Transport};
+use tesseract_one::envelope::EnvelopeHeader;
+use tesseract_one::serialize::Serializer;
+use tesseract_one::service::{Transport as _, TransportProcessor};
+use tesseract_one::transports::channel::{client::ChannelTransport, service, Channel};
+use tesseract_one::{client, ErrorKind};
+
+use tesseract_protocol_test::{Test, TestService};
+
+use common::TestWallet;
+
+fn wallet(channel: &Arc<Channel>) -> tesseract_one::service::Tesseract {
+    tesseract_one::service::Tesseract::new()
+        .transport(service::ChannelTransport::new(channel))
+        .service(TestWallet {})
+}
+
+fn dapp(channel: &Arc<Channel>) -> client::Tesseract {
+    client::Tesseract::new(SingleTransportDelegate::arc()).transport(ChannelTransport::new(channel))
+}
+
+fn request(id: u64) -> Vec<u8> {
+    let request = serde_json::json!({
+        "version": 1,
+        "protocol": "test",
+        "method": "sign_transaction",
+        "id": id,
+        "request": { "transaction": format!("tx{}", id) }
+    });
+
+    let mut marked = b"json".to_vec();
+    marked.extend(serde_json::to_vec(&request).unwrap());
+    marked
+}
+
+#[test]
+fn sign_in_process() {
+    let channel = Arc::new(Channel::new());
+    let _wallet = wallet(&channel);
+    let service = dapp(&channel).service(Test::Protocol);
+
+    block_on(async {
+        let signed = Arc::clone(&service).sign_transaction("transaction").await;
+        assert_eq!("transaction_signed!", signed.unwrap());
+
+        let failed = service.sign_transaction("make_error").await;
+        assert_eq!(ErrorKind::Weird, failed.unwrap_err().kind);
+    });
+}
+
+#[test]
+fn responses_come_in_order() {
+    let channel = Arc::new(Channel::new());
+    let _wallet = wallet(&channel);
+    let connection: Arc<dyn Connection + Sync + Send> = ChannelTransport::new(&channel).connect(Box::new(Test::Protocol)).into();
+
+    block_on(async {
+        for id in 1..=3 {
+            Arc::clone(&connection).send(request(id)).await.unwrap();
+        }
+
+        for id in 1..=3 {
+            let response = Arc::clone(&connection).receive().await.unwrap();
+            let (envelope, _) = Serializer::deserialize_marked::<EnvelopeHeader>(&response).unwrap();
+            assert_eq!(Some(id), envelope.id);
+        }
+
+        let nothing = connection.receive().await;
+        assert_eq!(ErrorKind::Transport, nothing.unwrap_err().kind);
+    });
+}
+
+#[test]
+fn serves_many_dapps_at_once() {
+    let channel = Arc::new(Channel::new());
+    let _wallet = wallet(&channel);
+
+    let dapps = (0..16).map(|i| {
+        let service = dapp(&channel).service(Test::Protocol);
+
+        async move {
+            for j in 0..10 {
+                let transaction = format!("tx_{}_{}", i, j);
+                let signed = Arc::clone(&service).sign_transaction(&transaction).await;
+                assert_eq!(format!("{}_signed!", transaction), signed.unwrap());
+            }
+        }
+    });
+
+    block_on(join_all(dapps));
+}
+
+#[test]
+fn disconnects_with_wallet() {
+    let channel = Arc::new(Channel::new());
+    let transport = Arc::new(ChannelTransport::new(&channel));
+    let connection: Arc<dyn Connection + Sync + Send> = transport.connect(Box::new(Test::Protocol)).into();
+
+    block_on(async {
+        let status = Arc::clone(&transport).status(Box::new(Test::Protocol)).await;
+        assert!(matches!(status, Status::Unavailable(_)));
+
+        let failed = Arc::clone(&connection).send(request(1)).await;
+        assert_eq!(ErrorKind::Transport, failed.unwrap_err().kind);
+
+        let wallet = wallet(&channel);
+        let status = Arc::clone(&transport).status(Box::new(Test::Protocol)).await;
+        assert_eq!(Status::Ready, status);
+
+        //sent before the wallet is gone, so still served
+        Arc::clone(&connection).send(request(2)).await.unwrap();
+        drop(wallet);
+        assert!(Arc::clone(&connection).receive().await.is_ok());
+
+        let status = Arc::clone(&transport).status(Box::new(Test::Protocol)).await;
+        assert!(matches!(status, Status::Unavailable(_)));
+
+        let failed = connection.send(request(3)).await;
+        assert_eq!(ErrorKind::Transport, failed.unwrap_err().kind);
+    });
+}
+
+//tells the test about every request it gets
+struct Notifying {
+    notify: Mutex<mpsc::Sender<Vec<u8>>>,
+}
+
+#[async_trait]
+impl TransportProcessor for Notifying {
+    async fn process(self: Arc<Self>, data: &[u8]) -> Vec<u8> {
+        self.notify.lock().unwrap().send(data.to_vec()).unwrap();
+        data.to_vec()
+    }
+}
+
+#[test]
+fn wallet_doesnt_wait_for_receive() {
+    let channel = Arc::new(Channel::new());
+    let (notify, notified) = mpsc::channel();
+    let _bound = service::ChannelTransport::new(&channel).bind(Arc::new(Notifying {
+        notify: Mutex::new(notify),
+    }));
+
+    let connection: Arc<dyn Connection + Sync + Send> = ChannelTransport::new(&channel).connect(Box::new(Test::Protocol)).into();
+
+    //processed while nobody awaits the response
+    block_on(Arc::clone(&connection).send(request(1))).unwrap();
+    let processed = notified.recv_timeout(Duration::from_secs(10)).unwrap();
+    assert_eq!(request(1), processed);
+
+    assert_eq!(request(1), block_on(connection.receive()).unwrap());
+}
@@ -0,0 +1,56 @@
+//===------------ mod.rs --------------------------------------------===//
+//  Copyright 2021, Tesseract Systems, Inc.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//===----------------------------------------------------------------------===//
+
+//Shared pieces of the transport tests: a wallet signing with the Test protocol
+
+#![allow(dead_code)]
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use tesseract_one::{Error, ErrorKind, Result};
+
+use tesseract_protocol_test::service::TestExecutor;
+use tesseract_protocol_test::{Test, TestService};
+
+pub struct TestWallet {}
+
+impl tesseract_one::service::Service for TestWallet {
+    type Protocol = Test;
+
+    fn protocol(&self) -> &Test {
+        &Test::Protocol
+    }
+
+    fn to_executor(self) -> Box<dyn tesseract_one::service::Executor + Send + Sync> {
+        Box::new(TestExecutor::from_service(self))
+    }
+}
+
+#[async_trait]
+impl TestService for TestWallet {
+    async fn sign_transaction(self: Arc<Self>, req: &str) -> Result<String> {
+        if req == "make_error" {
+            Err(Error::described(
+                ErrorKind::Weird,
+                "intentional error for test",
+            ))
+        } else {
+            Ok(format!("{}_signed!", req))
+        }
+    }
+}
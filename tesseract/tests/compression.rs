@@ -0,0 +1,205 @@
+//===------------ compression.rs ------------------------------------------===//
+//  Copyright 2021, Tesseract Systems, Inc.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//===----------------------------------------------------------------------===//
+
+mod common;
+
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use futures::executor::block_on;
+
+use tesseract_one::client::delegate::SingleTransportDelegate;
+use tesseract_one::client::{Connection, Transport};
+use tesseract_one::compression::{self, Algorithm, Config, HANDSHAKE_MARKER};
+use tesseract_one::service::{BoundTransport, TransportProcessor};
+use tesseract_one::transports::channel::{self, Channel};
+use tesseract_one::{client, service, ErrorKind};
+
+use tesseract_protocol_test::{Test, TestService};
+
+use common::TestWallet;
+
+//remembers the first 4 bytes of every message that reaches the wallet
+#[derive(Default, Clone)]
+struct Wire {
+    markers: Arc<Mutex<Vec<String>>>,
+}
+
+impl Wire {
+    fn markers(&self) -> Vec<String> {
+        self.markers.lock().unwrap().clone()
+    }
+}
+
+struct SpyProcessor {
+    processor: Arc<dyn TransportProcessor + Send + Sync>,
+    wire: Wire,
+}
+
+#[async_trait]
+impl TransportProcessor for SpyProcessor {
+    async fn process(self: Arc<Self>, data: &[u8]) -> Vec<u8> {
+        let marker = String::from_utf8_lossy(&data[..4.min(data.len())]).into_owned();
+        self.wire.markers.lock().unwrap().push(marker);
+        Arc::clone(&self.processor).process(data).await
+    }
+}
+
+struct SpyTransport<T: service::Transport> {
+    transport: T,
+    wire: Wire,
+}
+
+impl<T: service::Transport> service::Transport for SpyTransport<T> {
+    fn bind(
+        self,
+        processor: Arc<dyn TransportProcessor + Send + Sync>,
+    ) -> Box<dyn BoundTransport + Send> {
+        self.transport.bind(Arc::new(SpyProcessor {
+            processor,
+            wire: self.wire,
+        }))
+    }
+}
+
+fn wallet(channel: &Arc<Channel>, wire: &Wire, compressed: bool) -> service::Tesseract {
+    //the spy is bound the last, so it sees the wire
+    let transport = SpyTransport {
+        transport: channel::service::ChannelTransport::new(channel),
+        wire: wire.clone(),
+    };
+    let wallet = service::Tesseract::new();
+
+    let wallet = if compressed {
+        wallet.transport(compression::service::CompressedTransport::new(transport))
+    } else {
+        wallet.transport(transport)
+    };
+
+    wallet.service(TestWallet {})
+}
+
+fn dapp(channel: &Arc<Channel>, config: Config) -> client::Tesseract {
+    client::Tesseract::new(SingleTransportDelegate::arc()).transport(
+        compression::client::CompressedTransport::new_with_config(
+            channel::client::ChannelTransport::new(channel),
+            config,
+        ),
+    )
+}
+
+fn big_transaction() -> String {
+    "0a1b2c3d".repeat(1000)
+}
+
+#[test]
+fn compresses_big_requests() {
+    let channel = Arc::new(Channel::new());
+    let wire = Wire::default();
+    let _wallet = wallet(&channel, &wire, true);
+    let service = dapp(&channel, Config::default()).service(Test::Protocol);
+
+    block_on(async {
+        for _ in 0..2 {
+            let signed = Arc::clone(&service)
+                .sign_transaction(&big_transaction())
+                .await;
+            assert_eq!(format!("{}_signed!", big_transaction()), signed.unwrap());
+        }
+    });
+
+    //a single handshake per connection
+    assert_eq!(wire.markers(), [HANDSHAKE_MARKER, "defl", "defl"]);
+}
+
+#[test]
+fn plain_wallet_gets_plain_requests() {
+    let channel = Arc::new(Channel::new());
+    let wire = Wire::default();
+    let _wallet = wallet(&channel, &wire, false);
+    let service = dapp(&channel, Config::default()).service(Test::Protocol);
+
+    block_on(async {
+        for _ in 0..2 {
+            let signed = Arc::clone(&service)
+                .sign_transaction(&big_transaction())
+                .await;
+            assert_eq!(format!("{}_signed!", big_transaction()), signed.unwrap());
+        }
+    });
+
+    //the wallet answered the handshake with an error and is not asked again
+    assert_eq!(wire.markers(), [HANDSHAKE_MARKER, "json", "json"]);
+}
+
+#[test]
+fn small_requests_stay_plain() {
+    let channel = Arc::new(Channel::new());
+    let wire = Wire::default();
+    let _wallet = wallet(&channel, &wire, true);
+    let config = Config::default().threshold(big_transaction().len());
+    let service = dapp(&channel, config).service(Test::Protocol);
+
+    block_on(async {
+        let signed = Arc::clone(&service).sign_transaction("tx").await;
+        assert_eq!("tx_signed!", signed.unwrap());
+        assert_eq!(wire.markers(), ["json"]);
+
+        //the envelope makes it above the threshold
+        let signed = Arc::clone(&service)
+            .sign_transaction(&big_transaction())
+            .await;
+        assert_eq!(format!("{}_signed!", big_transaction()), signed.unwrap());
+    });
+
+    assert_eq!(wire.markers(), ["json", HANDSHAKE_MARKER, "defl"]);
+}
+
+#[test]
+fn decompression_is_limited() {
+    let bomb = Algorithm::Deflate.compress(&vec![0; 1024 * 1024]).unwrap();
+    assert!(bomb.len() < 4096);
+
+    let (algorithm, data) = Algorithm::read_marker(&bomb).unwrap();
+    assert_eq!(
+        1024 * 1024,
+        algorithm.decompress(data, 1024 * 1024).unwrap().len()
+    );
+    let exceeded = algorithm.decompress(data, 1024 * 1024 - 1);
+    assert_eq!(ErrorKind::Serialization, exceeded.unwrap_err().kind);
+
+    //the wallet refuses to inflate it
+    let channel = Arc::new(Channel::new());
+    let _wallet = service::Tesseract::new()
+        .transport(compression::service::CompressedTransport::new_with_config(
+            channel::service::ChannelTransport::new(&channel),
+            Config::default().limit(4096),
+        ))
+        .service(TestWallet {});
+
+    let connection: Arc<dyn Connection + Sync + Send> =
+        channel::client::ChannelTransport::new(&channel)
+            .connect(Box::new(Test::Protocol))
+            .into();
+
+    let response = block_on(async {
+        Arc::clone(&connection).send(bomb).await.unwrap();
+        connection.receive().await.unwrap()
+    });
+    let response = String::from_utf8(response).unwrap();
+    assert!(response.starts_with("json"));
+    assert!(response.contains("exceeds the limit"));
+}
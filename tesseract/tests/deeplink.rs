@@ -0,0 +1,120 @@
+//===------------ deeplink.rs --------------------------------------------===//
+//  Copyright 2021, Tesseract Systems, Inc.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//===----------------------------------------------------------------------===//
+
+use tesseract_one::serialize::Serializer;
+use tesseract_one::transports::deeplink::{client, service};
+use tesseract_one::ErrorKind;
+
+fn request(len: usize) -> Vec<u8> {
+    let request = serde_json::json!({
+        "protocol": "substrate-v1",
+        "method": "sign_transaction",
+        "id": 1,
+        "request": { "transaction": "ab".repeat(len) }
+    });
+    Serializer::Json.serialize(&request, true).unwrap()
+}
+
+#[test]
+fn test_request_and_response_round_trip() {
+    let dapp = client::DeepLink::new("mydapp://tesseract/callback?session=42").unwrap();
+    let wallet = service::DeepLink::new();
+
+    let request = request(100);
+    let url = dapp.request_url(&request).unwrap();
+    assert!(url.as_str().starts_with("tesseract://request?payload="));
+
+    let received = wallet.parse_request(url.as_str()).unwrap();
+    assert_eq!(received.request, request);
+    let return_url = received.return_url.unwrap();
+    assert_eq!(
+        return_url.as_str(),
+        "mydapp://tesseract/callback?session=42"
+    );
+
+    let response = Serializer::Cbor
+        .serialize(&serde_json::json!({"id": 1, "response": "signed"}), true)
+        .unwrap();
+    let url = wallet.response_url(&return_url, &response).unwrap();
+    assert!(url.as_str().contains("session=42"));
+
+    assert_eq!(dapp.parse_response(url.as_str()).unwrap(), response);
+}
+
+#[test]
+fn test_size_limits() {
+    let dapp = client::DeepLink::new("mydapp://tesseract")
+        .unwrap()
+        .max_url_len(1024);
+    let wallet = service::DeepLink::new().max_url_len(1024);
+
+    assert!(dapp.request_url(&request(100)).is_ok());
+    let error = dapp.request_url(&request(1000)).unwrap_err();
+    assert_eq!(error.kind, ErrorKind::Transport);
+
+    let long = client::DeepLink::new("mydapp://tesseract")
+        .unwrap()
+        .request_url(&request(1000))
+        .unwrap();
+    let error = wallet.parse_request(long.as_str()).unwrap_err();
+    assert_eq!(error.kind, ErrorKind::Transport);
+
+    let return_url = url::Url::parse("mydapp://tesseract").unwrap();
+    assert!(wallet.response_url(&return_url, &request(1000)).is_err());
+}
+
+#[test]
+fn test_rejects_malformed_urls() {
+    let wallet = service::DeepLink::new();
+    let payload = "anNvbnt9"; //json{}
+
+    let malformed = [
+        "not a url",
+        "https://request?payload=anNvbnt9",
+        "tesseract://response?payload=anNvbnt9",
+        "tesseract://request",
+        "tesseract://request?payload=%%%",
+        "tesseract://request?payload=anNvbnt9&payload=anNvbnt9",
+        "tesseract://request?payload=anNvbnt9&return=not%20a%20url",
+        "tesseract://request?payload=anNvbnt9&return=mailto%3Adapp%40example.com",
+    ];
+    for url in malformed {
+        let error = wallet.parse_request(url).unwrap_err();
+        assert_eq!(error.kind, ErrorKind::Serialization, "{}", url);
+    }
+
+    //the return URL is optional
+    let received = wallet
+        .parse_request(&format!("tesseract://request?payload={}", payload))
+        .unwrap();
+    assert_eq!(received.request, b"json{}");
+    assert_eq!(received.return_url, None);
+
+    //the payload is passed as is, even if it's not a marked message
+    let received = wallet.parse_request("tesseract://request?payload=e30").unwrap();
+    assert_eq!(received.request, b"{}");
+
+    let dapp = client::DeepLink::new("mydapp://tesseract").unwrap();
+    assert!(dapp
+        .parse_response("otherapp://tesseract?tesseract-response=anNvbnt9")
+        .is_err());
+    assert!(dapp.parse_response("mydapp://tesseract").is_err());
+    assert!(dapp
+        .parse_response("mydapp://tesseract?tesseract-response=anNvbnt9")
+        .is_ok());
+
+    assert!(client::DeepLink::new("mailto:dapp@example.com").is_err());
+}
@@ -0,0 +1,278 @@
+//===------------ encryption.rs --------------------------------------------===//
+//  Copyright 2021, Tesseract Systems, Inc.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//===----------------------------------------------------------------------===//
+
+mod common;
+
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use futures::executor::block_on;
+
+use tesseract_one::client::delegate::SingleTransportDelegate;
+use tesseract_one::client::{Connection, Transport};
+use tesseract_one::encryption::client::{EncryptedConnection, EncryptedTransport};
+use tesseract_one::encryption::{self, service, Identity, PublicKey};
+use tesseract_one::envelope::EnvelopeHeader;
+use tesseract_one::serialize::Serializer;
+use tesseract_one::transports::channel::{self, Channel};
+use tesseract_one::{client, ErrorKind};
+
+use tesseract_protocol_test::{Test, TestService};
+
+use common::TestWallet;
+
+type Link = Arc<dyn Connection + Sync + Send>;
+
+fn wallet(channel: &Arc<Channel>, identity: Identity) -> tesseract_one::service::Tesseract {
+    wallet_with(service::EncryptedTransport::new(
+        channel::service::ChannelTransport::new(channel),
+        identity,
+    ))
+}
+
+fn wallet_with(
+    transport: service::EncryptedTransport<channel::service::ChannelTransport>,
+) -> tesseract_one::service::Tesseract {
+    tesseract_one::service::Tesseract::new()
+        .transport(transport)
+        .service(TestWallet {})
+}
+
+fn dapp(transport: EncryptedTransport<channel::client::ChannelTransport>) -> client::Tesseract {
+    client::Tesseract::new(SingleTransportDelegate::arc()).transport(transport)
+}
+
+fn request(id: u64) -> Vec<u8> {
+    let request = serde_json::json!({
+        "version": 1,
+        "protocol": "test",
+        "method": "sign_transaction",
+        "id": id,
+        "request": { "transaction": format!("secret_tx{}", id) }
+    });
+
+    let mut marked = b"json".to_vec();
+    marked.extend(serde_json::to_vec(&request).unwrap());
+    marked
+}
+
+//records what goes over the link
+struct Tap {
+    link: Link,
+    sent: Arc<Mutex<Vec<Vec<u8>>>>,
+}
+
+#[async_trait]
+impl Connection for Tap {
+    async fn send(self: Arc<Self>, request: Vec<u8>) -> tesseract_one::Result<()> {
+        self.sent.lock().unwrap().push(request.clone());
+        Arc::clone(&self.link).send(request).await
+    }
+
+    async fn receive(self: Arc<Self>) -> tesseract_one::Result<Vec<u8>> {
+        Arc::clone(&self.link).receive().await
+    }
+}
+
+#[test]
+fn sign_over_encrypted_session() {
+    let channel = Arc::new(Channel::new());
+    let wallet_identity = Identity::generate().unwrap();
+    let wallet_key = wallet_identity.public_key();
+    let _wallet = wallet(&channel, wallet_identity);
+
+    let transport = EncryptedTransport::new(
+        channel::client::ChannelTransport::new(&channel),
+        Identity::generate().unwrap(),
+    )
+    .wallet(wallet_key);
+    let service = dapp(transport).service(Test::Protocol);
+
+    block_on(async {
+        let signed = Arc::clone(&service).sign_transaction("transaction").await;
+        assert_eq!("transaction_signed!", signed.unwrap());
+
+        let failed = Arc::clone(&service).sign_transaction("make_error").await;
+        assert_eq!(ErrorKind::Weird, failed.unwrap_err().kind);
+
+        let signed = service.sign_transaction("again").await;
+        assert_eq!("again_signed!", signed.unwrap());
+    });
+}
+
+#[test]
+fn rejects_unexpected_peers() {
+    let channel = Arc::new(Channel::new());
+    let dapp_identity = Identity::generate().unwrap();
+    let allowed = dapp_identity.public_key();
+    let _wallet = wallet_with(
+        service::EncryptedTransport::new(
+            channel::service::ChannelTransport::new(&channel),
+            Identity::generate().unwrap(),
+        )
+        .authorize(move |dapp| *dapp == allowed),
+    );
+
+    block_on(async {
+        //a plain dApp
+        let plain = client::Tesseract::new(SingleTransportDelegate::arc())
+            .transport(channel::client::ChannelTransport::new(&channel))
+            .service(Test::Protocol);
+        let failed = plain.sign_transaction("transaction").await;
+        assert_eq!(ErrorKind::Transport, failed.unwrap_err().kind);
+
+        //a dApp the wallet doesn't know
+        let stranger = dapp(EncryptedTransport::new(
+            channel::client::ChannelTransport::new(&channel),
+            Identity::generate().unwrap(),
+        ))
+        .service(Test::Protocol);
+        let failed = stranger.sign_transaction("transaction").await;
+        assert_eq!(ErrorKind::Transport, failed.unwrap_err().kind);
+
+        //a wallet the dApp doesn't expect
+        let impostor: PublicKey = "00".repeat(32).parse().unwrap();
+        let wary = dapp(
+            EncryptedTransport::new(
+                channel::client::ChannelTransport::new(&channel),
+                dapp_identity.clone(),
+            )
+            .wallet(impostor),
+        )
+        .service(Test::Protocol);
+        let failed = wary.sign_transaction("transaction").await;
+        assert_eq!(ErrorKind::Transport, failed.unwrap_err().kind);
+
+        let known = dapp(EncryptedTransport::new(
+            channel::client::ChannelTransport::new(&channel),
+            dapp_identity,
+        ))
+        .service(Test::Protocol);
+        let signed = known.sign_transaction("transaction").await;
+        assert_eq!("transaction_signed!", signed.unwrap());
+    });
+}
+
+#[test]
+fn messages_are_opaque_and_not_replayable() {
+    let channel = Arc::new(Channel::new());
+    let wallet_identity = Identity::generate().unwrap();
+    let wallet_key = wallet_identity.public_key();
+    let _wallet = wallet(&channel, wallet_identity);
+
+    let link: Link = channel::client::ChannelTransport::new(&channel)
+        .connect(Box::new(Test::Protocol))
+        .into();
+    let sent = Arc::new(Mutex::new(Vec::new()));
+    let tap = Tap {
+        link: Arc::clone(&link),
+        sent: Arc::clone(&sent),
+    };
+    let connection = Arc::new(EncryptedConnection::new(
+        Box::new(tap),
+        Identity::generate().unwrap(),
+        Some(wallet_key),
+    ));
+
+    block_on(async {
+        let info = connection.handshake().await.unwrap();
+        assert_eq!(info.peer, wallet_key);
+
+        for id in 1..=2 {
+            Arc::clone(&connection).send(request(id)).await.unwrap();
+            let response = Arc::clone(&connection).receive().await.unwrap();
+            let (header, _) = Serializer::deserialize_marked::<EnvelopeHeader>(&response).unwrap();
+            assert_eq!(header.id, Some(id));
+        }
+
+        let sent = sent.lock().unwrap().clone();
+        //the hello and the reveal
+        assert_eq!(sent.len(), 4);
+        assert!(encryption::is_handshake(&sent[0]));
+        assert!(encryption::is_handshake(&sent[1]));
+        for sealed in &sent[2..] {
+            assert!(encryption::is_encrypted(sealed));
+            let text = String::from_utf8_lossy(sealed);
+            assert!(!text.contains("secret_tx"));
+            assert!(!text.contains("sign_transaction"));
+        }
+
+        //the wallet doesn't process the same message twice
+        Arc::clone(&link).send(sent[2].clone()).await.unwrap();
+        let replayed = Arc::clone(&link).receive().await.unwrap();
+        assert!(!encryption::is_encrypted(&replayed));
+        assert!(String::from_utf8_lossy(&replayed).contains("replayed"));
+    });
+}
+
+#[test]
+fn hellos_dont_evict_active_sessions() {
+    let channel = Arc::new(Channel::new());
+    let wallet_identity = Identity::generate().unwrap();
+    let wallet_key = wallet_identity.public_key();
+    let _wallet = wallet_with(
+        service::EncryptedTransport::new(
+            channel::service::ChannelTransport::new(&channel),
+            wallet_identity,
+        )
+        .max_sessions(2)
+        .max_handshakes(4),
+    );
+
+    let session = || {
+        let link = channel::client::ChannelTransport::new(&channel).connect(Box::new(Test::Protocol));
+        Arc::new(EncryptedConnection::new(link, Identity::generate().unwrap(), Some(wallet_key)))
+    };
+    let sign = |connection: &Arc<EncryptedConnection>, id: u64| {
+        let connection = Arc::clone(connection);
+        async move {
+            Arc::clone(&connection).send(request(id)).await?;
+            connection.receive().await
+        }
+    };
+
+    block_on(async {
+        let active = session();
+        active.handshake().await.unwrap();
+        assert!(sign(&active, 1).await.is_ok());
+
+        //hellos that are never finished
+        let link: Link = channel::client::ChannelTransport::new(&channel)
+            .connect(Box::new(Test::Protocol))
+            .into();
+        for _ in 0..16 {
+            let hello = encryption::Hello {
+                ephemeral: Identity::generate().unwrap().public_key(),
+                identity: Identity::generate().unwrap().public_key(),
+                commitment: [0; 32],
+            };
+            Arc::clone(&link).send(hello.encode()).await.unwrap();
+            let welcome = Arc::clone(&link).receive().await.unwrap();
+            assert!(encryption::is_handshake(&welcome));
+        }
+        assert!(sign(&active, 2).await.is_ok());
+
+        //the least recently used session goes first
+        let idle = session();
+        idle.handshake().await.unwrap();
+        assert!(sign(&active, 3).await.is_ok());
+        let new = session();
+        new.handshake().await.unwrap();
+        assert!(sign(&active, 4).await.is_ok());
+        assert!(sign(&new, 5).await.is_ok());
+        assert!(!encryption::is_encrypted(&sign(&idle, 6).await.unwrap_or_default()));
+    });
+}
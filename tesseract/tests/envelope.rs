@@ -0,0 +1,201 @@
+//===------------ envelope.rs --------------------------------------------===//
+//  Copyright 2021, Tesseract Systems, Inc.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//===----------------------------------------------------------------------===//
+
+//Pins the v1 envelope format. Changing any of the golden values below means breaking the wire
+//compatibility with the wallets and dApps already out there.
+
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+use tesseract_one::envelope::{EnvelopeHeader, RequestEnvelope, ResponseEnvelope, VERSION};
+use tesseract_one::response::Response;
+use tesseract_one::serialize::Serializer;
+use tesseract_one::service::processor::Processor;
+use tesseract_one::service::TransportProcessor;
+use tesseract_one::{Error, ErrorKind};
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+struct SignTransactionRequest {
+    transaction: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+struct SignTransactionResponse {
+    signed: String,
+}
+
+const REQUEST_JSON: &str = r#"json{"version":1,"protocol":"test","method":"sign_transaction","id":1,"request":{"transaction":"tx"}}"#;
+const RESPONSE_JSON: &str = r#"json{"version":1,"id":1,"response":{"status":"ok","signed":"tx_signed!"}}"#;
+const ERROR_JSON: &str = r#"json{"version":1,"id":null,"response":{"status":"error","kind":"serialization","description":"bad"}}"#;
+
+const REQUEST_CBOR: &str = "63626f72a56776657273696f6e016870726f746f636f6c6474657374666d6574686f64707369676e5f7472616e73616374696f6e626964016772657175657374a16b7472616e73616374696f6e627478";
+const RESPONSE_CBOR: &str = "63626f72a36776657273696f6e016269640168726573706f6e7365a266737461747573626f6b667369676e65646a74785f7369676e656421";
+const ERROR_CBOR: &str = "63626f72a36776657273696f6e01626964f668726573706f6e7365a366737461747573656572726f72646b696e646d73657269616c697a6174696f6e6b6465736372697074696f6e63626164";
+
+fn hex(data: &str) -> Vec<u8> {
+    (0..data.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&data[i..i + 2], 16).unwrap())
+        .collect()
+}
+
+fn request() -> RequestEnvelope<SignTransactionRequest> {
+    RequestEnvelope {
+        version: VERSION,
+        protocol: "test".to_owned(),
+        method: "sign_transaction".to_owned(),
+        id: 1,
+        request: SignTransactionRequest {
+            transaction: "tx".to_owned(),
+        },
+    }
+}
+
+fn response() -> ResponseEnvelope<SignTransactionResponse> {
+    ResponseEnvelope {
+        version: VERSION,
+        id: Some(1),
+        response: Response::Ok(SignTransactionResponse {
+            signed: "tx_signed!".to_owned(),
+        }),
+    }
+}
+
+fn error() -> ResponseEnvelope<SignTransactionResponse> {
+    ResponseEnvelope {
+        version: VERSION,
+        id: None,
+        response: Response::Error(Error::described(ErrorKind::Serialization, "bad")),
+    }
+}
+
+#[test]
+fn v1_json_is_pinned() {
+    let serializer = Serializer::Json;
+
+    assert_eq!(REQUEST_JSON.as_bytes(), serializer.serialize(&request(), true).unwrap());
+    assert_eq!(RESPONSE_JSON.as_bytes(), serializer.serialize(&response(), true).unwrap());
+    assert_eq!(ERROR_JSON.as_bytes(), serializer.serialize(&error(), true).unwrap());
+}
+
+#[test]
+fn v1_cbor_is_pinned() {
+    let serializer = Serializer::Cbor;
+
+    assert_eq!(hex(REQUEST_CBOR), serializer.serialize(&request(), true).unwrap());
+    assert_eq!(hex(RESPONSE_CBOR), serializer.serialize(&response(), true).unwrap());
+    assert_eq!(hex(ERROR_CBOR), serializer.serialize(&error(), true).unwrap());
+}
+
+#[test]
+fn missing_version_is_v1() {
+    let legacy = r#"json{"protocol":"test","method":"sign_transaction","id":7,"request":{"transaction":"tx"}}"#;
+
+    let (envelope, _) = Serializer::deserialize_marked::<RequestEnvelope<SignTransactionRequest>>(
+        legacy.as_bytes(),
+    )
+    .unwrap();
+
+    assert_eq!(1, envelope.version);
+    assert_eq!(7, envelope.id);
+}
+
+#[test]
+fn unknown_fields_are_ignored() {
+    let request = r#"json{"version":1,"protocol":"test","method":"sign_transaction","id":1,"request":{"transaction":"tx","fee":5},"trace":"abc"}"#;
+    let response = r#"json{"version":1,"id":1,"response":{"status":"ok","signed":"tx_signed!","extra":[1,2]},"trace":"abc"}"#;
+
+    let (request, _) = Serializer::deserialize_marked::<RequestEnvelope<SignTransactionRequest>>(
+        request.as_bytes(),
+    )
+    .unwrap();
+    let (response, _) =
+        Serializer::deserialize_marked::<ResponseEnvelope<SignTransactionResponse>>(
+            response.as_bytes(),
+        )
+        .unwrap();
+
+    assert_eq!("tx", request.request.transaction);
+    assert_eq!("tx_signed!", response.response.into_result().unwrap().signed);
+}
+
+#[test]
+fn unknown_status_is_an_error() {
+    let response = r#"json{"version":1,"id":1,"response":{"status":"pending","eta":10}}"#;
+
+    let (response, _) =
+        Serializer::deserialize_marked::<ResponseEnvelope<SignTransactionResponse>>(
+            response.as_bytes(),
+        )
+        .unwrap();
+
+    assert_eq!(
+        ErrorKind::Serialization,
+        response.response.into_result().unwrap_err().kind
+    );
+}
+
+#[test]
+fn unknown_error_kind_is_decoded() {
+    let json = r#"json{"version":1,"id":1,"response":{"status":"error","kind":"rate-limited","description":"slow down"}}"#;
+    let cbor = Serializer::Cbor
+        .serialize(
+            &serde_json::json!({
+                "version": 1,
+                "id": 1,
+                "response": {"status": "error", "kind": "rate-limited", "description": "slow down"}
+            }),
+            true,
+        )
+        .unwrap();
+
+    for response in [json.as_bytes(), &cbor] {
+        let (response, _) =
+            Serializer::deserialize_marked::<ResponseEnvelope<SignTransactionResponse>>(response)
+                .unwrap();
+
+        let error = response.response.into_result().unwrap_err();
+        assert_eq!(ErrorKind::Unknown, error.kind);
+        assert_eq!(Some("slow down".to_owned()), error.description);
+    }
+}
+
+#[test]
+fn unsupported_version_is_rejected() {
+    let header = r#"json{"version":2,"id":3,"whatever":{}}"#;
+    let (header, _) = Serializer::deserialize_marked::<EnvelopeHeader>(header.as_bytes()).unwrap();
+
+    assert_eq!(ErrorKind::UnsupportedVersion, header.check().unwrap_err().kind);
+}
+
+#[test]
+fn processor_replies_to_unsupported_version() {
+    let request = r#"json{"version":2,"protocol":"test","method":"sign_transaction","id":3,"request":{}}"#;
+
+    let processor = Arc::new(Processor::new());
+    let response = futures::executor::block_on(processor.process(request.as_bytes()));
+
+    let (response, _) =
+        Serializer::deserialize_marked::<ResponseEnvelope<SignTransactionResponse>>(&response)
+            .unwrap();
+
+    assert_eq!(Some(3), response.id);
+    assert_eq!(
+        ErrorKind::UnsupportedVersion,
+        response.response.into_result().unwrap_err().kind
+    );
+}
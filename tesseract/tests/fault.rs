@@ -0,0 +1,188 @@
+//===------------ fault.rs -------------------------------------------===//
+//  Copyright 2021, Tesseract Systems, Inc.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//===----------------------------------------------------------------------===//
+
+mod common;
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use futures::executor::block_on;
+
+use tesseract_one::client::transport::Status;
+use tesseract_one::client::{Connection, Transport};
+use tesseract_one::envelope::EnvelopeHeader;
+use tesseract_one::fault::client::FaultyTransport;
+use tesseract_one::fault::Faults;
+use tesseract_one::serialize::Serializer;
+use tesseract_one::{service, ErrorKind};
+use tesseract_one::transports::channel::{self, Channel};
+
+use tesseract_protocol_test::Test;
+
+use common::TestWallet;
+
+type Link = Arc<dyn Connection + Sync + Send>;
+
+fn wallet() -> (service::Tesseract, Arc<Channel>) {
+    let channel = Arc::new(Channel::new());
+
+    let tesseract = service::Tesseract::new()
+        .transport(channel::service::ChannelTransport::new(&channel))
+        .service(TestWallet {});
+
+    (tesseract, channel)
+}
+
+fn faulty(channel: &Arc<Channel>, faults: Faults) -> Arc<FaultyTransport<channel::client::ChannelTransport>> {
+    Arc::new(FaultyTransport::new(channel::client::ChannelTransport::new(channel), faults))
+}
+
+fn connect(channel: &Arc<Channel>, faults: Faults) -> Link {
+    faulty(channel, faults).connect(Box::new(Test::Protocol)).into()
+}
+
+fn request(id: u64) -> Vec<u8> {
+    let request = serde_json::json!({
+        "version": 1,
+        "protocol": "test",
+        "method": "sign_transaction",
+        "id": id,
+        "request": { "transaction": format!("tx{}", id) }
+    });
+
+    let mut marked = b"json".to_vec();
+    marked.extend(serde_json::to_vec(&request).unwrap());
+    marked
+}
+
+async fn send(link: &Link, ids: &[u64]) {
+    for id in ids {
+        Arc::clone(link).send(request(*id)).await.unwrap();
+    }
+}
+
+async fn receive_id(link: &Link) -> Option<u64> {
+    let response = Arc::clone(link).receive().await.unwrap();
+    let (header, _) = Serializer::deserialize_marked::<EnvelopeHeader>(&response).unwrap();
+    header.id
+}
+
+#[test]
+fn drops_requests() {
+    let (_wallet, channel) = wallet();
+    let timeout = Duration::from_millis(50);
+    let link = connect(&channel, Faults::new(1).drop(1.0).drop_timeout(timeout));
+
+    block_on(send(&link, &[1, 2]));
+
+    //lost after the timeout, not hung: the next receive goes on
+    let started = Instant::now();
+    for _ in 0..2 {
+        let failed = block_on(Arc::clone(&link).receive());
+        assert_eq!(ErrorKind::Transport, failed.unwrap_err().kind);
+    }
+    assert!(started.elapsed() >= timeout);
+}
+
+#[test]
+fn duplicates_and_reorders_responses() {
+    let (_wallet, channel) = wallet();
+
+    let link = connect(&channel, Faults::new(1).duplicate(1.0));
+    block_on(async {
+        send(&link, &[1, 2]).await;
+        assert_eq!(Some(1), receive_id(&link).await);
+        assert_eq!(Some(1), receive_id(&link).await);
+        assert_eq!(Some(2), receive_id(&link).await);
+    });
+
+    let link = connect(&channel, Faults::new(1).reorder(1.0));
+    block_on(async {
+        send(&link, &[1, 2, 3]).await;
+        assert_eq!(Some(2), receive_id(&link).await);
+        assert_eq!(Some(1), receive_id(&link).await);
+        //nothing to swap the last one with
+        assert_eq!(Some(3), receive_id(&link).await);
+    });
+}
+
+#[test]
+fn corrupts_responses() {
+    let (_wallet, channel) = wallet();
+    let clean = connect(&channel, Faults::default());
+    let corrupt = connect(&channel, Faults::new(1).corrupt(1.0));
+
+    block_on(async {
+        send(&clean, &[1]).await;
+        send(&corrupt, &[1]).await;
+
+        let clean = Arc::clone(&clean).receive().await.unwrap();
+        let corrupt = Arc::clone(&corrupt).receive().await.unwrap();
+
+        let flipped: u32 = clean.iter().zip(&corrupt).map(|(a, b)| (a ^ b).count_ones()).sum();
+        assert_eq!(1, flipped);
+    });
+}
+
+#[test]
+fn flips_status() {
+    let (_wallet, channel) = wallet();
+
+    block_on(async {
+        let status = faulty(&channel, Faults::new(1).unavailable(1.0))
+            .status(Box::new(Test::Protocol))
+            .await;
+        assert!(matches!(status, Status::Unavailable(_)));
+
+        let status = faulty(&channel, Faults::new(1)).status(Box::new(Test::Protocol)).await;
+        assert_eq!(Status::Ready, status);
+    });
+}
+
+#[test]
+fn adds_latency() {
+    let (_wallet, channel) = wallet();
+    let faults = Faults::new(1).latency(Duration::from_millis(20)..Duration::from_millis(30));
+    let link = connect(&channel, faults);
+
+    let start = Instant::now();
+    block_on(async {
+        send(&link, &[1]).await;
+        receive_id(&link).await;
+    });
+    assert!(start.elapsed() >= Duration::from_millis(40));
+}
+
+#[test]
+fn same_seed_same_faults() {
+    let (_wallet, channel) = wallet();
+
+    let run = |seed: u64| {
+        let link = connect(&channel, Faults::new(seed).corrupt(0.5).duplicate(0.3));
+
+        block_on(async {
+            let mut responses = Vec::new();
+            for id in 1..=20 {
+                send(&link, &[id]).await;
+                responses.push(Arc::clone(&link).receive().await.unwrap());
+            }
+            responses
+        })
+    };
+
+    assert_eq!(run(42), run(42));
+    assert_ne!(run(42), run(43));
+}
@@ -0,0 +1,274 @@
+//===------------ file.rs -------------------------------------------===//
+//  Copyright 2021, Tesseract Systems, Inc.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//===----------------------------------------------------------------------===//
+
+mod common;
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use futures::executor::block_on;
+
+use tesseract_one::client::delegate::SingleTransportDelegate;
+use tesseract_one::client::transport::Status;
+use tesseract_one::client::{Connection, Transport};
+use tesseract_one::service::{Transport as _, TransportProcessor};
+use tesseract_one::transports::file::{self, decode, encode};
+use tesseract_one::{client, service, ErrorKind};
+
+use tesseract_protocol_test::{Test, TestService};
+
+use common::TestWallet;
+
+const POLL: Duration = Duration::from_millis(10);
+
+//a fresh directory
+fn directory(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("tesseract-file-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&path);
+    fs::create_dir_all(&path).unwrap();
+    path
+}
+
+fn files(directory: &Path, extension: &str) -> Vec<PathBuf> {
+    fs::read_dir(directory)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|e| e == extension))
+        .collect()
+}
+
+fn request(id: u64) -> Vec<u8> {
+    let request = serde_json::json!({
+        "version": 1,
+        "protocol": "test",
+        "method": "sign_transaction",
+        "id": id,
+        "request": { "transaction": "transaction" }
+    });
+
+    let mut marked = b"json".to_vec();
+    marked.extend(serde_json::to_vec(&request).unwrap());
+    marked
+}
+
+#[test]
+fn sign_through_files() {
+    //the stick is moved instantly: the dApp's outbox is the wallet's inbox and vice versa
+    let requests = directory("requests");
+    let responses = directory("responses");
+
+    let _wallet = service::Tesseract::new()
+        .service(TestWallet {})
+        .transport(file::service::FileTransport::new(&requests, &responses).poll_interval(POLL));
+
+    let transport = file::client::FileTransport::new(&requests, &responses).poll_interval(POLL);
+    let service = client::Tesseract::new(SingleTransportDelegate::arc())
+        .transport(transport)
+        .service(Test::Protocol);
+
+    block_on(async {
+        let signed = Arc::clone(&service).sign_transaction("transaction").await;
+        assert_eq!("transaction_signed!", signed.unwrap());
+
+        let failed = service.sign_transaction("make_error").await;
+        assert_eq!(ErrorKind::Weird, failed.unwrap_err().kind);
+    });
+
+    //everything has been consumed
+    assert!(files(&requests, "request").is_empty());
+    assert!(files(&responses, "response").is_empty());
+}
+
+#[test]
+fn rejects_corrupted_responses() {
+    let outbox = directory("corrupted-outbox");
+    let inbox = directory("corrupted-inbox");
+
+    let transport = file::client::FileTransport::new(&outbox, &inbox).poll_interval(POLL);
+    let connection: Arc<dyn Connection + Sync + Send> = transport.connect(Box::new(Test::Protocol)).into();
+
+    block_on(Arc::clone(&connection).send(request(7))).unwrap();
+
+    let written = files(&outbox, "request");
+    assert_eq!(1, written.len());
+    let name = written[0].file_name().unwrap().to_str().unwrap();
+    assert!(name.ends_with("-7.request"));
+
+    let mut response = encode(b"json{}");
+    *response.last_mut().unwrap() = b']';
+    fs::write(inbox.join(name.replace(".request", ".response")), response).unwrap();
+
+    let failed = block_on(connection.receive());
+    assert_eq!(ErrorKind::Transport, failed.unwrap_err().kind);
+}
+
+#[test]
+fn sets_aside_corrupted_requests() {
+    let inbox = directory("aside-inbox");
+    let outbox = directory("aside-outbox");
+
+    let mut corrupted = encode(&request(1));
+    corrupted[80] ^= 1;
+    fs::write(inbox.join("session-1.request"), corrupted).unwrap();
+    fs::write(inbox.join("session-2.request"), encode(&request(2))).unwrap();
+
+    let wallet = service::Tesseract::new()
+        .service(TestWallet {})
+        .transport(file::service::FileTransport::new(&inbox, &outbox).poll_interval(POLL));
+
+    let response = outbox.join("session-2.response");
+    while !response.exists() {
+        std::thread::sleep(POLL);
+    }
+    drop(wallet);
+
+    let response = decode(&fs::read(&response).unwrap()).unwrap();
+    assert!(String::from_utf8(response).unwrap().contains("transaction_signed!"));
+
+    assert!(inbox.join("session-1.corrupted").exists());
+    assert_eq!(1, files(&outbox, "response").len());
+}
+
+#[test]
+fn names_opaque_requests() {
+    let outbox = directory("opaque-outbox");
+    let inbox = directory("opaque-inbox");
+
+    let transport = file::client::FileTransport::new(&outbox, &inbox);
+    let connection: Arc<dyn Connection + Sync + Send> = transport.connect(Box::new(Test::Protocol)).into();
+
+    //i.e. compressed or encrypted, there is no envelope to read the id from
+    for request in [b"zstd\x28\xb5".to_vec(), b"\x00\x01\x02".to_vec()] {
+        block_on(Arc::clone(&connection).send(request)).unwrap();
+    }
+
+    let mut names: Vec<String> = files(&outbox, "request")
+        .iter()
+        .map(|path| path.file_name().unwrap().to_str().unwrap().to_owned())
+        .collect();
+    names.sort();
+    assert_eq!(2, names.len());
+    assert!(names[0].ends_with("-1.request"));
+    assert!(names[1].ends_with("-2.request"));
+    assert_eq!(names[0].trim_end_matches("-1.request"), names[1].trim_end_matches("-2.request"));
+}
+
+#[test]
+fn names_requests_by_id() {
+    let outbox = directory("id-outbox");
+    let inbox = directory("id-inbox");
+
+    let transport = file::client::FileTransport::new(&outbox, &inbox);
+    let connection: Arc<dyn Connection + Sync + Send> = transport.connect(Box::new(Test::Protocol)).into();
+
+    //the opaque one goes after the last id
+    for request in [request(41), b"zstd\x28\xb5".to_vec(), request(43)] {
+        block_on(Arc::clone(&connection).send(request)).unwrap();
+    }
+
+    let mut names: Vec<String> = files(&outbox, "request")
+        .iter()
+        .map(|path| path.file_name().unwrap().to_str().unwrap().to_owned())
+        .collect();
+    names.sort();
+    assert_eq!(3, names.len());
+    assert!(names[0].ends_with("-41.request"));
+    assert!(names[1].ends_with("-42.request"));
+    assert!(names[2].ends_with("-43.request"));
+}
+
+//remembers the requests in the order they were processed
+#[derive(Default)]
+struct Recorder {
+    processed: Mutex<Vec<Vec<u8>>>,
+}
+
+#[async_trait]
+impl TransportProcessor for Recorder {
+    async fn process(self: Arc<Self>, data: &[u8]) -> Vec<u8> {
+        self.processed.lock().unwrap().push(data.to_vec());
+        data.to_vec()
+    }
+}
+
+#[test]
+fn processes_requests_in_numeric_order() {
+    let inbox = directory("order-inbox");
+    let outbox = directory("order-outbox");
+
+    for number in [10, 9, 2, 1] {
+        fs::write(
+            inbox.join(format!("session-{}.request", number)),
+            encode(number.to_string().as_bytes()),
+        )
+        .unwrap();
+    }
+
+    let recorder = Arc::new(Recorder::default());
+    let bound = file::service::FileTransport::new(&inbox, &outbox)
+        .poll_interval(POLL)
+        .bind(Arc::clone(&recorder) as Arc<dyn TransportProcessor + Send + Sync>);
+
+    while files(&outbox, "response").len() < 4 {
+        std::thread::sleep(POLL);
+    }
+    drop(bound);
+
+    let processed = recorder.processed.lock().unwrap().clone();
+    let expected: Vec<Vec<u8>> = ["1", "2", "9", "10"].iter().map(|n| n.as_bytes().to_vec()).collect();
+    assert_eq!(expected, processed);
+}
+
+#[test]
+fn times_out_and_reports_status() {
+    let outbox = directory("timeout-outbox");
+    let inbox = directory("timeout-inbox");
+
+    let transport = Arc::new(
+        file::client::FileTransport::new(&outbox, &inbox)
+            .poll_interval(POLL)
+            .timeout(Duration::from_millis(50)),
+    );
+    let connection: Arc<dyn Connection + Sync + Send> = transport.connect(Box::new(Test::Protocol)).into();
+
+    block_on(async {
+        Arc::clone(&connection).send(request(1)).await.unwrap();
+        let failed = connection.receive().await;
+        assert_eq!(ErrorKind::Transport, failed.unwrap_err().kind);
+
+        assert_eq!(Status::Ready, Arc::clone(&transport).status(Box::new(Test::Protocol)).await);
+
+        let missing = Arc::new(file::client::FileTransport::new(outbox.join("missing"), &inbox));
+        let status = missing.status(Box::new(Test::Protocol)).await;
+        assert!(matches!(status, Status::Unavailable(_)));
+    });
+}
+
+#[test]
+fn checksums() {
+    let file = encode(b"json{}");
+    assert!(file.starts_with(b"sha256:"));
+    assert_eq!(b"json{}".to_vec(), decode(&file).unwrap());
+
+    let mut tampered = file.clone();
+    *tampered.last_mut().unwrap() ^= 1;
+    assert!(decode(&tampered).is_err());
+
+    assert!(decode(b"json{}").is_err());
+}
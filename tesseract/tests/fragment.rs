@@ -0,0 +1,170 @@
+//===------------ fragment.rs --------------------------------------------===//
+//  Copyright 2021, Tesseract Systems, Inc.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//===----------------------------------------------------------------------===//
+
+use std::time::{Duration, Instant};
+
+use tesseract_one::fragment::{self, Fragment, Reassembler, HEADER_LEN};
+use tesseract_one::serialize::Serializer;
+
+fn message() -> Vec<u8> {
+    let request = serde_json::json!({
+        "protocol": "substrate-v1",
+        "method": "sign_transaction",
+        "id": 1,
+        "request": { "transaction": "ab".repeat(500) }
+    });
+    Serializer::Json.serialize(&request, true).unwrap()
+}
+
+#[test]
+fn test_reassembles_in_any_order() {
+    let message = message();
+    let fragments = fragment::split(&message, 7, 100).unwrap();
+
+    assert!(fragments.len() > 10);
+    assert!(fragments.iter().all(|f| f.encode().len() <= 100));
+
+    let mut reassembler = Reassembler::default();
+    let mut encoded: Vec<Vec<u8>> = fragments.iter().map(Fragment::encode).collect();
+    encoded.reverse();
+    encoded.swap(1, 5);
+
+    let (last, rest) = encoded.split_last().unwrap();
+    for data in rest {
+        assert!(Fragment::is_fragment(data));
+        assert_eq!(reassembler.push_encoded(data).unwrap(), None);
+    }
+    //repeated fragments are ignored
+    assert_eq!(reassembler.push_encoded(&rest[0]).unwrap(), None);
+
+    assert_eq!(reassembler.push_encoded(last).unwrap(), Some(message));
+    assert!(reassembler.pending().is_empty());
+}
+
+#[test]
+fn test_missing_and_expired() {
+    let message = message();
+    let fragments = fragment::split(&message, 1, 64).unwrap();
+    let count = fragments.len() as u16;
+
+    let start = Instant::now();
+    let mut reassembler = Reassembler::new(Duration::from_secs(10));
+    for fragment in fragments.into_iter().filter(|f| f.index % 3 != 0) {
+        assert_eq!(reassembler.push_at(fragment, start).unwrap(), None);
+    }
+
+    let expected: Vec<u16> = (0..count).filter(|i| i % 3 == 0).collect();
+    let mut missing = reassembler.missing(1).unwrap();
+    missing.sort();
+    assert_eq!(missing, expected);
+    assert_eq!(reassembler.missing(2), None);
+
+    assert!(reassembler
+        .expire_at(start + Duration::from_secs(5))
+        .is_empty());
+    assert_eq!(
+        reassembler.expire_at(start + Duration::from_secs(10)),
+        vec![1]
+    );
+    assert_eq!(reassembler.missing(1), None);
+}
+
+#[test]
+fn test_rejects_corrupted() {
+    let message = message();
+    let mut fragments = fragment::split(&message, 3, 200).unwrap();
+    fragments[2].payload[10] ^= 0x01;
+
+    let mut reassembler = Reassembler::default();
+    let results: Vec<_> = fragments.into_iter().map(|f| reassembler.push(f)).collect();
+    assert!(results.last().unwrap().is_err());
+
+    //a fragment of another message with the same id
+    let mut reassembler = Reassembler::default();
+    let first = fragment::split(&message, 4, 200).unwrap();
+    let other = fragment::split(b"another message", 4, 200).unwrap();
+    reassembler.push(first[0].clone()).unwrap();
+    assert!(reassembler.push(other[0].clone()).is_err());
+
+    assert!(Fragment::decode(b"json{}").is_err());
+    assert!(Fragment::decode(&first[0].encode()[..HEADER_LEN - 1]).is_err());
+    assert!(fragment::split(&message, 4, HEADER_LEN).is_err());
+
+    //an empty message still takes a fragment
+    let empty = fragment::split(&[], 5, 32).unwrap();
+    assert_eq!(empty.len(), 1);
+    assert_eq!(
+        Reassembler::default().push(empty[0].clone()).unwrap(),
+        Some(vec![])
+    );
+}
+
+#[test]
+fn test_expires_while_pushing() {
+    let start = Instant::now();
+    let mut reassembler = Reassembler::new(Duration::from_secs(10));
+
+    let first = fragment::split(&message(), 1, 64).unwrap();
+    let second = fragment::split(&message(), 2, 64).unwrap();
+    reassembler.push_at(first[0].clone(), start).unwrap();
+    reassembler
+        .push_at(second[0].clone(), start + Duration::from_secs(10))
+        .unwrap();
+
+    assert_eq!(reassembler.pending(), vec![2]);
+}
+
+#[test]
+fn test_limits_partial_messages() {
+    let start = Instant::now();
+    let at = |seconds: u64| start + Duration::from_secs(seconds);
+
+    //the oldest message makes room for a new one
+    let mut reassembler = Reassembler::default().max_messages(2);
+    for id in 1..=3 {
+        let fragments = fragment::split(&message(), id, 64).unwrap();
+        reassembler
+            .push_at(fragments[0].clone(), at(id as u64))
+            .unwrap();
+    }
+    let mut pending = reassembler.pending();
+    pending.sort();
+    assert_eq!(pending, vec![2, 3]);
+
+    //and for its bytes
+    let small = vec![7u8; 120];
+    let mut reassembler = Reassembler::default().max_bytes(150);
+    let first = fragment::split(&small, 1, 64).unwrap();
+    let second = fragment::split(&small, 2, 64).unwrap();
+    reassembler.push_at(first[0].clone(), at(1)).unwrap();
+
+    let mut result = None;
+    for fragment in second {
+        result = reassembler.push_at(fragment, at(2)).unwrap();
+    }
+    assert_eq!(result, Some(small));
+    assert!(reassembler.pending().is_empty());
+
+    //a message that can't fit at all
+    let mut reassembler = Reassembler::default().max_bytes(150);
+    let results: Vec<_> = fragment::split(&message(), 1, 64)
+        .unwrap()
+        .into_iter()
+        .map(|f| reassembler.push_at(f, at(1)))
+        .collect();
+    assert!(results.iter().any(|r| r.is_err()));
+    assert!(results.iter().all(|r| !matches!(r, Ok(Some(_)))));
+}
@@ -0,0 +1,218 @@
+//===------------ http.rs -------------------------------------------===//
+//  Copyright 2021, Tesseract Systems, Inc.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//===----------------------------------------------------------------------===//
+
+mod common;
+#[path = "../../vectors/support.rs"]
+mod support;
+
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use http_body_util::{BodyExt, Full};
+use hyper::body::Bytes;
+use hyper::{Method, Request, StatusCode};
+use hyper_util::client::legacy::Client;
+use hyper_util::rt::TokioExecutor;
+
+use tesseract_one::client::delegate::SingleTransportDelegate;
+use tesseract_one::client::transport::Status;
+use tesseract_one::client::Transport;
+use tesseract_one::transports::http;
+use tesseract_one::transports::http::DEFAULT_PATH;
+use tesseract_one::{client, service, ErrorKind};
+
+use tesseract_protocol_test::{Test, TestService};
+
+use common::TestWallet;
+
+const TOKEN: &str = "Bearer backend-token";
+
+fn vector(name: &str) -> Vec<u8> {
+    support::vector("test", name)
+}
+
+fn wallet() -> (service::Tesseract, SocketAddr) {
+    let transport = http::service::HttpTransport::listen("127.0.0.1:0")
+        .unwrap()
+        .path("/signer")
+        .max_body_len(1024)
+        .auth(|authorization| authorization == Some(TOKEN));
+    let address = transport.local_addr().unwrap();
+
+    let tesseract = service::Tesseract::new()
+        .transport(transport)
+        .service(TestWallet {});
+
+    (tesseract, address)
+}
+
+fn backend(transport: http::client::HttpTransport) -> client::Tesseract {
+    client::Tesseract::new(SingleTransportDelegate::arc()).transport(transport)
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn sign_with_post() {
+    let (_wallet, address) = wallet();
+    let transport = http::client::HttpTransport::new(&format!("http://{}/signer", address))
+        .authorization(|| Some(TOKEN.to_owned()));
+    let service = backend(transport).service(Test::Protocol);
+
+    let signed = Arc::clone(&service).sign_transaction("transaction").await;
+    assert_eq!("transaction_signed!", signed.unwrap());
+
+    let failed = Arc::clone(&service).sign_transaction("make_error").await;
+    assert_eq!(ErrorKind::Weird, failed.unwrap_err().kind);
+
+    //over the limit of the wallet
+    let failed = Arc::clone(&service).sign_transaction(&"x".repeat(2048)).await;
+    assert_eq!(ErrorKind::Transport, failed.unwrap_err().kind);
+
+    let signed = service.sign_transaction("again").await;
+    assert_eq!("again_signed!", signed.unwrap());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn rejects_unauthorized() {
+    let (_wallet, address) = wallet();
+
+    let url = format!("http://{}/signer", address);
+    for transport in [
+        http::client::HttpTransport::new(&url),
+        http::client::HttpTransport::new(&url).authorization(|| Some("Bearer wrong".to_owned())),
+    ] {
+        let failed = backend(transport).service(Test::Protocol).sign_transaction("transaction").await;
+        assert_eq!(ErrorKind::Transport, failed.unwrap_err().kind);
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn plain_http() {
+    let (_wallet, address) = wallet();
+    let client = Client::builder(TokioExecutor::new()).build_http::<Full<Bytes>>();
+
+    let post = |path: &str, body: Vec<u8>| {
+        Request::builder()
+            .method(Method::POST)
+            .uri(format!("http://{}{}", address, path))
+            .header("Authorization", TOKEN)
+            .body(Full::new(Bytes::from(body)))
+            .unwrap()
+    };
+
+    let response = client
+        .request(post("/signer", vector("sign_transaction.request.json")))
+        .await
+        .unwrap();
+    assert_eq!(StatusCode::OK, response.status());
+    assert_eq!("application/json", response.headers()["Content-Type"]);
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    assert_eq!(vector("sign_transaction.response.json"), body.to_vec());
+
+    let response = client
+        .request(post("/signer", vector("sign_transaction.request.cbor")))
+        .await
+        .unwrap();
+    assert_eq!("application/cbor", response.headers()["Content-Type"]);
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    assert_eq!(vector("sign_transaction.response.cbor"), body.to_vec());
+
+    let response = client.request(post("/other", Vec::new())).await.unwrap();
+    assert_eq!(StatusCode::NOT_FOUND, response.status());
+
+    let get = Request::builder()
+        .uri(format!("http://{}/signer", address))
+        .body(Full::default())
+        .unwrap();
+    let response = client.request(get).await.unwrap();
+    assert_eq!(StatusCode::METHOD_NOT_ALLOWED, response.status());
+}
+
+//what a website the user visits could do: a cross-origin POST to the signer on the loopback
+#[tokio::test(flavor = "multi_thread")]
+async fn checks_origins() {
+    let client = Client::builder(TokioExecutor::new()).build_http::<Full<Bytes>>();
+
+    let post = |address: SocketAddr, origin: &str| {
+        Request::builder()
+            .method(Method::POST)
+            .uri(format!("http://{}{}", address, DEFAULT_PATH))
+            .header("Origin", origin)
+            .body(Full::new(Bytes::from(vector("sign_transaction.request.json"))))
+            .unwrap()
+    };
+
+    let transport = http::service::HttpTransport::listen("127.0.0.1:0").unwrap();
+    let address = transport.local_addr().unwrap();
+    let _wallet = service::Tesseract::new()
+        .transport(transport)
+        .service(TestWallet {});
+
+    let response = client.request(post(address, "https://evil.example")).await.unwrap();
+    assert_eq!(StatusCode::FORBIDDEN, response.status());
+
+    let transport = http::service::HttpTransport::listen("127.0.0.1:0")
+        .unwrap()
+        .allow_origins(["https://backend.example"]);
+    let address = transport.local_addr().unwrap();
+    let _wallet = service::Tesseract::new()
+        .transport(transport)
+        .service(TestWallet {});
+
+    let response = client.request(post(address, "https://evil.example")).await.unwrap();
+    assert_eq!(StatusCode::FORBIDDEN, response.status());
+
+    //the preflight of a browser posting JSON with a token
+    let preflight = |origin: &str| {
+        Request::builder()
+            .method(Method::OPTIONS)
+            .uri(format!("http://{}{}", address, DEFAULT_PATH))
+            .header("Origin", origin)
+            .header("Access-Control-Request-Method", "POST")
+            .header("Access-Control-Request-Headers", "authorization, content-type")
+            .body(Full::default())
+            .unwrap()
+    };
+
+    let response = client.request(preflight("https://evil.example")).await.unwrap();
+    assert_eq!(StatusCode::FORBIDDEN, response.status());
+    assert!(response.headers().get("Access-Control-Allow-Origin").is_none());
+
+    let response = client.request(preflight("https://backend.example")).await.unwrap();
+    assert!(response.status().is_success());
+    let headers = response.headers();
+    assert_eq!("https://backend.example", headers["Access-Control-Allow-Origin"]);
+    assert_eq!("POST", headers["Access-Control-Allow-Methods"]);
+    let allowed = headers["Access-Control-Allow-Headers"].to_str().unwrap().to_lowercase();
+    assert!(allowed.contains("authorization") && allowed.contains("content-type"));
+
+    let response = client.request(post(address, "https://backend.example")).await.unwrap();
+    assert_eq!(StatusCode::OK, response.status());
+    assert_eq!("https://backend.example", response.headers()["Access-Control-Allow-Origin"]);
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    assert_eq!(vector("sign_transaction.response.json"), body.to_vec());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn unavailable_without_wallet() {
+    let address = {
+        let (_wallet, address) = wallet();
+        address
+    };
+
+    let transport = Arc::new(http::client::HttpTransport::new(&format!("http://{}", address)));
+    let status = transport.status(Box::new(Test::Protocol)).await;
+    assert!(matches!(status, Status::Unavailable(_)));
+}
@@ -0,0 +1,235 @@
+//===------------ keepalive.rs --------------------------------------------===//
+//  Copyright 2021, Tesseract Systems, Inc.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//===----------------------------------------------------------------------===//
+
+mod common;
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use futures::channel::mpsc;
+use futures::executor::block_on;
+use futures::future::{self, Either};
+use futures::{FutureExt, StreamExt};
+use futures_timer::Delay;
+
+use tesseract_one::client::transport::Status;
+use tesseract_one::client::{Connection, ConnectionState, Delegate, Lifecycle, Transport};
+use tesseract_one::keepalive::{self, KeepAlive};
+use tesseract_one::service::{BoundTransport, TransportProcessor};
+use tesseract_one::transports::channel::{self, Channel};
+use tesseract_one::{client, service, ErrorKind};
+
+use tesseract_protocol_test::{Test, TestService};
+
+use common::TestWallet;
+
+#[derive(Default)]
+struct CountingDelegate {
+    asked: AtomicUsize,
+}
+
+#[async_trait]
+impl Delegate for CountingDelegate {
+    async fn select_transport(&self, transports: &HashMap<String, Status>) -> Option<String> {
+        self.asked.fetch_add(1, Ordering::SeqCst);
+        transports.keys().next().cloned()
+    }
+}
+
+//a wallet that stops answering when it hangs and takes its time to sign, reports the pings it gets
+#[derive(Default, Clone)]
+struct Behavior {
+    hung: Arc<AtomicBool>,
+    signing: Duration,
+    pings: Option<mpsc::UnboundedSender<()>>,
+}
+
+struct SlowProcessor {
+    processor: Arc<dyn TransportProcessor + Send + Sync>,
+    behavior: Behavior,
+}
+
+#[async_trait]
+impl TransportProcessor for SlowProcessor {
+    async fn process(self: Arc<Self>, data: &[u8]) -> Vec<u8> {
+        if self.behavior.hung.load(Ordering::SeqCst) {
+            futures::future::pending::<()>().await;
+        }
+        if !keepalive::is_ping(data) {
+            Delay::new(self.behavior.signing).await;
+        } else if let Some(pings) = &self.behavior.pings {
+            let _ = pings.unbounded_send(());
+        }
+        Arc::clone(&self.processor).process(data).await
+    }
+}
+
+struct SlowTransport {
+    transport: channel::service::ChannelTransport,
+    behavior: Behavior,
+}
+
+impl service::Transport for SlowTransport {
+    fn bind(
+        self,
+        processor: Arc<dyn TransportProcessor + Send + Sync>,
+    ) -> Box<dyn BoundTransport + Send> {
+        self.transport.bind(Arc::new(SlowProcessor {
+            processor,
+            behavior: self.behavior,
+        }))
+    }
+}
+
+fn wallet(channel: &Arc<Channel>, behavior: &Behavior) -> service::Tesseract {
+    service::Tesseract::new()
+        .transport(SlowTransport {
+            transport: channel::service::ChannelTransport::new(channel),
+            behavior: behavior.clone(),
+        })
+        .service(TestWallet {})
+}
+
+fn dapp(channel: &Arc<Channel>, delegate: &Arc<CountingDelegate>) -> client::Tesseract {
+    let keepalive = KeepAlive::new(Duration::from_millis(20), Duration::from_millis(100)).unwrap();
+
+    client::Tesseract::new(Arc::clone(delegate) as Arc<dyn Delegate + Send + Sync>)
+        .transport(channel::client::ChannelTransport::new(channel))
+        .keepalive(keepalive, |task| {
+            thread::spawn(move || block_on(task));
+        })
+}
+
+#[test]
+fn wallet_answers_ping() {
+    let channel = Arc::new(Channel::new());
+    let _wallet = wallet(&channel, &Behavior::default());
+
+    let connection: Arc<dyn Connection + Send + Sync> =
+        channel::client::ChannelTransport::new(&channel)
+            .connect(Box::new(Test::Protocol))
+            .into();
+
+    block_on(async {
+        Arc::clone(&connection)
+            .send(keepalive::PING.to_vec())
+            .await
+            .unwrap();
+        let pong = connection.receive().await.unwrap();
+        assert!(keepalive::is_pong(&pong));
+    });
+}
+
+#[test]
+fn alive_wallet_stays_connected() {
+    let channel = Arc::new(Channel::new());
+    let (pings, pinged) = mpsc::unbounded();
+    let behavior = Behavior {
+        pings: Some(pings),
+        ..Behavior::default()
+    };
+    let _wallet = wallet(&channel, &behavior);
+    let delegate = Arc::new(CountingDelegate::default());
+
+    let service = dapp(&channel, &delegate).service(Test::Protocol);
+    block_on(Arc::clone(&service).connect()).unwrap();
+    let mut states = service.states();
+
+    //the next ping is sent only after the pong to the previous one, so a few pings in a row
+    //prove the pongs are accepted. A dropped connection isn't pinged anymore, but reported
+    block_on(async {
+        assert_eq!(Some(ConnectionState::Connected), states.next().await);
+
+        match future::select(pinged.take(4).count(), states.next()).await {
+            Either::Left((count, _)) => assert_eq!(4, count),
+            Either::Right((state, _)) => panic!("the alive wallet is {:?}", state),
+        }
+    });
+    assert_eq!(ConnectionState::Connected, service.state());
+
+    let signed = block_on(Arc::clone(&service).sign_transaction("tx"));
+    assert_eq!("tx_signed!", signed.unwrap());
+    assert_eq!(1, delegate.asked.load(Ordering::SeqCst));
+}
+
+#[test]
+fn hung_wallet_gets_disconnected() {
+    let channel = Arc::new(Channel::new());
+    let behavior = Behavior::default();
+    let _wallet = wallet(&channel, &behavior);
+    let delegate = Arc::new(CountingDelegate::default());
+
+    let service = dapp(&channel, &delegate).service(Test::Protocol);
+    block_on(Arc::clone(&service).connect()).unwrap();
+    let mut states = service.states();
+
+    behavior.hung.store(true, Ordering::SeqCst);
+    block_on(async {
+        assert_eq!(Some(ConnectionState::Connected), states.next().await);
+        assert_eq!(Some(ConnectionState::Disconnected), states.next().await);
+    });
+
+    //the wallet recovers and the next call connects again
+    behavior.hung.store(false, Ordering::SeqCst);
+    let signed = block_on(Arc::clone(&service).sign_transaction("tx"));
+    assert_eq!("tx_signed!", signed.unwrap());
+    assert_eq!(ConnectionState::Connected, service.state());
+    assert_eq!(2, delegate.asked.load(Ordering::SeqCst));
+}
+
+#[test]
+fn slow_requests_are_not_interrupted() {
+    let channel = Arc::new(Channel::new());
+    let behavior = Behavior {
+        signing: Duration::from_millis(300),
+        ..Behavior::default()
+    };
+    let _wallet = wallet(&channel, &behavior);
+    let delegate = Arc::new(CountingDelegate::default());
+
+    let service = dapp(&channel, &delegate).service(Test::Protocol);
+    block_on(Arc::clone(&service).connect()).unwrap();
+    let mut states = service.states();
+
+    //the signing takes longer than a pong may
+    for transaction in ["first", "second"] {
+        let signed = block_on(Arc::clone(&service).sign_transaction(transaction));
+        assert_eq!(format!("{}_signed!", transaction), signed.unwrap());
+    }
+
+    //never disconnected meanwhile
+    assert_eq!(
+        Some(Some(ConnectionState::Connected)),
+        states.next().now_or_never()
+    );
+    assert_eq!(None, states.next().now_or_never());
+    assert_eq!(1, delegate.asked.load(Ordering::SeqCst));
+}
+
+#[test]
+fn zero_durations_are_rejected() {
+    let failed = KeepAlive::new(Duration::ZERO, keepalive::DEFAULT_TIMEOUT);
+    assert_eq!(ErrorKind::Weird, failed.unwrap_err().kind);
+
+    let failed = KeepAlive::default().timeout(Duration::ZERO);
+    assert_eq!(ErrorKind::Weird, failed.unwrap_err().kind);
+
+    assert!(KeepAlive::new(Duration::from_millis(1), Duration::from_millis(1)).is_ok());
+}
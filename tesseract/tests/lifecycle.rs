@@ -0,0 +1,161 @@
+//===------------ lifecycle.rs --------------------------------------------===//
+//  Copyright 2021, Tesseract Systems, Inc.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//===----------------------------------------------------------------------===//
+
+mod common;
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use futures::executor::block_on;
+use futures::StreamExt;
+
+use tesseract_one::client::transport::Status;
+use tesseract_one::client::{ConnectionState, Delegate, Lifecycle};
+use tesseract_one::transports::channel::{self, Channel};
+use tesseract_one::{client, service, ErrorKind};
+
+use tesseract_protocol_test::{Test, TestService};
+
+use common::TestWallet;
+
+//selects the ready endpoint, unless the user declines, and counts how many times it was asked
+#[derive(Default)]
+struct UserDelegate {
+    asked: AtomicUsize,
+    declines: AtomicBool,
+}
+
+#[async_trait]
+impl Delegate for UserDelegate {
+    async fn select_transport(&self, transports: &HashMap<String, Status>) -> Option<String> {
+        self.asked.fetch_add(1, Ordering::SeqCst);
+
+        if self.declines.load(Ordering::SeqCst) {
+            return None;
+        }
+        transports
+            .iter()
+            .find(|(_, status)| **status == Status::Ready)
+            .map(|(id, _)| id.clone())
+    }
+}
+
+fn dapp(channel: &Arc<Channel>, delegate: &Arc<UserDelegate>) -> client::Tesseract {
+    client::Tesseract::new(Arc::clone(delegate) as Arc<dyn Delegate + Send + Sync>)
+        .transport(channel::client::ChannelTransport::new(channel))
+}
+
+fn wallet(channel: &Arc<Channel>) -> service::Tesseract {
+    service::Tesseract::new()
+        .transport(channel::service::ChannelTransport::new(channel))
+        .service(TestWallet {})
+}
+
+#[test]
+fn connect_and_disconnect() {
+    let channel = Arc::new(Channel::new());
+    let _wallet = wallet(&channel);
+    let delegate = Arc::new(UserDelegate::default());
+
+    let service = dapp(&channel, &delegate).service(Test::Protocol);
+    let mut states = service.states();
+    assert_eq!(ConnectionState::Disconnected, service.state());
+
+    block_on(async {
+        //the wallet is selected on connect, not on the first call
+        Arc::clone(&service).connect().await.unwrap();
+        assert_eq!(1, delegate.asked.load(Ordering::SeqCst));
+        assert_eq!(ConnectionState::Connected, service.state());
+
+        let signed = Arc::clone(&service).sign_transaction("tx").await;
+        assert_eq!("tx_signed!", signed.unwrap());
+        Arc::clone(&service).connect().await.unwrap();
+        assert_eq!(1, delegate.asked.load(Ordering::SeqCst));
+
+        Arc::clone(&service).disconnect().await;
+        assert_eq!(ConnectionState::Disconnected, service.state());
+
+        //the next call connects again
+        let signed = Arc::clone(&service).sign_transaction("again").await;
+        assert_eq!("again_signed!", signed.unwrap());
+        assert_eq!(2, delegate.asked.load(Ordering::SeqCst));
+
+        let expected = [
+            ConnectionState::Disconnected,
+            ConnectionState::Connecting,
+            ConnectionState::Connected,
+            ConnectionState::Disconnected,
+            ConnectionState::Connecting,
+            ConnectionState::Connected,
+        ];
+        for state in expected {
+            assert_eq!(Some(state), states.next().await);
+        }
+    });
+}
+
+#[test]
+fn declined_selection_stays_disconnected() {
+    let channel = Arc::new(Channel::new());
+    let _wallet = wallet(&channel);
+    let delegate = Arc::new(UserDelegate::default());
+    delegate.declines.store(true, Ordering::SeqCst);
+
+    let service = dapp(&channel, &delegate).service(Test::Protocol);
+
+    block_on(async {
+        let declined = Arc::clone(&service).connect().await;
+        assert_eq!(ErrorKind::Cancelled, declined.unwrap_err().kind);
+        assert_eq!(ConnectionState::Disconnected, service.state());
+
+        delegate.declines.store(false, Ordering::SeqCst);
+        Arc::clone(&service).connect().await.unwrap();
+        assert_eq!(ConnectionState::Connected, service.state());
+        assert_eq!(2, delegate.asked.load(Ordering::SeqCst));
+    });
+}
+
+#[test]
+fn fails_with_the_transport() {
+    let channel = Arc::new(Channel::new());
+    let delegate = Arc::new(UserDelegate::default());
+    let service = dapp(&channel, &delegate).service(Test::Protocol);
+
+    block_on(async {
+        let bound = wallet(&channel);
+        Arc::clone(&service).connect().await.unwrap();
+        drop(bound);
+
+        let failed = Arc::clone(&service).sign_transaction("tx").await;
+        assert_eq!(ErrorKind::Transport, failed.unwrap_err().kind);
+        assert!(
+            matches!(service.state(), ConnectionState::Failed(error) if error.kind == ErrorKind::Transport)
+        );
+
+        //the wallet is back
+        let _wallet = wallet(&channel);
+        let signed = Arc::clone(&service).sign_transaction("tx").await;
+        assert_eq!("tx_signed!", signed.unwrap());
+        assert_eq!(ConnectionState::Connected, service.state());
+
+        //a wallet error is not the connection's
+        let failed = Arc::clone(&service).sign_transaction("make_error").await;
+        assert_eq!(ErrorKind::Weird, failed.unwrap_err().kind);
+        assert_eq!(ConnectionState::Connected, service.state());
+    });
+}
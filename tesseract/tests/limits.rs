@@ -0,0 +1,138 @@
+//===------------ limits.rs -----------------------------------------------===//
+//  Copyright 2021, Tesseract Systems, Inc.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//===----------------------------------------------------------------------===//
+
+//Regressions of the hostile input: short messages, deep nesting and garbage must be answered
+//with an error, never a panic or a blown stack.
+
+use std::sync::Arc;
+
+use futures::executor::block_on;
+
+use tesseract_one::envelope::ResponseEnvelope;
+use tesseract_one::serialize::{Limits, Serializer};
+use tesseract_one::service::processor::Processor;
+use tesseract_one::service::TransportProcessor;
+use tesseract_one::ErrorKind;
+
+fn nested_json(depth: usize) -> Vec<u8> {
+    format!("{}1{}", "[".repeat(depth), "]".repeat(depth)).into_bytes()
+}
+
+//arrays of one item
+fn nested_cbor(depth: usize) -> Vec<u8> {
+    let mut data = vec![0x81; depth];
+    data.push(0x01);
+    data
+}
+
+fn error_of(response: &[u8]) -> (Serializer, Option<u64>, ErrorKind) {
+    let (envelope, serializer) =
+        Serializer::deserialize_marked::<ResponseEnvelope<()>>(response).unwrap();
+    let kind = envelope.response.into_result().unwrap_err().kind;
+    (serializer, envelope.id, kind)
+}
+
+#[test]
+fn short_marker() {
+    for length in 0..Serializer::marker_len() {
+        let error = Serializer::read_marker(&b"json"[..length]).unwrap_err();
+        assert_eq!(ErrorKind::Serialization, error.kind);
+    }
+
+    let (serializer, data) = Serializer::read_marker(b"json").unwrap();
+    assert!(matches!(serializer, Serializer::Json));
+    assert!(data.is_empty());
+
+    assert!(Serializer::read_marker(b"xml!<a/>").is_err());
+}
+
+#[test]
+fn json_depth() {
+    let limits = Limits::default().max_depth(3);
+
+    assert!(limits.check(Serializer::Json, &nested_json(3)).is_ok());
+    let error = limits.check(Serializer::Json, &nested_json(4)).unwrap_err();
+    assert_eq!(ErrorKind::Serialization, error.kind);
+
+    //the brackets in strings don't count, escaped quotes don't end them
+    let quoted = br#"[{"a": "[[[[\"[[[["}]"#;
+    assert!(limits.check(Serializer::Json, quoted).is_ok());
+
+    assert!(Limits::default()
+        .check(Serializer::Json, &nested_json(1_000_000))
+        .is_err());
+}
+
+#[test]
+fn cbor_depth() {
+    let limits = Limits::default().max_depth(3);
+
+    assert!(limits.check(Serializer::Cbor, &nested_cbor(3)).is_ok());
+    let error = limits.check(Serializer::Cbor, &nested_cbor(4)).unwrap_err();
+    assert_eq!(ErrorKind::Serialization, error.kind);
+
+    //indefinite length: [_ [_ [_ 1]]]
+    let indefinite = [0x9f, 0x9f, 0x9f, 0x01, 0xff, 0xff, 0xff];
+    assert!(limits.check(Serializer::Cbor, &indefinite).is_ok());
+    assert!(Limits::default()
+        .max_depth(2)
+        .check(Serializer::Cbor, &indefinite)
+        .is_err());
+
+    //an array of 2 with a single item
+    assert!(limits.check(Serializer::Cbor, &[0x82, 0x01]).is_err());
+    //a string claiming 4 GiB
+    assert!(limits
+        .check(Serializer::Cbor, &[0x7a, 0xff, 0xff, 0xff, 0xff, 0x61])
+        .is_err());
+
+    assert!(Limits::default()
+        .check(Serializer::Cbor, &nested_cbor(1_000_000))
+        .is_err());
+}
+
+#[test]
+fn malformed_requests_are_answered() {
+    let processor = Arc::new(Processor::new_with_limits(Limits::default().max_size(1024)));
+    let process = |request: &[u8]| block_on(Arc::clone(&processor).process(request));
+
+    //no marker: the default serializer
+    for request in [&b""[..], b"js", b"xml!<a/>"] {
+        let (serializer, id, kind) = error_of(&process(request));
+        assert!(matches!(serializer, Serializer::Json));
+        assert_eq!((None, ErrorKind::Serialization), (id, kind));
+    }
+
+    //the errors come in the request's format
+    let mut cbor = b"cbor".to_vec();
+    cbor.extend(nested_cbor(100));
+    let (serializer, _, kind) = error_of(&process(&cbor));
+    assert!(matches!(serializer, Serializer::Cbor));
+    assert_eq!(ErrorKind::Serialization, kind);
+
+    let (serializer, _, kind) = error_of(&process(b"cbor\xff\x00garbage"));
+    assert!(matches!(serializer, Serializer::Cbor));
+    assert_eq!(ErrorKind::Serialization, kind);
+
+    let (serializer, _, kind) = error_of(&process(b"json{\"id\": 1, \"version\""));
+    assert!(matches!(serializer, Serializer::Json));
+    assert_eq!(ErrorKind::Serialization, kind);
+
+    let mut big = b"json".to_vec();
+    big.extend(nested_json(1024));
+    let (_, _, kind) = error_of(&process(&big));
+    assert_eq!(ErrorKind::Serialization, kind);
+}
@@ -0,0 +1,150 @@
+//===------------ native_messaging.rs ------------------------------------------===//
+//  Copyright 2021, Tesseract Systems, Inc.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//===----------------------------------------------------------------------===//
+
+mod common;
+#[path = "../../vectors/support.rs"]
+mod support;
+
+use serde_json::Value;
+
+use tokio::io::DuplexStream;
+
+use tesseract_one::{service, ErrorKind};
+use tesseract_one::transports::native_messaging::service::NativeMessagingTransport;
+use tesseract_one::transports::native_messaging::{
+    chunks, read_message, write_message, ChunkMessage, MAX_EXTENSION_MESSAGE_LEN, MAX_HOST_MESSAGE_LEN,
+};
+
+use common::TestWallet;
+
+fn vector(name: &str) -> Vec<u8> {
+    support::vector("test", name)[4..].to_vec() //the extension sends no marker
+}
+
+//the browser's end of the host's stdio
+fn wallet() -> (service::Tesseract, DuplexStream) {
+    let (browser, host) = tokio::io::duplex(64 * 1024);
+    let (reader, writer) = tokio::io::split(host);
+
+    let tesseract = service::Tesseract::new()
+        .service(TestWallet {})
+        .transport(NativeMessagingTransport::with_io(reader, writer));
+
+    (tesseract, browser)
+}
+
+fn sign_request(id: u64, transaction: &str) -> Vec<u8> {
+    let request = serde_json::json!({
+        "version": 1,
+        "protocol": "test",
+        "method": "sign_transaction",
+        "id": id,
+        "request": { "transaction": transaction }
+    });
+    serde_json::to_vec(&request).unwrap()
+}
+
+//what the extension does with the chunks
+async fn read_response(browser: &mut DuplexStream) -> Value {
+    let mut data = String::new();
+
+    loop {
+        let message = read_message(browser, MAX_EXTENSION_MESSAGE_LEN).await.unwrap().unwrap();
+        assert!(message.len() <= MAX_HOST_MESSAGE_LEN);
+
+        match serde_json::from_slice::<ChunkMessage>(&message) {
+            Ok(ChunkMessage { chunk }) => {
+                data.push_str(&chunk.data);
+                if chunk.index + 1 == chunk.count {
+                    return serde_json::from_str(&data).unwrap();
+                }
+            }
+            Err(_) => {
+                assert!(data.is_empty());
+                return serde_json::from_slice(&message).unwrap();
+            }
+        }
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn serves_extension() {
+    let (_wallet, mut browser) = wallet();
+
+    write_message(&mut browser, &vector("sign_transaction.request.json")).await.unwrap();
+    let response = read_message(&mut browser, MAX_HOST_MESSAGE_LEN).await.unwrap().unwrap();
+    assert_eq!(vector("sign_transaction.response.json"), response);
+
+    write_message(&mut browser, &sign_request(2, "make_error")).await.unwrap();
+    let response = read_response(&mut browser).await;
+    assert_eq!(2, response["id"]);
+    assert_eq!("error", response["response"]["status"]);
+
+    write_message(&mut browser, b"not json").await.unwrap();
+    let response = read_response(&mut browser).await;
+    assert_eq!(Value::Null, response["id"]);
+    assert_eq!("error", response["response"]["status"]);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn chunks_big_responses() {
+    let (_wallet, mut browser) = wallet();
+
+    //quotes and control chars grow when the response is escaped into the chunks
+    let transaction = "\"\\\u{1}é🦀".repeat(200 * 1024);
+    write_message(&mut browser, &sign_request(7, &transaction)).await.unwrap();
+
+    let response = read_response(&mut browser).await;
+    assert_eq!(7, response["id"]);
+    assert_eq!(format!("{}_signed!", transaction), response["response"]["signed"]);
+
+    //the next response isn't affected
+    write_message(&mut browser, &sign_request(8, "small")).await.unwrap();
+    let response = read_response(&mut browser).await;
+    assert_eq!("small_signed!", response["response"]["signed"]);
+}
+
+#[test]
+fn chunks_fit_the_limit() {
+    let message = serde_json::to_string(&"\u{0}\"x".repeat(1000)).unwrap();
+
+    let parts = chunks(&message, 200).unwrap();
+    assert!(parts.len() > 1);
+
+    let mut data = String::new();
+    for (index, part) in parts.iter().enumerate() {
+        assert!(part.len() <= 200);
+
+        let ChunkMessage { chunk } = serde_json::from_slice(part).unwrap();
+        assert_eq!((index, parts.len()), (chunk.index, chunk.count));
+        data.push_str(&chunk.data);
+    }
+    assert_eq!(message, data);
+
+    assert_eq!(vec![b"{}".to_vec()], chunks("{}", 200).unwrap());
+}
+
+#[tokio::test]
+async fn truncated_length_is_an_error() {
+    let mut closed: &[u8] = &[];
+    assert!(read_message(&mut closed, MAX_HOST_MESSAGE_LEN).await.unwrap().is_none());
+
+    for cut in 1..4 {
+        let mut truncated: &[u8] = &2u32.to_ne_bytes()[..cut];
+        let failed = read_message(&mut truncated, MAX_HOST_MESSAGE_LEN).await;
+        assert_eq!(ErrorKind::Transport, failed.unwrap_err().kind);
+    }
+}
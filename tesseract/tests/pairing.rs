@@ -0,0 +1,342 @@
+//===------------ pairing.rs --------------------------------------------===//
+//  Copyright 2021, Tesseract Systems, Inc.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//===----------------------------------------------------------------------===//
+
+mod common;
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use futures::executor::block_on;
+use sha2::{Digest, Sha256};
+
+use tesseract_one::client::delegate::SingleTransportDelegate;
+use tesseract_one::client::{Connection, Transport};
+use tesseract_one::encryption::client::EncryptedTransport;
+use tesseract_one::encryption::{
+    self, Confirmation, Hello, Identity, Reveal, Session, SessionInfo, Welcome, NONCE_LEN,
+    SESSION_ID_LEN,
+};
+use tesseract_one::pairing::{self, Credentials, PairingUri};
+use tesseract_one::transports::channel::{self, Channel};
+use tesseract_one::{client, service, ErrorKind};
+
+use tesseract_protocol_test::{Test, TestService};
+
+use common::TestWallet;
+
+const RELAY: &str = "relay.example.com:7070";
+
+#[test]
+fn pair_and_reconnect() {
+    let channel = Arc::new(Channel::new());
+
+    let dapp_pairing =
+        pairing::client::Pairing::new(Identity::generate().unwrap(), RELAY, &["test"]).unwrap();
+    let uri = dapp_pairing.uri().to_string();
+    assert!(uri.starts_with("tesseract:pair?v=1&key="));
+
+    let wallet_pairing =
+        pairing::service::Pairing::accept(&uri, Identity::generate().unwrap(), &["test", "other"])
+            .unwrap();
+    assert_eq!(wallet_pairing.protocols(), ["test".to_owned()]);
+    assert_eq!(wallet_pairing.sas(), None);
+
+    let _wallet = service::Tesseract::new()
+        .transport(wallet_pairing.transport(channel::service::ChannelTransport::new(&channel)))
+        .service(TestWallet {});
+
+    let connection =
+        channel::client::ChannelTransport::new(&channel).connect(Box::new(Test::Protocol));
+    let pending = block_on(dapp_pairing.handshake(connection)).unwrap();
+
+    //the user compares the codes
+    assert_eq!(pending.sas().len(), 6);
+    assert_eq!(Some(pending.sas().to_owned()), wallet_pairing.sas());
+
+    let dapp_credentials = pending.confirm();
+    let wallet_credentials = wallet_pairing.confirm().unwrap();
+    assert_eq!(
+        dapp_credentials.peer,
+        wallet_credentials.identity.public_key()
+    );
+    assert_eq!(
+        wallet_credentials.peer,
+        dapp_credentials.identity.public_key()
+    );
+    assert_eq!(dapp_credentials.topic, wallet_credentials.topic);
+    assert_eq!(dapp_credentials.relay, RELAY);
+
+    //stored and loaded on the next launch
+    let stored = serde_json::to_string(&dapp_credentials).unwrap();
+    let loaded: Credentials = serde_json::from_str(&stored).unwrap();
+    assert_eq!(
+        loaded.identity.public_key(),
+        dapp_credentials.identity.public_key()
+    );
+
+    let transport = EncryptedTransport::new(
+        channel::client::ChannelTransport::new(&channel),
+        loaded.identity,
+    )
+    .wallet(loaded.peer);
+    let service = client::Tesseract::new(SingleTransportDelegate::arc())
+        .transport(transport)
+        .service(Test::Protocol);
+
+    let signed = block_on(service.sign_transaction("paired"));
+    assert_eq!("paired_signed!", signed.unwrap());
+}
+
+#[test]
+fn only_the_dapp_of_the_uri_pairs() {
+    let channel = Arc::new(Channel::new());
+
+    let dapp_pairing =
+        pairing::client::Pairing::new(Identity::generate().unwrap(), RELAY, &["test"]).unwrap();
+    let wallet_pairing = pairing::service::Pairing::accept(
+        &dapp_pairing.uri().to_string(),
+        Identity::generate().unwrap(),
+        &["test"],
+    )
+    .unwrap();
+
+    let _wallet = service::Tesseract::new()
+        .transport(wallet_pairing.transport(channel::service::ChannelTransport::new(&channel)))
+        .service(TestWallet {});
+
+    let intruder =
+        pairing::client::Pairing::new(Identity::generate().unwrap(), RELAY, &["test"]).unwrap();
+    let connection =
+        channel::client::ChannelTransport::new(&channel).connect(Box::new(Test::Protocol));
+    let failed = block_on(intruder.handshake(connection));
+    assert_eq!(ErrorKind::Transport, failed.err().unwrap().kind);
+
+    assert_eq!(wallet_pairing.session(), None);
+    assert_eq!(
+        ErrorKind::Cancelled,
+        wallet_pairing.confirm().unwrap_err().kind
+    );
+}
+
+fn random<const N: usize>() -> [u8; N] {
+    let mut bytes = [0u8; N];
+    bytes.copy_from_slice(&Identity::generate().unwrap().to_bytes()[..N]);
+    bytes
+}
+
+//What the relay in the middle has seen and sent posing as the wallet to the dApp
+#[derive(Default)]
+struct Relayed {
+    hello: Option<Vec<u8>>,
+    welcome: Option<Vec<u8>>,
+    reveal: Option<Reveal>,
+}
+
+//Tries the welcomes until the SAS of the transcript it can see matches the one it aims for.
+//But the dApp's nonce isn't in that transcript yet
+struct FakeWallet {
+    identity: Identity,
+    ephemeral: Identity,
+    target: String,
+    relayed: Arc<Mutex<Relayed>>,
+}
+
+impl FakeWallet {
+    const TRIES: usize = 1_000;
+
+    fn welcome(&self, hello: &[u8]) -> Vec<u8> {
+        let mut welcome = Vec::new();
+
+        for _ in 0..Self::TRIES {
+            welcome = Welcome {
+                session: random::<SESSION_ID_LEN>(),
+                ephemeral: self.ephemeral.public_key(),
+                identity: self.identity.public_key(),
+            }
+            .encode();
+
+            let seen = SessionInfo {
+                id: [0; SESSION_ID_LEN],
+                peer: self.identity.public_key(),
+                transcript: Sha256::new()
+                    .chain_update(hello)
+                    .chain_update(&welcome)
+                    .finalize()
+                    .into(),
+            };
+            if pairing::sas(&seen) == self.target {
+                break;
+            }
+        }
+
+        welcome
+    }
+}
+
+#[async_trait]
+impl Connection for FakeWallet {
+    async fn send(self: Arc<Self>, request: Vec<u8>) -> tesseract_one::Result<()> {
+        let mut relayed = self.relayed.lock().unwrap();
+        if request.len() == Hello::LEN {
+            relayed.hello = Some(request);
+        } else {
+            relayed.reveal = Some(Reveal::decode(&request)?);
+        }
+        Ok(())
+    }
+
+    async fn receive(self: Arc<Self>) -> tesseract_one::Result<Vec<u8>> {
+        let mut relayed = self.relayed.lock().unwrap();
+        if let Some(reveal) = &relayed.reveal {
+            return Ok(Confirmation {
+                session: reveal.session,
+            }
+            .encode());
+        }
+
+        let welcome = self.welcome(relayed.hello.as_ref().unwrap());
+        relayed.welcome = Some(welcome.clone());
+        Ok(welcome)
+    }
+}
+
+#[test]
+fn relay_cant_match_the_sas() {
+    let channel = Arc::new(Channel::new());
+
+    let dapp_pairing =
+        pairing::client::Pairing::new(Identity::generate().unwrap(), RELAY, &["test"]).unwrap();
+    let wallet_pairing = pairing::service::Pairing::accept(
+        &dapp_pairing.uri().to_string(),
+        Identity::generate().unwrap(),
+        &["test"],
+    )
+    .unwrap();
+
+    let _wallet = service::Tesseract::new()
+        .transport(wallet_pairing.transport(channel::service::ChannelTransport::new(&channel)))
+        .service(TestWallet {});
+
+    //the relay poses as the dApp to the wallet. It can't read the session, but gets the wallet's SAS
+    let link: Arc<dyn Connection + Sync + Send> = channel::client::ChannelTransport::new(&channel)
+        .connect(Box::new(Test::Protocol))
+        .into();
+    let nonce = random::<NONCE_LEN>();
+    let hello = Hello {
+        ephemeral: Identity::generate().unwrap().public_key(),
+        identity: dapp_pairing.uri().key,
+        commitment: encryption::commit(&nonce),
+    };
+    block_on(async {
+        Arc::clone(&link).send(hello.encode()).await.unwrap();
+        let welcome = Welcome::decode(&Arc::clone(&link).receive().await.unwrap()).unwrap();
+        let reveal = Reveal {
+            session: welcome.session,
+            nonce,
+        };
+        Arc::clone(&link).send(reveal.encode()).await.unwrap();
+        Confirmation::decode(&Arc::clone(&link).receive().await.unwrap()).unwrap();
+    });
+    let target = wallet_pairing.sas().unwrap();
+
+    let relayed = Arc::new(Mutex::new(Relayed::default()));
+    let relay = FakeWallet {
+        identity: Identity::generate().unwrap(),
+        ephemeral: Identity::generate().unwrap(),
+        target: target.clone(),
+        relayed: Arc::clone(&relayed),
+    };
+    let (identity, ephemeral) = (relay.identity.clone(), relay.ephemeral.clone());
+    let pending = block_on(dapp_pairing.handshake(Box::new(relay))).unwrap();
+    assert_ne!(target, pending.sas());
+
+    //the relay learns the dApp's SAS only after its welcome is sent
+    let relayed = relayed.lock().unwrap();
+    let session = Session::wallet(
+        &ephemeral,
+        &identity,
+        relayed.hello.as_ref().unwrap(),
+        relayed.welcome.as_ref().unwrap(),
+        &relayed.reveal.as_ref().unwrap().nonce,
+    )
+    .unwrap();
+    assert_eq!(pairing::sas(session.info()), pending.sas());
+}
+
+#[test]
+fn rejects_bad_uris() {
+    let identity = Identity::generate().unwrap();
+    let pairing = pairing::client::Pairing::new(identity.clone(), RELAY, &["test"]).unwrap();
+    let uri = pairing.uri().clone();
+
+    //round trip
+    let parsed: PairingUri = uri.to_string().parse().unwrap();
+    assert_eq!(parsed, uri);
+
+    let accept = |uri: &str| pairing::service::Pairing::accept(uri, identity.clone(), &["test"]);
+
+    let expired = PairingUri {
+        expires: 1,
+        ..uri.clone()
+    };
+    assert_eq!(
+        ErrorKind::Cancelled,
+        accept(&expired.to_string()).err().unwrap().kind
+    );
+
+    let unsupported = PairingUri {
+        protocols: vec!["unknown".to_owned()],
+        ..uri.clone()
+    };
+    assert_eq!(
+        ErrorKind::Cancelled,
+        accept(&unsupported.to_string()).err().unwrap().kind
+    );
+
+    let newer = uri.to_string().replace("v=1", "v=2");
+    assert_eq!(
+        ErrorKind::UnsupportedVersion,
+        accept(&newer).err().unwrap().kind
+    );
+
+    let malformed = [
+        "not a uri".to_owned(),
+        uri.to_string()
+            .replace("tesseract:pair", "tesseract:request"),
+        uri.to_string().replace("key=", "key=00"),
+        uri.to_string().replace("protocols=test", "protocols="),
+        uri.to_string().replace("expires=", "expires=soon"),
+    ];
+    for uri in malformed {
+        assert_eq!(
+            ErrorKind::Serialization,
+            accept(&uri).err().unwrap().kind,
+            "{}",
+            uri
+        );
+    }
+
+    //the dApp doesn't start the handshake with an expired URI
+    let channel = Arc::new(Channel::new());
+    let connection =
+        channel::client::ChannelTransport::new(&channel).connect(Box::new(Test::Protocol));
+    let late = pairing.ttl(Duration::ZERO);
+    assert_eq!(
+        ErrorKind::Cancelled,
+        block_on(late.handshake(connection)).err().unwrap().kind
+    );
+}
@@ -0,0 +1,182 @@
+//===------------ qr.rs --------------------------------------------------===//
+//  Copyright 2021, Tesseract Systems, Inc.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//===----------------------------------------------------------------------===//
+
+use data_encoding::BASE32_NOPAD;
+
+use tesseract_one::qr::{Decoder, Encoder, PREFIX};
+use tesseract_one::serialize::{Limits, Serializer};
+
+const QR_ALPHANUMERIC: &str = "0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZ $%*+-./:";
+
+fn message() -> Vec<u8> {
+    let request = serde_json::json!({
+        "protocol": "substrate-v1",
+        "method": "sign_transaction",
+        "id": 1,
+        "request": { "transaction": "0a1b2c3d".repeat(300) }
+    });
+    Serializer::Json.serialize(&request, true).unwrap()
+}
+
+#[test]
+fn test_frames_are_alphanumeric() {
+    let mut encoder = Encoder::new(&message(), 100).unwrap();
+    assert!(encoder.count() > 20);
+
+    for _ in 0..encoder.count() * 2 {
+        let frame = encoder.next_frame();
+        assert!(frame.starts_with(PREFIX));
+        assert!(frame.chars().all(|c| QR_ALPHANUMERIC.contains(c)));
+    }
+}
+
+#[test]
+fn test_decodes_with_lost_frames() {
+    let message = message();
+    let encoder = Encoder::new(&message, 100).unwrap();
+    let count = encoder.count() as u32;
+
+    //a third of the plain frames is never scanned, the rest come in reverse
+    let mut frames: Vec<u32> = (1..=count).filter(|seq| seq % 3 != 0).collect();
+    frames.reverse();
+
+    let mut decoder = Decoder::new();
+    for seq in frames {
+        assert_eq!(decoder.receive(&encoder.frame(seq)).unwrap(), None);
+    }
+    assert_eq!(decoder.count(), Some(count as usize));
+    assert!(decoder.solved() < count as usize);
+
+    //the fountain coded frames fill the gaps
+    let mut decoded = None;
+    for seq in count + 1..count * 10 {
+        if let Some(result) = decoder.receive(&encoder.frame(seq)).unwrap() {
+            decoded = Some(result);
+            break;
+        }
+    }
+
+    assert_eq!(decoded, Some(message));
+    assert!(decoder.is_complete());
+    assert_eq!(decoder.receive(&encoder.frame(1)).unwrap(), None);
+}
+
+#[test]
+fn test_single_frame() {
+    let message = Serializer::Json
+        .serialize(&serde_json::json!({"id": 1}), true)
+        .unwrap();
+    let encoder = Encoder::new(&message, 200).unwrap();
+    assert!(encoder.is_single());
+
+    //some scanners lowercase what they read
+    let frame = encoder.frame(1).to_lowercase();
+    assert_eq!(Decoder::new().receive(&frame).unwrap(), Some(message));
+}
+
+#[test]
+fn test_rejects_malformed() {
+    let message = message();
+    let encoder = Encoder::new(&message, 100).unwrap();
+    let frame = encoder.frame(1);
+
+    let mut decoder = Decoder::new();
+    assert!(decoder.receive("").is_err());
+    assert!(decoder.receive("UR:BYTES/1-2/ABC").is_err());
+    assert!(decoder.receive(&frame.replacen("/1-", "/0-", 1)).is_err());
+    assert!(decoder.receive(&frame.replacen("-", "-1", 1)).is_err());
+    assert!(decoder.receive(&format!("{}!", frame)).is_err());
+    assert!(decoder.receive(&frame[..PREFIX.len() + 20]).is_err());
+
+    assert!(decoder.receive(&frame).unwrap().is_none());
+    let other = Encoder::new(b"another message", 100).unwrap();
+    assert!(decoder.receive(&other.frame(1)).is_err());
+
+    assert!(Encoder::new(&message, 0).is_err());
+}
+
+//a frame made by hand, claiming a message of len bytes
+fn forged_frame(seq: u32, count: u64, len: u32, part: &[u8]) -> String {
+    let mut body = len.to_be_bytes().to_vec();
+    body.extend_from_slice(&0u32.to_be_bytes());
+    body.extend_from_slice(part);
+    format!(
+        "{}/{}-{}/{}",
+        PREFIX,
+        seq,
+        count,
+        BASE32_NOPAD.encode(&body)
+    )
+}
+
+#[test]
+fn test_rejects_huge_headers() {
+    //4 GiB in one byte fragments would be billions of slots
+    let huge = forged_frame(1, u32::MAX as u64, u32::MAX, &[0]);
+    let mut decoder = Decoder::new();
+    assert!(decoder.receive(&huge).is_err());
+    assert_eq!(decoder.count(), None);
+
+    //the count has to match the length
+    assert!(decoder.receive(&forged_frame(1, 1 << 40, 4, &[0])).is_err());
+
+    //within the size limit, but too many fragments
+    let limits = Limits::default().max_size(1000);
+    let mut decoder = Decoder::new().limits(limits);
+    assert!(decoder.receive(&forged_frame(1, 1000, 1000, &[0])).is_err());
+    assert!(decoder.receive(&forged_frame(1, 1001, 1001, &[0])).is_err());
+    assert_eq!(decoder.count(), None);
+
+    let message = message();
+    let encoder = Encoder::new(&message, 100).unwrap();
+    let mut decoder = Decoder::new().limits(Limits::default().max_size(message.len() - 1));
+    assert!(decoder.receive(&encoder.frame(1)).is_err());
+}
+
+#[test]
+fn test_limits_pending_frames() {
+    //a stream that never gets decoded: random fragments of a message that doesn't exist
+    let limits = Limits::default().max_size(24000);
+    let mut decoder = Decoder::new().limits(limits);
+
+    let mut state = 1u32;
+    for seq in 1001..4001 {
+        let part: Vec<u8> = (0..24)
+            .map(|_| {
+                state = state.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+                (state >> 16) as u8
+            })
+            .collect();
+
+        let _ = decoder.receive(&forged_frame(seq, 1000, 24000, &part));
+        assert!(decoder.pending() <= limits.max_size);
+    }
+    assert!(decoder.pending() > 0);
+
+    //a real message still gets through the tight limit without the plain frames
+    let message = message();
+    let encoder = Encoder::new(&message, 100).unwrap();
+    let count = encoder.count() as u32;
+    let limits = Limits::default().max_size(message.len());
+    let mut decoder = Decoder::new().limits(limits);
+
+    let decoded = (count + 1..count * 20).find_map(|seq| {
+        let decoded = decoder.receive(&encoder.frame(seq)).unwrap();
+        assert!(decoder.pending() <= limits.max_size);
+        decoded
+    });
+    assert_eq!(decoded, Some(message));
+}
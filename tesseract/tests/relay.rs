@@ -0,0 +1,246 @@
+//===------------ relay.rs --------------------------------------------===//
+//  Copyright 2021, Tesseract Systems, Inc.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//===----------------------------------------------------------------------===//
+
+mod common;
+
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::net::TcpStream;
+
+use tesseract_one::client::delegate::SingleTransportDelegate;
+use tesseract_one::transports::relay::server::{BoundRelayServer, RelayServer};
+use tesseract_one::transports::relay::{self, Join, Role};
+use tesseract_one::transports::stream::{read_frame, write_frame};
+use tesseract_one::{client, service};
+
+use tesseract_protocol_test::{Test, TestService};
+
+use common::TestWallet;
+
+fn relay_server(max_queued: usize) -> (BoundRelayServer, SocketAddr) {
+    bind(RelayServer::listen("127.0.0.1:0").unwrap().max_queued(max_queued))
+}
+
+fn bind(server: RelayServer) -> (BoundRelayServer, SocketAddr) {
+    let address = server.local_addr().unwrap();
+    (server.bind(), address)
+}
+
+//the relay closes the connection
+async fn is_closed(stream: &mut TcpStream) -> bool {
+    let closed = tokio::time::timeout(Duration::from_secs(1), read_frame(stream, 1024)).await;
+    matches!(closed, Ok(Ok(None)) | Ok(Err(_)))
+}
+
+//nothing comes for a while
+async fn is_quiet(stream: &mut TcpStream) -> bool {
+    tokio::time::timeout(Duration::from_millis(100), read_frame(stream, 1024))
+        .await
+        .is_err()
+}
+
+fn wallet(address: SocketAddr, topic: &str) -> service::Tesseract {
+    service::Tesseract::new().service(TestWallet {}).transport(
+        relay::service::RelayTransport::new(address, topic)
+            .reconnect_delay(Duration::from_millis(50)),
+    )
+}
+
+fn dapp(address: SocketAddr, topic: &str) -> client::Tesseract {
+    client::Tesseract::new(SingleTransportDelegate::arc())
+        .transport(relay::client::RelayTransport::new(address, topic))
+}
+
+async fn join(address: SocketAddr, topic: &str, role: Role) -> TcpStream {
+    let mut stream = TcpStream::connect(address).await.unwrap();
+    let join = serde_json::to_vec(&Join {
+        topic: topic.to_owned(),
+        role,
+    })
+    .unwrap();
+    write_frame(&mut stream, &join).await.unwrap();
+    stream
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn sign_through_relay() {
+    let (_relay, address) = relay_server(16);
+    let _wallet = wallet(address, "session-1");
+    let service = dapp(address, "session-1").service(Test::Protocol);
+
+    for i in 0..5 {
+        let transaction = format!("tx_{}", i);
+        let signed = Arc::clone(&service).sign_transaction(&transaction).await;
+        assert_eq!(format!("{}_signed!", transaction), signed.unwrap());
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn request_waits_for_wallet() {
+    let (_relay, address) = relay_server(16);
+    let service = dapp(address, "session-2").service(Test::Protocol);
+
+    let signing = tokio::spawn(async move { service.sign_transaction("early").await });
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    assert!(!signing.is_finished());
+
+    let _wallet = wallet(address, "session-2");
+    let signed = tokio::time::timeout(Duration::from_secs(5), signing)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!("early_signed!", signed.unwrap());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn forwards_opaque_frames_within_topic() {
+    let (_relay, address) = relay_server(2);
+
+    let mut dapp = join(address, "topic-a", Role::Dapp).await;
+    let mut other = join(address, "topic-b", Role::Wallet).await;
+    let mut wallet = join(address, "topic-a", Role::Wallet).await;
+
+    let opaque = vec![0u8, 255, 1, 254, 42];
+    write_frame(&mut dapp, &opaque).await.unwrap();
+    assert_eq!(read_frame(&mut wallet, 1024).await.unwrap(), Some(opaque));
+
+    write_frame(&mut wallet, b"back").await.unwrap();
+    assert_eq!(
+        read_frame(&mut dapp, 1024).await.unwrap(),
+        Some(b"back".to_vec())
+    );
+
+    //nothing leaks into the other topic
+    let leaked =
+        tokio::time::timeout(Duration::from_millis(100), read_frame(&mut other, 1024)).await;
+    assert!(leaked.is_err());
+
+    //the mailbox of a side that is not connected is limited
+    let mut lonely = join(address, "topic-c", Role::Dapp).await;
+    for _ in 0..3 {
+        let _ = write_frame(&mut lonely, b"anyone?").await;
+    }
+    assert!(is_closed(&mut lonely).await);
+
+    //the wallet joining later gets what was queued before the limit
+    let mut late = join(address, "topic-c", Role::Wallet).await;
+    assert_eq!(
+        read_frame(&mut late, 1024).await.unwrap(),
+        Some(b"anyone?".to_vec())
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn rejects_invalid_join() {
+    let (_relay, address) = relay_server(16);
+
+    let mut stream = TcpStream::connect(address).await.unwrap();
+    write_frame(&mut stream, b"{\"topic\":\"\",\"role\":\"dapp\"}")
+        .await
+        .unwrap();
+    let closed = tokio::time::timeout(Duration::from_secs(1), read_frame(&mut stream, 1024)).await;
+    assert!(matches!(closed, Ok(Ok(None)) | Ok(Err(_))));
+
+    assert!(!relay::is_valid_topic(
+        &"x".repeat(relay::MAX_TOPIC_LEN + 1)
+    ));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn limits_the_mailboxes() {
+    let (_relay, address) = bind(
+        RelayServer::listen("127.0.0.1:0")
+            .unwrap()
+            .max_mailboxes(2)
+            .mailbox_ttl(Duration::from_millis(300)),
+    );
+
+    //the dApp's own mailbox and the one of the wallet that never comes
+    let mut dapp = join(address, "topic-a", Role::Dapp).await;
+    write_frame(&mut dapp, b"anyone?").await.unwrap();
+    assert!(is_quiet(&mut dapp).await);
+
+    let mut other = join(address, "topic-b", Role::Dapp).await;
+    assert!(is_closed(&mut other).await);
+
+    //the ones nobody has come for expire
+    drop(dapp);
+    tokio::time::sleep(Duration::from_millis(400)).await;
+    let mut other = join(address, "topic-b", Role::Dapp).await;
+    assert!(is_quiet(&mut other).await);
+
+    let mut late = join(address, "topic-a", Role::Wallet).await;
+    assert!(is_quiet(&mut late).await);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn limits_the_queued_bytes() {
+    let (_relay, address) = bind(
+        RelayServer::listen("127.0.0.1:0")
+            .unwrap()
+            .max_queued_bytes(8),
+    );
+
+    let mut first = join(address, "topic-a", Role::Dapp).await;
+    write_frame(&mut first, b"12345").await.unwrap();
+    assert!(is_quiet(&mut first).await);
+
+    //another topic, but the same relay
+    let mut second = join(address, "topic-b", Role::Dapp).await;
+    let _ = write_frame(&mut second, b"6789").await;
+    assert!(is_closed(&mut second).await);
+
+    let mut wallet = join(address, "topic-a", Role::Wallet).await;
+    assert_eq!(
+        read_frame(&mut wallet, 1024).await.unwrap(),
+        Some(b"12345".to_vec())
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn rejoin_drops_stale_frames() {
+    let (_relay, address) = relay_server(16);
+
+    let mut dapp = join(address, "topic-a", Role::Dapp).await;
+    let mut wallet = join(address, "topic-a", Role::Wallet).await;
+    write_frame(&mut dapp, b"request").await.unwrap();
+    assert_eq!(
+        read_frame(&mut wallet, 1024).await.unwrap(),
+        Some(b"request".to_vec())
+    );
+
+    //the response comes after the dApp has gone
+    drop(dapp);
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    write_frame(&mut wallet, b"stale").await.unwrap();
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    //the wallet gets the new request once the dApp has joined again
+    let mut dapp = join(address, "topic-a", Role::Dapp).await;
+    write_frame(&mut dapp, b"again").await.unwrap();
+    assert_eq!(
+        read_frame(&mut wallet, 1024).await.unwrap(),
+        Some(b"again".to_vec())
+    );
+
+    write_frame(&mut wallet, b"fresh").await.unwrap();
+    assert_eq!(
+        read_frame(&mut dapp, 1024).await.unwrap(),
+        Some(b"fresh".to_vec())
+    );
+}
@@ -0,0 +1,112 @@
+//===------------ replay.rs ------------------------------------------===//
+//  Copyright 2021, Tesseract Systems, Inc.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//===----------------------------------------------------------------------===//
+
+mod common;
+
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use futures::executor::block_on;
+
+use tesseract_one::client::delegate::SingleTransportDelegate;
+use tesseract_one::client::Transport;
+use tesseract_one::replay::client::{RecordingTransport, ReplayTransport};
+use tesseract_one::replay::read_cassette;
+use tesseract_one::serialize::Serializer;
+use tesseract_one::transports::channel::{self, Channel};
+use tesseract_one::{client, service, ErrorKind};
+
+use tesseract_protocol_test::{Test, TestService};
+
+use common::TestWallet;
+
+fn cassette(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("tesseract-{}-{}.jsonl", name, std::process::id()))
+}
+
+fn dapp<T: Transport + Send + Sync + 'static>(transport: T, serializer: Serializer) -> client::Tesseract {
+    client::Tesseract::new_with_serializer(SingleTransportDelegate::arc(), serializer).transport(transport)
+}
+
+//the session to be recorded and then replayed
+async fn session(tesseract: &client::Tesseract) {
+    let service = tesseract.service(Test::Protocol);
+
+    let signed = Arc::clone(&service).sign_transaction("first").await;
+    assert_eq!("first_signed!", signed.unwrap());
+
+    let failed = Arc::clone(&service).sign_transaction("make_error").await;
+    assert_eq!(ErrorKind::Weird, failed.unwrap_err().kind);
+
+    let signed = service.sign_transaction("second").await;
+    assert_eq!("second_signed!", signed.unwrap());
+}
+
+fn record(name: &str, serializer: Serializer) -> PathBuf {
+    let path = cassette(name);
+
+    let channel = Arc::new(Channel::new());
+    let _wallet = service::Tesseract::new()
+        .transport(channel::service::ChannelTransport::new(&channel))
+        .service(TestWallet {});
+
+    let transport = RecordingTransport::create(channel::client::ChannelTransport::new(&channel), &path).unwrap();
+    block_on(session(&dapp(transport, serializer)));
+
+    path
+}
+
+#[test]
+fn replays_without_wallet() {
+    for (name, serializer) in [("json", Serializer::Json), ("cbor", Serializer::Cbor)] {
+        let path = record(&format!("replay-{}", name), serializer);
+        assert_eq!(3, read_cassette(&path).unwrap().len());
+
+        let replay = ReplayTransport::open(&path).unwrap();
+        block_on(session(&dapp(replay.clone(), serializer)));
+        assert!(replay.mismatches().is_empty());
+        assert_eq!(0, replay.remaining());
+    }
+}
+
+#[test]
+fn json_is_readable() {
+    let path = record("readable", Serializer::Json);
+    let cassette = std::fs::read_to_string(&path).unwrap();
+
+    assert_eq!(3, cassette.lines().count());
+    assert!(cassette.contains(r#"{"request":{"text":"json{"#));
+    assert!(cassette.contains("first_signed!"));
+}
+
+#[test]
+fn flags_mismatched_requests() {
+    let path = record("mismatch", Serializer::Json);
+
+    let replay = ReplayTransport::open(&path).unwrap();
+    let service = dapp(replay.clone(), Serializer::Json).service(Test::Protocol);
+
+    block_on(async {
+        let failed = Arc::clone(&service).sign_transaction("other").await;
+        assert_eq!(ErrorKind::Transport, failed.unwrap_err().kind);
+    });
+
+    let mismatches = replay.mismatches();
+    assert_eq!(1, mismatches.len());
+    assert_eq!(0, mismatches[0].index);
+    assert!(String::from_utf8_lossy(mismatches[0].expected.as_ref().unwrap()).contains("first"));
+    assert!(String::from_utf8_lossy(&mismatches[0].actual).contains("other"));
+}
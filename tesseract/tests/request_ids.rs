@@ -0,0 +1,111 @@
+//===------------ request_ids.rs ------------------------------------------===//
+//  Copyright 2021, Tesseract Systems, Inc.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//===----------------------------------------------------------------------===//
+
+mod common;
+
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use futures::executor::block_on;
+use futures::future::join_all;
+
+use tesseract_one::client::delegate::SingleTransportDelegate;
+use tesseract_one::client::RequestIds;
+use tesseract_one::envelope::EnvelopeHeader;
+use tesseract_one::serialize::Serializer;
+use tesseract_one::service::{BoundTransport, TransportProcessor};
+use tesseract_one::transports::channel::{self, Channel};
+use tesseract_one::{client, service};
+
+use tesseract_protocol_test::{Test, TestService};
+
+use common::TestWallet;
+
+//the ids of the requests that reach the wallet
+struct SpyProcessor {
+    processor: Arc<dyn TransportProcessor + Send + Sync>,
+    ids: Arc<Mutex<Vec<u64>>>,
+}
+
+#[async_trait]
+impl TransportProcessor for SpyProcessor {
+    async fn process(self: Arc<Self>, data: &[u8]) -> Vec<u8> {
+        let (header, _) = Serializer::deserialize_marked::<EnvelopeHeader>(data).unwrap();
+        self.ids.lock().unwrap().push(header.id.unwrap());
+        Arc::clone(&self.processor).process(data).await
+    }
+}
+
+struct SpyTransport {
+    transport: channel::service::ChannelTransport,
+    ids: Arc<Mutex<Vec<u64>>>,
+}
+
+impl service::Transport for SpyTransport {
+    fn bind(
+        self,
+        processor: Arc<dyn TransportProcessor + Send + Sync>,
+    ) -> Box<dyn BoundTransport + Send> {
+        self.transport.bind(Arc::new(SpyProcessor {
+            processor,
+            ids: self.ids,
+        }))
+    }
+}
+
+#[test]
+fn services_share_the_ids() {
+    let channel = Arc::new(Channel::new());
+    let ids = Arc::new(Mutex::new(Vec::new()));
+    let _wallet = service::Tesseract::new()
+        .transport(SpyTransport {
+            transport: channel::service::ChannelTransport::new(&channel),
+            ids: Arc::clone(&ids),
+        })
+        .service(TestWallet {});
+
+    let tesseract = client::Tesseract::new(SingleTransportDelegate::arc())
+        .transport(channel::client::ChannelTransport::new(&channel));
+    let first = tesseract.service(Test::Protocol);
+    let second = tesseract.service(Test::Protocol);
+
+    let transactions: Vec<String> = (0..5).map(|i| format!("tx{}", i)).collect();
+    let calls = transactions.iter().flat_map(|transaction| {
+        [
+            Arc::clone(&first).sign_transaction(transaction),
+            Arc::clone(&second).sign_transaction(transaction),
+        ]
+    });
+    for signed in block_on(join_all(calls)) {
+        assert!(signed.unwrap().ends_with("_signed!"));
+    }
+
+    let ids = ids.lock().unwrap();
+    assert_eq!(10, ids.len());
+    assert_eq!(10, ids.iter().collect::<HashSet<_>>().len());
+}
+
+#[test]
+fn ids_wrap_around_skipping_zero() {
+    let ids = RequestIds::starting_at(u64::MAX - 1);
+
+    let next: Vec<u64> = (0..4).map(|_| ids.next()).collect();
+    assert_eq!(next, [u64::MAX - 1, u64::MAX, 1, 2]);
+
+    assert_eq!(1, RequestIds::new().next());
+    assert_eq!(1, RequestIds::starting_at(0).next());
+}
@@ -0,0 +1,207 @@
+//===------------ session_store.rs --------------------------------------------===//
+//  Copyright 2021, Tesseract Systems, Inc.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//===----------------------------------------------------------------------===//
+
+mod common;
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use futures::executor::block_on;
+
+use tesseract_one::client::session::{FileSessionStore, SessionStore, StoredSession};
+use tesseract_one::client::transport::Status;
+use tesseract_one::client::Delegate;
+use tesseract_one::transports::channel::{self, Channel};
+use tesseract_one::{client, service, ErrorKind};
+
+use tesseract_protocol_test::{Test, TestService};
+
+use common::TestWallet;
+
+//selects the first ready endpoint and counts how many times it was asked
+#[derive(Default)]
+struct CountingDelegate {
+    asked: AtomicUsize,
+}
+
+#[async_trait]
+impl Delegate for CountingDelegate {
+    async fn select_transport(&self, transports: &HashMap<String, Status>) -> Option<String> {
+        self.asked.fetch_add(1, Ordering::SeqCst);
+
+        transports
+            .iter()
+            .find(|(_, status)| **status == Status::Ready)
+            .map(|(id, _)| id.clone())
+    }
+}
+
+fn store_path(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!(
+        "tesseract-sessions-{}-{}.json",
+        name,
+        std::process::id()
+    ));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+//a new launch of the dApp
+fn dapp(
+    channel: &Arc<Channel>,
+    path: &PathBuf,
+    delegate: &Arc<CountingDelegate>,
+) -> client::Tesseract {
+    client::Tesseract::new(Arc::clone(delegate) as Arc<dyn Delegate + Send + Sync>)
+        .transport(channel::client::ChannelTransport::new(channel))
+        .session_store(FileSessionStore::new(path))
+}
+
+#[test]
+fn resumes_the_selected_endpoint() {
+    let channel = Arc::new(Channel::new());
+    let _wallet = service::Tesseract::new()
+        .transport(channel::service::ChannelTransport::new(&channel))
+        .service(TestWallet {});
+
+    let path = store_path("resume");
+    let delegate = Arc::new(CountingDelegate::default());
+
+    let first = dapp(&channel, &path, &delegate).service(Test::Protocol);
+    let signed = block_on(first.sign_transaction("first"));
+    assert_eq!("first_signed!", signed.unwrap());
+    assert_eq!(1, delegate.asked.load(Ordering::SeqCst));
+
+    let tesseract = dapp(&channel, &path, &delegate);
+    let stored = tesseract.resume(Test::Protocol).unwrap().unwrap();
+    assert_eq!(stored.protocol, "test");
+    assert_eq!(stored.transport.as_deref(), Some("channel"));
+    assert_eq!(stored.endpoint.as_deref(), Some("channel"));
+
+    //the next launch doesn't ask the Delegate
+    let second = tesseract.service(Test::Protocol);
+    let signed = block_on(second.sign_transaction("second"));
+    assert_eq!("second_signed!", signed.unwrap());
+    assert_eq!(1, delegate.asked.load(Ordering::SeqCst));
+
+    //until the session is revoked
+    assert!(tesseract.revoke(Test::Protocol).unwrap());
+    assert!(!tesseract.revoke(Test::Protocol).unwrap());
+    assert_eq!(tesseract.resume(Test::Protocol).unwrap(), None);
+
+    let third = dapp(&channel, &path, &delegate).service(Test::Protocol);
+    let signed = block_on(third.sign_transaction("third"));
+    assert_eq!("third_signed!", signed.unwrap());
+    assert_eq!(2, delegate.asked.load(Ordering::SeqCst));
+
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn remembers_wallet_and_keys() {
+    let channel = Arc::new(Channel::new());
+    let _wallet = service::Tesseract::new()
+        .transport(channel::service::ChannelTransport::new(&channel))
+        .service(TestWallet {});
+
+    let path = store_path("keys");
+    let delegate = Arc::new(CountingDelegate::default());
+
+    //i.e. right after pairing, before any connection
+    let tesseract = dapp(&channel, &path, &delegate);
+    let keys = serde_json::json!({ "identity": "00ff", "topic": "abc" });
+    tesseract
+        .remember(
+            Test::Protocol,
+            Some("wallet-key".to_owned()),
+            Some(keys.clone()),
+        )
+        .unwrap();
+
+    let service = tesseract.service(Test::Protocol);
+    assert!(block_on(service.sign_transaction("tx")).is_ok());
+
+    let sessions = dapp(&channel, &path, &delegate).sessions().unwrap();
+    assert_eq!(sessions.len(), 1);
+    assert_eq!(sessions[0].wallet.as_deref(), Some("wallet-key"));
+    assert_eq!(sessions[0].keys, Some(keys));
+    assert_eq!(sessions[0].endpoint.as_deref(), Some("channel"));
+
+    //the keys of another wallet are forgotten when the Delegate selects a different one
+    let store = FileSessionStore::new(&path);
+    store
+        .save(StoredSession {
+            endpoint: Some("elsewhere".to_owned()),
+            ..sessions[0].clone()
+        })
+        .unwrap();
+
+    let service = dapp(&channel, &path, &delegate).service(Test::Protocol);
+    assert!(block_on(service.sign_transaction("tx")).is_ok());
+
+    let stored = store.load("test").unwrap().unwrap();
+    assert_eq!(stored.endpoint.as_deref(), Some("channel"));
+    assert_eq!(stored.keys, None);
+
+    //without a store there is nothing to remember in
+    let plain = client::Tesseract::new(Arc::clone(&delegate) as Arc<dyn Delegate + Send + Sync>);
+    assert!(plain.remember(Test::Protocol, None, None).is_err());
+    assert!(plain.sessions().unwrap().is_empty());
+
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn file_store() {
+    let path = store_path("file");
+    let store = FileSessionStore::new(&path);
+
+    //a missing file is an empty store
+    assert!(store.list().unwrap().is_empty());
+    assert_eq!(store.load("test").unwrap(), None);
+    assert!(!store.remove("test").unwrap());
+
+    for protocol in ["b", "a"] {
+        store.save(StoredSession::new(protocol)).unwrap();
+    }
+    let protocols: Vec<_> = store
+        .list()
+        .unwrap()
+        .into_iter()
+        .map(|s| s.protocol)
+        .collect();
+    assert_eq!(protocols, ["a", "b"]);
+
+    //it holds the keys
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(0o600, mode & 0o777);
+    }
+
+    let unwritable = FileSessionStore::new(path.join("missing").join("sessions.json"));
+    let failed = unwritable.save(StoredSession::new("test"));
+    assert_eq!(ErrorKind::Storage, failed.unwrap_err().kind);
+
+    std::fs::write(&path, "{ not json").unwrap();
+    assert_eq!(ErrorKind::Serialization, store.list().unwrap_err().kind);
+
+    let _ = std::fs::remove_file(&path);
+}
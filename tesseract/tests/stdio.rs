@@ -0,0 +1,117 @@
+//===------------ stdio.rs ------------------------------------------===//
+//  Copyright 2021, Tesseract Systems, Inc.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//===----------------------------------------------------------------------===//
+
+//The test binary is the wallet too: the dApp spawns it again with WALLET_MODE set
+
+mod common;
+
+use std::sync::Arc;
+
+use tesseract_one::client::delegate::SingleTransportDelegate;
+use tesseract_one::client::transport::Status;
+use tesseract_one::client::Transport;
+use tesseract_one::transports::stdio;
+use tesseract_one::{client, service, ErrorKind};
+
+use tesseract_protocol_test::{Test, TestService};
+
+use common::TestWallet;
+
+const WALLET_MODE: &str = "TESSERACT_STDIO_WALLET";
+
+fn wallet(mode: &str) {
+    match mode {
+        "exit" => std::process::exit(3),
+        "crash" => std::process::abort(),
+        _ => (),
+    }
+
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let (closed, wait) = futures::channel::oneshot::channel();
+
+    let _tesseract = runtime.block_on(async {
+        service::Tesseract::new()
+            .service(TestWallet {})
+            .transport(stdio::service::StdioTransport::new().on_close(move || {
+                let _ = closed.send(());
+            }))
+    });
+
+    runtime.block_on(wait).unwrap();
+}
+
+fn dapp(mode: &str) -> client::Tesseract {
+    let transport = stdio::client::StdioTransport::new(std::env::current_exe().unwrap())
+        .env(WALLET_MODE, mode);
+
+    client::Tesseract::new(SingleTransportDelegate::arc()).transport(transport)
+}
+
+async fn sign_over_stdio() {
+    let service = dapp("serve").service(Test::Protocol);
+
+    let signed = Arc::clone(&service).sign_transaction("transaction").await;
+    assert_eq!("transaction_signed!", signed.unwrap());
+
+    let failed = Arc::clone(&service).sign_transaction("make_error").await;
+    assert_eq!(ErrorKind::Weird, failed.unwrap_err().kind);
+
+    let signed = service.sign_transaction("again").await;
+    assert_eq!("again_signed!", signed.unwrap());
+}
+
+async fn reports_exit_status() {
+    let failed = dapp("exit").service(Test::Protocol).sign_transaction("transaction").await;
+    let error = failed.unwrap_err();
+
+    assert_eq!(ErrorKind::Transport, error.kind);
+    assert!(error.description.unwrap().contains("exited with code 3"));
+}
+
+async fn reports_crash() {
+    let failed = dapp("crash").service(Test::Protocol).sign_transaction("transaction").await;
+    let error = failed.unwrap_err();
+
+    assert_eq!(ErrorKind::Transport, error.kind);
+    assert!(error.description.unwrap().contains("crashed"));
+}
+
+async fn unavailable_without_wallet() {
+    let transport = Arc::new(stdio::client::StdioTransport::new("/nonexistent/wallet"));
+    let status = transport.status(Box::new(Test::Protocol)).await;
+    assert!(matches!(status, Status::Unavailable(_)));
+
+    let transport = Arc::new(stdio::client::StdioTransport::new(std::env::current_exe().unwrap()));
+    let status = transport.status(Box::new(Test::Protocol)).await;
+    assert_eq!(Status::Ready, status);
+}
+
+fn main() {
+    if let Ok(mode) = std::env::var(WALLET_MODE) {
+        return wallet(&mode);
+    }
+
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+
+    println!("test sign_over_stdio");
+    runtime.block_on(sign_over_stdio());
+    println!("test reports_exit_status");
+    runtime.block_on(reports_exit_status());
+    println!("test reports_crash");
+    runtime.block_on(reports_crash());
+    println!("test unavailable_without_wallet");
+    runtime.block_on(unavailable_without_wallet());
+}
@@ -0,0 +1,62 @@
+//===------------ stream.rs -----------------------------------------------===//
+//  Copyright 2021, Tesseract Systems, Inc.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//===----------------------------------------------------------------------===//
+
+use tesseract_one::transports::stream::{read_frame, write_frame};
+use tesseract_one::ErrorKind;
+
+#[tokio::test]
+async fn frames_roundtrip() {
+    let mut stream = Vec::new();
+    write_frame(&mut stream, b"json{}").await.unwrap();
+    write_frame(&mut stream, b"").await.unwrap();
+
+    let mut reader = stream.as_slice();
+    assert_eq!(
+        Some(b"json{}".to_vec()),
+        read_frame(&mut reader, 1024).await.unwrap()
+    );
+    assert_eq!(Some(vec![]), read_frame(&mut reader, 1024).await.unwrap());
+    //closed between the frames
+    assert_eq!(None, read_frame(&mut reader, 1024).await.unwrap());
+}
+
+#[tokio::test]
+async fn truncated_frames_are_errors() {
+    //a part of the header
+    let mut reader = &[0u8, 0][..];
+    let error = read_frame(&mut reader, 1024).await.unwrap_err();
+    assert_eq!(ErrorKind::Transport, error.kind);
+
+    //a part of the frame
+    let mut reader = &[0u8, 0, 0, 10, b'j', b's'][..];
+    let error = read_frame(&mut reader, 1024).await.unwrap_err();
+    assert_eq!(ErrorKind::Transport, error.kind);
+
+    //the header claims 1 GiB, but only a few bytes follow
+    let mut reader = &[0x40u8, 0, 0, 0, 1, 2, 3][..];
+    let error = read_frame(&mut reader, usize::MAX).await.unwrap_err();
+    assert_eq!(ErrorKind::Transport, error.kind);
+}
+
+#[tokio::test]
+async fn frames_are_limited() {
+    let mut stream = Vec::new();
+    write_frame(&mut stream, &[0; 100]).await.unwrap();
+
+    let error = read_frame(&mut stream.as_slice(), 99).await.unwrap_err();
+    assert_eq!(ErrorKind::Transport, error.kind);
+    assert!(read_frame(&mut stream.as_slice(), 100).await.is_ok());
+}
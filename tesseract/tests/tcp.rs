@@ -0,0 +1,98 @@
+//===------------ tcp.rs --------------------------------------------===//
+//  Copyright 2021, Tesseract Systems, Inc.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//===----------------------------------------------------------------------===//
+
+mod common;
+
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use tesseract_one::client::delegate::SingleTransportDelegate;
+use tesseract_one::client::transport::Status;
+use tesseract_one::client::Transport;
+use tesseract_one::transports::tcp;
+use tesseract_one::{client, service, ErrorKind};
+
+use tesseract_protocol_test::{Test, TestService};
+
+use common::TestWallet;
+
+fn wallet() -> (service::Tesseract, SocketAddr) {
+    let transport = tcp::service::TcpTransport::listen("127.0.0.1:0").unwrap();
+    let address = transport.local_addr().unwrap();
+
+    let tesseract = service::Tesseract::new()
+        .transport(transport)
+        .service(TestWallet {});
+
+    (tesseract, address)
+}
+
+fn dapp(address: SocketAddr) -> client::Tesseract {
+    client::Tesseract::new(SingleTransportDelegate::arc())
+        .transport(tcp::client::TcpTransport::new(address))
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn sign_over_loopback() {
+    let (_wallet, address) = wallet();
+    let service = dapp(address).service(Test::Protocol);
+
+    let signed = Arc::clone(&service).sign_transaction("transaction").await;
+    assert_eq!("transaction_signed!", signed.unwrap());
+
+    let failed = Arc::clone(&service).sign_transaction("make_error").await;
+    assert_eq!(ErrorKind::Weird, failed.unwrap_err().kind);
+
+    let signed = service.sign_transaction("again").await;
+    assert_eq!("again_signed!", signed.unwrap());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn serves_many_dapps_at_once() {
+    let (_wallet, address) = wallet();
+
+    let dapps = (0..16).map(|i| {
+        tokio::spawn(async move {
+            let service = dapp(address).service(Test::Protocol);
+
+            for j in 0..10 {
+                let transaction = format!("tx_{}_{}", i, j);
+                let signed = Arc::clone(&service).sign_transaction(&transaction).await;
+                assert_eq!(format!("{}_signed!", transaction), signed.unwrap());
+            }
+        })
+    });
+
+    for dapp in futures::future::join_all(dapps).await {
+        dapp.unwrap();
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn unavailable_without_wallet() {
+    let address = {
+        let (_wallet, address) = wallet();
+        address
+    };
+
+    let transport = Arc::new(tcp::client::TcpTransport::new(address));
+    let status = transport.status(Box::new(Test::Protocol)).await;
+    assert!(matches!(status, Status::Unavailable(_)));
+
+    let service = dapp(address).service(Test::Protocol);
+    let failed = service.sign_transaction("transaction").await;
+    assert_eq!(ErrorKind::Transport, failed.unwrap_err().kind);
+}
@@ -0,0 +1,127 @@
+//===------------ uds.rs --------------------------------------------===//
+//  Copyright 2021, Tesseract Systems, Inc.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//===----------------------------------------------------------------------===//
+
+mod common;
+
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use tesseract_one::client::delegate::SingleTransportDelegate;
+use tesseract_one::client::transport::Status;
+use tesseract_one::client::Transport;
+use tesseract_one::service::Context;
+use tesseract_one::transports::uds;
+use tesseract_one::transports::uds::PeerCredentials;
+use tesseract_one::{client, service, Error, ErrorKind, Result};
+
+use tesseract_protocol_test::service::TestExecutor;
+use tesseract_protocol_test::{Test, TestService};
+
+use common::TestWallet;
+
+//tells which process asked for the signature
+struct CheckingWallet {}
+
+impl service::Service for CheckingWallet {
+    type Protocol = Test;
+
+    fn protocol(&self) -> &Test {
+        &Test::Protocol
+    }
+
+    fn to_executor(self) -> Box<dyn service::Executor + Send + Sync> {
+        Box::new(TestExecutor::from_service(self))
+    }
+}
+
+#[async_trait]
+impl TestService for CheckingWallet {
+    async fn sign_transaction(self: Arc<Self>, req: &str) -> Result<String> {
+        let context = Context::current();
+        let credentials = context
+            .get::<PeerCredentials>()
+            .ok_or_else(|| Error::kinded(ErrorKind::Weird))?;
+
+        Ok(format!("{}_signed_for_{}", req, credentials.pid.unwrap_or(0)))
+    }
+}
+
+fn socket_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("tesseract-{}-{}.sock", name, std::process::id()))
+}
+
+fn dapp(path: &PathBuf) -> client::Tesseract {
+    client::Tesseract::new(SingleTransportDelegate::arc())
+        .transport(uds::client::UdsTransport::new(path))
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn sign_over_socket() {
+    let path = socket_path("sign");
+    let _wallet = service::Tesseract::new()
+        .transport(uds::service::UdsTransport::listen(&path).unwrap())
+        .service(TestWallet {});
+
+    let service = dapp(&path).service(Test::Protocol);
+
+    let signed = Arc::clone(&service).sign_transaction("transaction").await;
+    assert_eq!("transaction_signed!", signed.unwrap());
+
+    let failed = service.sign_transaction("make_error").await;
+    assert_eq!(ErrorKind::Weird, failed.unwrap_err().kind);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn executors_see_peer_credentials() {
+    let path = socket_path("credentials");
+    let _wallet = service::Tesseract::new()
+        .transport(uds::service::UdsTransport::listen(&path).unwrap())
+        .service(CheckingWallet {});
+
+    let service = dapp(&path).service(Test::Protocol);
+
+    let signed = service.sign_transaction("transaction").await;
+    assert_eq!(
+        format!("transaction_signed_for_{}", std::process::id()),
+        signed.unwrap()
+    );
+
+    //outside of a request there is nothing to check
+    assert!(Context::current().get::<PeerCredentials>().is_none());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn socket_is_removed_with_wallet() {
+    let path = socket_path("removed");
+    {
+        let _wallet = service::Tesseract::new()
+            .transport(uds::service::UdsTransport::listen(&path).unwrap())
+            .service(TestWallet {});
+
+        assert!(path.exists());
+        assert!(uds::service::UdsTransport::listen(&path).is_err());
+    }
+    assert!(!path.exists());
+
+    let transport = Arc::new(uds::client::UdsTransport::new(&path));
+    let status = transport.status(Box::new(Test::Protocol)).await;
+    assert!(matches!(status, Status::Unavailable(_)));
+
+    let failed = dapp(&path).service(Test::Protocol).sign_transaction("transaction").await;
+    assert_eq!(ErrorKind::Transport, failed.unwrap_err().kind);
+}
@@ -0,0 +1,43 @@
+//===------------ vectors.rs --------------------------------------------===//
+//  Copyright 2021, Tesseract Systems, Inc.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//===----------------------------------------------------------------------===//
+
+#[path = "../../vectors/support.rs"]
+mod support;
+
+use support::roundtrip;
+
+use tesseract_one::envelope::ResponseEnvelope;
+use tesseract_one::ErrorKind;
+
+#[test]
+fn errors() {
+    for marker in ["json", "cbor"] {
+        let no_id: ResponseEnvelope<()> =
+            roundtrip("core", &format!("error_without_id.{}", marker));
+        assert_eq!(None, no_id.id);
+        assert_eq!(ErrorKind::Serialization, no_id.response.into_result().unwrap_err().kind);
+
+        let version: ResponseEnvelope<()> =
+            roundtrip("core", &format!("unsupported_version.{}", marker));
+        assert_eq!(Some(3), version.id);
+        assert_eq!(ErrorKind::UnsupportedVersion, version.response.into_result().unwrap_err().kind);
+
+        let cancelled: ResponseEnvelope<()> =
+            roundtrip("core", &format!("cancelled.{}", marker));
+        assert_eq!(Some(4), cancelled.id);
+        assert_eq!(ErrorKind::Cancelled, cancelled.response.into_result().unwrap_err().kind);
+    }
+}
@@ -0,0 +1,139 @@
+//===------------ ws.rs ---------------------------------------------===//
+//  Copyright 2021, Tesseract Systems, Inc.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//===----------------------------------------------------------------------===//
+
+mod common;
+#[path = "../../vectors/support.rs"]
+mod support;
+
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use futures::{SinkExt, StreamExt};
+
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::Message;
+
+use tesseract_one::client::delegate::SingleTransportDelegate;
+use tesseract_one::client::transport::Status;
+use tesseract_one::client::Transport;
+use tesseract_one::transports::ws;
+use tesseract_one::{client, service, ErrorKind};
+
+use tesseract_protocol_test::{Test, TestService};
+
+use common::TestWallet;
+
+const DAPP_ORIGIN: &str = "https://dapp.example";
+
+fn vector(name: &str) -> Vec<u8> {
+    support::vector("test", name)
+}
+
+fn wallet(transport: ws::service::WsTransport) -> (service::Tesseract, SocketAddr) {
+    let address = transport.local_addr().unwrap();
+
+    let tesseract = service::Tesseract::new()
+        .transport(transport)
+        .service(TestWallet {});
+
+    (tesseract, address)
+}
+
+fn dapp(address: SocketAddr) -> client::Tesseract {
+    client::Tesseract::new(SingleTransportDelegate::arc())
+        .transport(ws::client::WsTransport::new(&format!("ws://{}", address)))
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn sign_over_loopback() {
+    let (_wallet, address) = wallet(ws::service::WsTransport::listen("127.0.0.1:0").unwrap());
+    let service = dapp(address).service(Test::Protocol);
+
+    let signed = Arc::clone(&service).sign_transaction("transaction").await;
+    assert_eq!("transaction_signed!", signed.unwrap());
+
+    let failed = Arc::clone(&service).sign_transaction("make_error").await;
+    assert_eq!(ErrorKind::Weird, failed.unwrap_err().kind);
+
+    let signed = service.sign_transaction("again").await;
+    assert_eq!("again_signed!", signed.unwrap());
+}
+
+//what a browser does: a handshake with the Origin header and binary messages
+#[tokio::test(flavor = "multi_thread")]
+async fn serves_allowed_origin() {
+    let transport = ws::service::WsTransport::listen("127.0.0.1:0")
+        .unwrap()
+        .allow_origins([DAPP_ORIGIN]);
+    let (_wallet, address) = wallet(transport);
+
+    let mut request = format!("ws://{}", address).into_client_request().unwrap();
+    request
+        .headers_mut()
+        .insert("Origin", DAPP_ORIGIN.parse().unwrap());
+    let (mut socket, _) = tokio_tungstenite::connect_async(request).await.unwrap();
+
+    let request = vector("sign_transaction.request.json");
+    socket.send(Message::binary(request)).await.unwrap();
+
+    let response = socket.next().await.unwrap().unwrap();
+    assert_eq!(vector("sign_transaction.response.json"), response.into_data().to_vec());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn rejects_other_origins() {
+    let transport = ws::service::WsTransport::listen("127.0.0.1:0")
+        .unwrap()
+        .origin(|origin| origin == Some(DAPP_ORIGIN));
+    let (_wallet, address) = wallet(transport);
+
+    let mut request = format!("ws://{}", address).into_client_request().unwrap();
+    request
+        .headers_mut()
+        .insert("Origin", "https://evil.example".parse().unwrap());
+    assert!(tokio_tungstenite::connect_async(request).await.is_err());
+
+    //the Rust dApp sends no Origin at all
+    let failed = dapp(address).service(Test::Protocol).sign_transaction("transaction").await;
+    assert_eq!(ErrorKind::Transport, failed.unwrap_err().kind);
+}
+
+//a website the user happens to visit can't reach the wallet on localhost
+#[tokio::test(flavor = "multi_thread")]
+async fn rejects_browser_origins_by_default() {
+    let (_wallet, address) = wallet(ws::service::WsTransport::listen("127.0.0.1:0").unwrap());
+
+    let mut request = format!("ws://{}", address).into_client_request().unwrap();
+    request
+        .headers_mut()
+        .insert("Origin", "https://evil.example".parse().unwrap());
+    assert!(tokio_tungstenite::connect_async(request).await.is_err());
+
+    let signed = dapp(address).service(Test::Protocol).sign_transaction("transaction").await;
+    assert_eq!("transaction_signed!", signed.unwrap());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn unavailable_without_wallet() {
+    let address = {
+        let (_wallet, address) = wallet(ws::service::WsTransport::listen("127.0.0.1:0").unwrap());
+        address
+    };
+
+    let transport = Arc::new(ws::client::WsTransport::new(&format!("ws://{}", address)));
+    let status = transport.status(Box::new(Test::Protocol)).await;
+    assert!(matches!(status, Status::Unavailable(_)));
+}
@@ -0,0 +1,163 @@
+//===------------ xdg.rs --------------------------------------------===//
+//  Copyright 2021, Tesseract Systems, Inc.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//===----------------------------------------------------------------------===//
+
+mod common;
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+
+use tesseract_one::client::delegate::SingleTransportDelegate;
+use tesseract_one::client::transport::{Endpoint, Status};
+use tesseract_one::client::{Delegate, Transport};
+use tesseract_one::transports::xdg;
+use tesseract_one::transports::xdg::Manifest;
+use tesseract_one::{client, service, ErrorKind};
+
+use tesseract_protocol_test::{Test, TestService};
+
+use common::TestWallet;
+
+//remembers what was offered and picks the wallet by name
+struct PickingDelegate {
+    name: String,
+    offered: Mutex<Vec<Endpoint>>,
+}
+
+#[async_trait]
+impl Delegate for PickingDelegate {
+    async fn select_transport(&self, _: &HashMap<String, Status>) -> Option<String> {
+        None
+    }
+
+    async fn select_endpoint(&self, endpoints: &[Endpoint]) -> Option<String> {
+        *self.offered.lock().unwrap() = endpoints.to_vec();
+
+        endpoints
+            .iter()
+            .find(|e| e.name.as_deref() == Some(self.name.as_str()))
+            .map(|e| e.id.clone())
+    }
+}
+
+fn directory(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("tesseract-xdg-{}-{}", name, std::process::id()))
+}
+
+fn wallet(directory: &PathBuf, id: &str, manifest: Manifest) -> service::Tesseract {
+    service::Tesseract::new()
+        .transport(xdg::service::XdgTransport::register_in(directory, id, manifest).unwrap())
+        .service(TestWallet {})
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn delegate_selects_among_wallets() {
+    let directory = directory("select");
+    let _first = wallet(
+        &directory,
+        "first",
+        Manifest::new("First Wallet").protocol("test").icon("/usr/share/icons/first.png"),
+    );
+    let _second = wallet(&directory, "second", Manifest::new("Second Wallet").protocol("test"));
+    let _other = wallet(&directory, "other", Manifest::new("Other Wallet").protocol("substrate-v1"));
+
+    let delegate = Arc::new(PickingDelegate {
+        name: "Second Wallet".to_owned(),
+        offered: Mutex::new(Vec::new()),
+    });
+    let service = client::Tesseract::new(Arc::clone(&delegate) as Arc<dyn Delegate + Send + Sync>)
+        .transport(xdg::client::XdgTransport::new().directory(&directory))
+        .service(Test::Protocol);
+
+    let signed = service.sign_transaction("transaction").await;
+    assert_eq!("transaction_signed!", signed.unwrap());
+
+    let offered = delegate.offered.lock().unwrap().clone();
+    let ids: Vec<_> = offered.iter().map(|e| e.id.as_str()).collect();
+    assert_eq!(vec!["xdg/first", "xdg/other", "xdg/second"], ids);
+
+    assert_eq!(Some("/usr/share/icons/first.png"), offered[0].icon.as_deref());
+    assert_eq!(Status::Ready, offered[0].status);
+    assert!(matches!(offered[1].status, Status::Unavailable(_)));
+    assert_eq!(Status::Ready, offered[2].status);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn wallet_unregisters_when_dropped() {
+    let directory = directory("unregister");
+    let transport = Arc::new(xdg::client::XdgTransport::new().directory(&directory));
+
+    {
+        let _wallet = wallet(&directory, "wallet", Manifest::new("Wallet").protocol("test"));
+
+        let endpoints = Arc::clone(&transport).endpoints(Box::new(Test::Protocol)).await;
+        assert_eq!(1, endpoints.len());
+        assert_eq!(Some("Wallet"), endpoints[0].name.as_deref());
+
+        let status = Arc::clone(&transport).status(Box::new(Test::Protocol)).await;
+        assert_eq!(Status::Ready, status);
+    }
+
+    let endpoints = Arc::clone(&transport).endpoints(Box::new(Test::Protocol)).await;
+    assert_eq!(1, endpoints.len());
+    assert_eq!("xdg", endpoints[0].id);
+    assert!(matches!(endpoints[0].status, Status::Unavailable(_)));
+
+    let status = transport.status(Box::new(Test::Protocol)).await;
+    assert!(matches!(status, Status::Unavailable(_)));
+
+    let failed = client::Tesseract::new(SingleTransportDelegate::arc())
+        .transport(xdg::client::XdgTransport::new().directory(&directory))
+        .service(Test::Protocol)
+        .sign_transaction("transaction")
+        .await;
+    assert_eq!(ErrorKind::Transport, failed.unwrap_err().kind);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn single_transport_delegate_with_several_wallets() {
+    let directory = directory("single");
+    let dapp = || {
+        client::Tesseract::new(SingleTransportDelegate::arc())
+            .transport(xdg::client::XdgTransport::new().directory(&directory))
+            .service(Test::Protocol)
+    };
+
+    //the only one that can serve the protocol
+    let _first = wallet(&directory, "first", Manifest::new("First Wallet").protocol("test"));
+    let _other = wallet(&directory, "other", Manifest::new("Other Wallet").protocol("substrate-v1"));
+
+    let signed = dapp().sign_transaction("transaction").await;
+    assert_eq!("transaction_signed!", signed.unwrap());
+
+    //two of them, it's for the user to choose
+    let _second = wallet(&directory, "second", Manifest::new("Second Wallet").protocol("test"));
+
+    let failed = dapp().sign_transaction("transaction").await;
+    assert_eq!(ErrorKind::Cancelled, failed.unwrap_err().kind);
+}
+
+#[test]
+fn rejects_unsafe_ids() {
+    let directory = directory("ids");
+
+    for id in ["", ".hidden", "../escape", "a/b"] {
+        let registered = xdg::service::XdgTransport::register_in(&directory, id, Manifest::new("Wallet"));
+        assert!(registered.is_err(), "{:?} must be rejected", id);
+    }
+}
@@ -0,0 +1,58 @@
+//===------------ support.rs --------------------------------------------===//
+//  Copyright 2021, Tesseract Systems, Inc.
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//===----------------------------------------------------------------------===//
+
+//Loads the vectors in the Rust tests. Shared by the crates as a module:
+//  #[path = "<relative path>/vectors/support.rs"] mod support;
+
+#![allow(dead_code)]
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{de::DeserializeOwned, Serialize};
+
+use tesseract_one::serialize::Serializer;
+
+//the vectors dir is at the root of the workspace, above the crate being tested
+fn dir(set: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .ancestors()
+        .map(|dir| dir.join("vectors"))
+        .find(|dir| dir.is_dir())
+        .expect("can't find the vectors")
+        .join(set)
+}
+
+//the marked message of a set, i.e. vector("test", "sign_transaction.request.json")
+pub fn vector(set: &str, name: &str) -> Vec<u8> {
+    let path = dir(set).join(name);
+    fs::read(&path).unwrap_or_else(|e| panic!("can't read {}: {}", path.display(), e))
+}
+
+//decodes a vector and checks it encodes back to the very same bytes
+pub fn roundtrip<T: Serialize + DeserializeOwned>(set: &str, name: &str) -> T {
+    let data = vector(set, name);
+    let (value, serializer) = Serializer::deserialize_marked::<T>(&data).unwrap();
+
+    assert_eq!(
+        data,
+        serializer.serialize(&value, true).unwrap(),
+        "{} doesn't encode back to the same bytes",
+        name
+    );
+
+    value
+}